@@ -0,0 +1,231 @@
+//! Derive macros for the `diego` crate.
+//!
+//! Use the macros through their re-exports in `diego`, e.g.
+//! `diego::glx::vertex_layout::VertexLayout`, since the generated code refers to `::diego`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, Data, DeriveInput, Expr, Fields, Lit, LitStr, Type};
+
+/// Implements `diego::glx::vertex_layout::VertexLayout` for a `#[repr(C)]` struct.
+///
+/// Every field becomes one vertex attribute. The component count and `VertexDataType` are
+/// derived from the field type, the offset from `offset_of!` and the stride from the size of
+/// the struct, so padding is taken into account.
+///
+/// Supported field types are `f32`, `f64`, `i8`, `u8`, `i16`, `u16`, `i32`, `u32`, arrays
+/// of 1 to 4 of them and matrices stored as arrays of columns, e.g. `[[f32; 4]; 4]` for a
+/// `mat4`, which occupy one location per column. Other types are rejected at compile time.
+///
+/// Fields can be configured with `#[vertex(...)]`:
+/// - `normalized`: integer values are normalized to `[0, 1]` or `[-1, 1]`.
+/// - `name = "aPos"`: the attribute name; defaults to the field name.
+/// - `location = 2`: an explicit shader location; defaults to the next free location.
+#[proc_macro_derive(VertexLayout, attributes(vertex))]
+pub fn derive_vertex_layout(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_vertex_layout(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_vertex_layout(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    if !has_repr_c(input) {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "#[derive(VertexLayout)] requires #[repr(C)] to guarantee the field layout",
+        ));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new(
+                    input.ident.span(),
+                    "#[derive(VertexLayout)] requires a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "#[derive(VertexLayout)] can only be used on structs",
+            ))
+        }
+    };
+
+    let mut attributes = Vec::with_capacity(fields.len());
+    let mut errors: Option<syn::Error> = None;
+    for field in fields {
+        match field_attribute(field) {
+            Ok(attribute) => attributes.push(attribute),
+            Err(e) => match &mut errors {
+                Some(errors) => errors.combine(e),
+                None => errors = Some(e),
+            },
+        }
+    }
+    if let Some(errors) = errors {
+        return Err(errors);
+    }
+
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::diego::glx::vertex_layout::VertexLayout for #name #type_generics #where_clause {
+            fn attributes() -> ::std::vec::Vec<::diego::glx::vertex_attribute::VertexAttribute> {
+                let stride = ::core::mem::size_of::<Self>() as i32;
+                ::std::vec![#(#attributes),*]
+            }
+        }
+    })
+}
+
+fn has_repr_c(input: &DeriveInput) -> bool {
+    let mut repr_c = false;
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("repr")) {
+        let _ = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("C") {
+                repr_c = true;
+            }
+            Ok(())
+        });
+    }
+    repr_c
+}
+
+/// Options of a `#[vertex(...)]` field attribute.
+#[derive(Default)]
+struct VertexOptions {
+    normalized: bool,
+    name: Option<LitStr>,
+    location: Option<u32>,
+}
+
+fn parse_options(field: &syn::Field) -> syn::Result<VertexOptions> {
+    let mut options = VertexOptions::default();
+    for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("vertex")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("normalized") {
+                options.normalized = true;
+            } else if meta.path.is_ident("name") {
+                options.name = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("location") {
+                let value: Expr = meta.value()?.parse()?;
+                options.location = Some(parse_location(&value)?);
+            } else {
+                return Err(meta.error("unknown vertex option; expected `normalized`, `name` or `location`"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(options)
+}
+
+fn parse_location(value: &Expr) -> syn::Result<u32> {
+    if let Expr::Lit(expr) = value {
+        if let Lit::Int(lit) = &expr.lit {
+            return lit.base10_parse();
+        }
+    }
+    Err(syn::Error::new(value.span(), "`location` must be an integer literal"))
+}
+
+fn field_attribute(field: &syn::Field) -> syn::Result<TokenStream2> {
+    let options = parse_options(field)?;
+    let ident = field.ident.as_ref().expect("named field");
+    let (components, columns, data_type) = vertex_type(&field.ty)?;
+
+    let name = options
+        .name
+        .unwrap_or_else(|| LitStr::new(&ident.to_string(), ident.span()));
+    let normalized = options.normalized;
+    let location = match options.location {
+        Some(location) => quote!(::core::option::Option::Some(#location)),
+        None => quote!(::core::option::Option::None),
+    };
+    let data_type = syn::Ident::new(data_type, field.ty.span());
+
+    Ok(quote_spanned! {field.span()=>
+        ::diego::glx::vertex_attribute::VertexAttribute::new(
+            #components,
+            ::diego::glx::vertex_data_type::VertexDataType::#data_type,
+        )
+        .with_name(::std::string::String::from(#name))
+        .with_normalized(#normalized)
+        .with_stride(stride)
+        .with_offset(::core::option::Option::Some(::core::mem::offset_of!(Self, #ident) as u32))
+        .with_location(#location)
+        .with_columns(#columns)
+    })
+}
+
+/// Maps a field type to its component count, column count and `VertexDataType` variant name.
+fn vertex_type(ty: &Type) -> syn::Result<(u8, u8, &'static str)> {
+    if let Some(data_type) = scalar_type(ty) {
+        return Ok((1, 1, data_type));
+    }
+    let Type::Array(array) = ty else {
+        return Err(unsupported_type(ty));
+    };
+    let length = array_length(&array.len).ok_or_else(|| unsupported_type(ty))?;
+
+    if let Some(data_type) = scalar_type(&array.elem) {
+        return match length {
+            1..=4 => Ok((length, 1, data_type)),
+            _ => Err(unsupported_type(ty)),
+        };
+    }
+
+    // Matrices are arrays of columns, e.g. `[[f32; 4]; 4]` for a `mat4`.
+    if let Type::Array(column) = array.elem.as_ref() {
+        let rows = array_length(&column.len);
+        if let (Some(rows @ 2..=4), 2..=4, Some(data_type)) = (rows, length, scalar_type(&column.elem)) {
+            return Ok((rows, length, data_type));
+        }
+    }
+    Err(unsupported_type(ty))
+}
+
+fn array_length(len: &Expr) -> Option<u8> {
+    match len {
+        Expr::Lit(expr) => match &expr.lit {
+            Lit::Int(lit) => lit.base10_parse::<u8>().ok(),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn scalar_type(ty: &Type) -> Option<&'static str> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let ident = path.path.get_ident()?;
+    let data_type = match ident.to_string().as_str() {
+        "f32" => "Float",
+        "f64" => "Double",
+        "i8" => "Byte",
+        "u8" => "UnsignedByte",
+        "i16" => "Short",
+        "u16" => "UnsignedShort",
+        "i32" => "Int",
+        "u32" => "UnsignedInt",
+        _ => return None,
+    };
+    Some(data_type)
+}
+
+fn unsupported_type(ty: &Type) -> syn::Error {
+    syn::Error::new(
+        ty.span(),
+        format!(
+            "unsupported vertex field type `{}`; expected f32, f64, i8, u8, i16, u16, i32, u32, \
+             an array of 1 to 4 of them or a 2x2 to 4x4 matrix like `[[f32; 4]; 4]`",
+            quote!(#ty).to_string().replace(' ', "")
+        ),
+    )
+}
@@ -1,5 +1,6 @@
 use std::mem;
 
+#[allow(unused_macros)]
 macro_rules! determine_vertex_type {
     ([f32; 2]) => {
         println!("Detected: 2-component float (vec2 in GLSL)");
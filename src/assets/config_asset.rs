@@ -0,0 +1,165 @@
+use crate::assets::Reloadable;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Failed to read config file `{path}`: {source}")]
+    ReadError { path: PathBuf, source: std::io::Error },
+    #[error("Invalid config entry in line {line}: `{content}`")]
+    ParseError { line: usize, content: String },
+}
+
+/// A simple `key = value` configuration file.
+///
+/// Empty lines and lines starting with `#` or `;` are ignored. Keys below a `[section]`
+/// header are stored as `section.key`.
+#[derive(Debug, Default)]
+pub struct ConfigAsset {
+    path: Option<PathBuf>,
+    values: HashMap<String, String>,
+}
+
+impl ConfigAsset {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let mut config = Self {
+            path: Some(path.as_ref().to_path_buf()),
+            values: HashMap::new(),
+        };
+        config.reload_from_file()?;
+        Ok(config)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    /// Returns the value of `key` parsed into `T`, or `None` if the key is missing or the
+    /// value cannot be parsed.
+    pub fn get_parsed<T: FromStr>(&self, key: &str) -> Option<T> {
+        self.get(key).and_then(|value| value.parse().ok())
+    }
+
+    pub fn values(&self) -> &HashMap<String, String> {
+        &self.values
+    }
+
+    /// Reads and parses the config file again. The current values are only replaced if the
+    /// whole file could be parsed.
+    pub fn reload_from_file(&mut self) -> Result<(), ConfigError> {
+        if let Some(path) = &self.path {
+            let content = fs::read_to_string(path)
+                .map_err(|source| ConfigError::ReadError { path: path.clone(), source })?;
+            self.values = parse_config(&content)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ConfigAsset {
+    type Err = ConfigError;
+
+    fn from_str(content: &str) -> Result<Self, Self::Err> {
+        Ok(Self {
+            path: None,
+            values: parse_config(content)?,
+        })
+    }
+}
+
+impl Reloadable for ConfigAsset {
+    fn source_files(&self) -> Vec<PathBuf> {
+        self.path.iter().cloned().collect()
+    }
+
+    fn reload(&mut self) -> anyhow::Result<()> {
+        Ok(self.reload_from_file()?)
+    }
+
+    fn asset_name(&self) -> String {
+        match &self.path {
+            Some(path) => path.display().to_string(),
+            None => "<inline config>".to_string(),
+        }
+    }
+}
+
+fn parse_config(content: &str) -> Result<HashMap<String, String>, ConfigError> {
+    let mut values = HashMap::new();
+    let mut section = String::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name.trim().to_string();
+            continue;
+        }
+
+        let (key, value) = line.split_once('=').ok_or_else(|| ConfigError::ParseError {
+            line: index + 1,
+            content: line.to_string(),
+        })?;
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(ConfigError::ParseError { line: index + 1, content: line.to_string() });
+        }
+
+        let key = if section.is_empty() { key.to_string() } else { format!("{}.{}", section, key) };
+        values.insert(key, value.trim().trim_matches('"').to_string());
+    }
+
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config_with_sections() {
+        let config: ConfigAsset =
+            "# window settings\nvsync = true\n\n[light]\ncolor = \"1.0 0.5 0.3\"\nintensity=2.5\n"
+                .parse()
+                .unwrap();
+
+        assert_eq!(config.get("vsync"), Some("true"));
+        assert_eq!(config.get("light.color"), Some("1.0 0.5 0.3"));
+        assert_eq!(config.get_parsed::<f32>("light.intensity"), Some(2.5));
+        assert_eq!(config.get_parsed::<bool>("vsync"), Some(true));
+        assert!(config.get("intensity").is_none());
+    }
+
+    #[test]
+    fn test_parse_config_rejects_invalid_line() {
+        let result = "vsync = true\nthis is not valid\n".parse::<ConfigAsset>();
+        match result {
+            Err(ConfigError::ParseError { line, .. }) => assert_eq!(line, 2),
+            other => panic!("Expected parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_reload_keeps_values_on_error() {
+        let path = std::env::temp_dir().join(format!("diego_config_{}.cfg", std::process::id()));
+        fs::write(&path, "speed = 1\n").unwrap();
+        let mut config = ConfigAsset::from_file(&path).unwrap();
+        assert_eq!(config.get_parsed::<i32>("speed"), Some(1));
+
+        fs::write(&path, "speed 2\n").unwrap();
+        assert!(config.reload().is_err());
+        assert_eq!(config.get_parsed::<i32>("speed"), Some(1));
+
+        fs::write(&path, "speed = 3\n").unwrap();
+        config.reload().unwrap();
+        assert_eq!(config.get_parsed::<i32>("speed"), Some(3));
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,243 @@
+use crate::assets::{AssetHandle, Reloadable};
+use crate::{log_error, log_info, log_warn};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::{Rc, Weak};
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(100);
+
+#[derive(Error, Debug)]
+pub enum HotReloadError {
+    #[error("File watcher error: {0}")]
+    WatcherError(#[from] notify::Error),
+    #[error("Asset `{0}` has no source files to watch")]
+    NoSourceFiles(String),
+}
+
+struct WatchedAsset {
+    files: HashSet<PathBuf>,
+    asset: Weak<RefCell<dyn Reloadable>>,
+}
+
+/// Watches the source files of registered assets and reloads them when they change.
+///
+/// File system events are collected in the background, but reloading only happens inside
+/// `process_changes`, which has to be called from the thread that owns the OpenGL context
+/// (usually once per frame). Several events for the same file within the debounce interval
+/// result in a single reload. Failed reloads are logged and leave the asset untouched.
+///
+/// # Example
+/// ```no_run
+/// use diego::assets::asset_handle;
+/// use diego::assets::hot_reloader::HotReloader;
+/// use diego::glx::shader_program::ShaderProgram;
+///
+/// let shader = asset_handle(ShaderProgram::from_files(
+///     "assets/shaders/light/light_cube.vert",
+///     "assets/shaders/light/light_cube.frag",
+/// ).unwrap());
+///
+/// let mut hot_reloader = HotReloader::new().unwrap();
+/// hot_reloader.watch(&shader).unwrap();
+///
+/// // Once per frame:
+/// hot_reloader.process_changes();
+/// ```
+pub struct HotReloader {
+    watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<notify::Event>>,
+    watched_dirs: HashSet<PathBuf>,
+    assets: Vec<WatchedAsset>,
+    pending: HashMap<PathBuf, Instant>,
+    debounce: Duration,
+}
+
+impl HotReloader {
+    pub fn new() -> Result<Self, HotReloadError> {
+        let (sender, events) = channel();
+        let watcher = notify::recommended_watcher(sender)?;
+        Ok(Self {
+            watcher,
+            events,
+            watched_dirs: HashSet::new(),
+            assets: Vec::new(),
+            pending: HashMap::new(),
+            debounce: DEFAULT_DEBOUNCE,
+        })
+    }
+
+    /// Sets how long a file must stay unchanged before it is reloaded.
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Registers an asset for hot reloading.
+    ///
+    /// The reloader only keeps a weak reference, so dropping the last handle stops watching
+    /// the asset.
+    pub fn watch<T: Reloadable + 'static>(&mut self, asset: &AssetHandle<T>)
+        -> Result<(), HotReloadError>
+    {
+        let files = asset.borrow().source_files();
+        if files.is_empty() {
+            return Err(HotReloadError::NoSourceFiles(asset.borrow().asset_name()));
+        }
+
+        let files = self.watch_files(&files)?;
+        let asset: Rc<RefCell<dyn Reloadable>> = asset.clone();
+        self.assets.push(WatchedAsset { files, asset: Rc::downgrade(&asset) });
+        Ok(())
+    }
+
+    /// Returns the number of assets that are still alive and being watched.
+    pub fn watched_asset_count(&self) -> usize {
+        self.assets.iter().filter(|watched| watched.asset.strong_count() > 0).count()
+    }
+
+    /// Reloads all assets whose source files changed since the last call.
+    ///
+    /// # Returns
+    /// The number of assets that were reloaded successfully.
+    pub fn process_changes(&mut self) -> usize {
+        self.collect_events();
+        self.assets.retain(|watched| watched.asset.strong_count() > 0);
+
+        let now = Instant::now();
+        let changed: HashSet<PathBuf> = self.pending
+            .iter()
+            .filter(|(_, &changed_at)| now.duration_since(changed_at) >= self.debounce)
+            .map(|(path, _)| path.clone())
+            .collect();
+        if changed.is_empty() {
+            return 0;
+        }
+        self.pending.retain(|path, _| !changed.contains(path));
+
+        let mut reloaded = 0;
+        let mut new_files = Vec::new();
+        for (index, watched) in self.assets.iter().enumerate() {
+            if watched.files.is_disjoint(&changed) {
+                continue;
+            }
+            let Some(asset) = watched.asset.upgrade() else {
+                continue;
+            };
+            let Ok(mut asset) = asset.try_borrow_mut() else {
+                // The asset is in use right now; try again on the next call.
+                for path in watched.files.intersection(&changed) {
+                    self.pending.insert(path.clone(), now);
+                }
+                continue;
+            };
+
+            match asset.reload() {
+                Ok(()) => {
+                    log_info!("Reloaded asset `{}`", asset.asset_name());
+                    reloaded += 1;
+                }
+                Err(e) => log_error!("Failed to reload asset `{}`: {}", asset.asset_name(), e),
+            }
+            // The set of source files may change with the new contents (e.g. includes).
+            new_files.push((index, asset.source_files()));
+        }
+
+        for (index, files) in new_files {
+            match self.watch_files(&files) {
+                Ok(files) => self.assets[index].files = files,
+                Err(e) => log_warn!("Failed to watch asset files: {}", e),
+            }
+        }
+        reloaded
+    }
+
+    fn collect_events(&mut self) {
+        loop {
+            match self.events.try_recv() {
+                Ok(Ok(event)) => {
+                    if matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_)) {
+                        let now = Instant::now();
+                        for path in &event.paths {
+                            self.pending.insert(normalize_path(path), now);
+                        }
+                    }
+                }
+                Ok(Err(e)) => log_warn!("File watcher error: {}", e),
+                Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => break,
+            }
+        }
+    }
+
+    /// Watches the parent directories of the given files and returns their normalized paths.
+    ///
+    /// Directories are watched instead of the files themselves, because many editors save by
+    /// writing a new file and renaming it over the old one.
+    fn watch_files(&mut self, files: &[PathBuf]) -> Result<HashSet<PathBuf>, HotReloadError> {
+        let mut normalized = HashSet::new();
+        for file in files {
+            let file = normalize_path(file);
+            if let Some(dir) = file.parent() {
+                if !self.watched_dirs.contains(dir) {
+                    self.watcher.watch(dir, RecursiveMode::NonRecursive)?;
+                    self.watched_dirs.insert(dir.to_path_buf());
+                }
+            }
+            normalized.insert(file);
+        }
+        Ok(normalized)
+    }
+}
+
+/// Returns the canonical form of `path`. Falls back to canonicalizing only the parent
+/// directory if the file itself does not exist (e.g. in the middle of an atomic save).
+fn normalize_path(path: &Path) -> PathBuf {
+    if let Ok(canonical) = fs::canonicalize(path) {
+        return canonical;
+    }
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => {
+            let parent = if parent.as_os_str().is_empty() { Path::new(".") } else { parent };
+            fs::canonicalize(parent).map(|dir| dir.join(name)).unwrap_or_else(|_| path.to_path_buf())
+        }
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::asset_handle;
+    use crate::assets::config_asset::ConfigAsset;
+    use std::thread;
+
+    #[test]
+    fn test_reloads_changed_config() {
+        let dir = std::env::temp_dir().join(format!("diego_hot_reload_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("settings.cfg");
+        fs::write(&path, "fov = 60\n").unwrap();
+
+        let config = asset_handle(ConfigAsset::from_file(&path).unwrap());
+        let mut hot_reloader = HotReloader::new().unwrap().with_debounce(Duration::from_millis(10));
+        hot_reloader.watch(&config).unwrap();
+        assert_eq!(hot_reloader.watched_asset_count(), 1);
+
+        fs::write(&path, "fov = 90\n").unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while config.borrow().get_parsed::<i32>("fov") != Some(90) && Instant::now() < deadline {
+            hot_reloader.process_changes();
+            thread::sleep(Duration::from_millis(20));
+        }
+        assert_eq!(config.borrow().get_parsed::<i32>("fov"), Some(90));
+
+        drop(config);
+        assert_eq!(hot_reloader.watched_asset_count(), 0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
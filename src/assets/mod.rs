@@ -0,0 +1,35 @@
+//! Loading and hot reloading of file based assets.
+//!
+//! Assets that can be rebuilt from their source files implement `Reloadable` and are shared
+//! through an `AssetHandle`. A `HotReloader` watches the source files of registered assets and
+//! reloads them in place, so every holder of the handle sees the new version.
+
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+pub mod config_asset;
+pub mod hot_reloader;
+
+/// A shared, mutable handle to an asset. Reloading replaces the asset contents behind the
+/// handle, so existing clones stay valid.
+pub type AssetHandle<T> = Rc<RefCell<T>>;
+
+/// Wraps an asset into a new `AssetHandle`.
+pub fn asset_handle<T>(asset: T) -> AssetHandle<T> {
+    Rc::new(RefCell::new(asset))
+}
+
+/// A trait for assets that can be rebuilt from the files they were loaded from.
+///
+/// # Methods
+/// - `source_files`: Returns every file the asset depends on; a change to any of them
+///   triggers a reload.
+/// - `reload`: Rebuilds the asset in place. When reloading fails, the implementation must
+///   keep the previous state usable and return the error.
+/// - `asset_name`: A human readable name used in log messages.
+pub trait Reloadable {
+    fn source_files(&self) -> Vec<PathBuf>;
+    fn reload(&mut self) -> anyhow::Result<()>;
+    fn asset_name(&self) -> String;
+}
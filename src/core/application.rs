@@ -1,12 +1,17 @@
 use crate::core::application_context::{ApplicationContextImpl, SharedApplicationContext};
+use crate::core::diego_runtime::{install_log_panic_hook, shutdown_logging};
 use crate::core::main_loop::{DefaultMainLoop, SharedApplicationMainLoop};
 use crate::display::window::{GLWindow, Window, WindowSettings, WinitWindow};
 use crate::events::event::Event;
-use crate::events::event::Event::WindowCloseRequested;
+use crate::events::event::Event::{WindowCloseRequested, WindowResized};
+use crate::gl::capabilities::GpuCapabilities;
 use crate::gl::color::Color;
 use crate::gl::rendering::clear;
 use crate::gl::setup::clear_color;
-use glutin::event::KeyboardInput;
+use crate::gl::viewport::set_framebuffer_size;
+use crate::display::types::Size;
+use crate::{log_debug, log_fatal, log_info};
+use glutin::event::{KeyboardInput, WindowEvent};
 use glutin::event::VirtualKeyCode;
 use glutin::event_loop::{ControlFlow, EventLoop};
 
@@ -44,24 +49,48 @@ impl Application {
     pub fn run(&mut self) {
         let context = self.application_context.clone();
         self.running = true;
+        install_log_panic_hook();
 
         let main_loop_clone = self.main_loop.clone();
 
         // Create window
         let event_loop = EventLoop::new();
         let window_settings = self.application_context.borrow().window_settings.clone();
-        let mut window = WinitWindow::create(window_settings.clone(), &event_loop);
+        let mut window = match WinitWindow::create(window_settings.clone(), &event_loop) {
+            Ok(window) => window,
+            Err(e) => {
+                log_fatal!("{}", e);
+                panic!("{}", e);
+            }
+        };
+        let (min_version, max_version) = window_settings.get_gl_version_range();
+        log_info!("Created {} (requested {} {} to {})",
+            window.context_info(), window_settings.get_api(), min_version, max_version);
+        log_debug!("GL_VERSION: {}", window.context_info().version_string);
+        self.application_context.borrow_mut().context_info = Some(window.context_info().clone());
+
+        let gpu_capabilities = GpuCapabilities::query();
+        gpu_capabilities.log();
+        self.application_context.borrow_mut().gpu_capabilities = Some(gpu_capabilities);
+        let framebuffer_size = window.framebuffer_size();
+        update_framebuffer_size(&mut window, framebuffer_size, &context);
         prepare_window(&mut window, &window_settings);
 
         // Start event loop
         let mut event_collection  = Vec::<Event>::new();
         event_loop.run(move |event, _, control_flow| {
             *control_flow = ControlFlow::Poll;
+            if let glutin::event::Event::LoopDestroyed = event {
+                // The event loop exits the process afterwards.
+                shutdown_logging();
+                return;
+            }
 
             // Update delta time
             let delta_time= context.borrow_mut().delta_time.update();
 
             // Handle events
+            handle_resize(&mut event_collection, &event, &mut window, &context);
             handle_event(&mut event_collection, &event, control_flow, &context);
 
             main_loop_clone.borrow_mut().on_update(delta_time);
@@ -99,14 +128,46 @@ fn prepare_window(window: &mut WinitWindow, settings: &WindowSettings) {
     }
 }
 
+fn handle_resize(event_collection: &mut Vec<Event>,
+                 event: &glutin::event::Event<()>,
+                 window: &mut WinitWindow,
+                 context: &SharedApplicationContext) {
+    let framebuffer_size = match event {
+        glutin::event::Event::WindowEvent { event: WindowEvent::Resized(size), .. } => *size,
+        glutin::event::Event::WindowEvent { event: WindowEvent::ScaleFactorChanged { new_inner_size, .. }, .. } => {
+            **new_inner_size
+        }
+        _ => return,
+    };
+    // Minimized windows report a size of 0; keep the last valid size until they are restored.
+    if framebuffer_size.width == 0 || framebuffer_size.height == 0 {
+        return;
+    }
+    let framebuffer_size = Size::new(framebuffer_size.width, framebuffer_size.height);
+    update_framebuffer_size(window, framebuffer_size, context);
+    event_collection.push(WindowResized { framebuffer_size, scale_factor: window.scale_factor() });
+}
+
+/// Resizes the default framebuffer and the viewport and stores the new size in the context.
+fn update_framebuffer_size(window: &mut WinitWindow, framebuffer_size: Size, context: &SharedApplicationContext) {
+    window.resize(framebuffer_size);
+    set_framebuffer_size(framebuffer_size);
+
+    let mut context = context.borrow_mut();
+    context.framebuffer_size = framebuffer_size;
+    context.scale_factor = window.scale_factor();
+    let logical_size = window.logical_size();
+    log_debug!("Framebuffer resized to {}x{} pixels (logical size {}x{}, scale factor {})",
+        framebuffer_size.width, framebuffer_size.height, logical_size.width, logical_size.height,
+        context.scale_factor);
+}
+
 fn handle_event(event_collection: &mut Vec<Event>,
                 event: &glutin::event::Event<()>,
                 control_flow: &ControlFlow,
                 context: &SharedApplicationContext) {
-    match event {
-        glutin::event::Event::WindowEvent { event, .. } =>
-            handle_window_event(event_collection, event, control_flow, context),
-        _ => {}
+    if let glutin::event::Event::WindowEvent { event, .. } = event {
+        handle_window_event(event_collection, event, control_flow, context);
     }
 }
 
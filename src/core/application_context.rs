@@ -2,26 +2,57 @@ use std::cell::{RefCell};
 use std::fmt::{Debug, Formatter};
 use std::rc::Rc;
 use crate::core::delta_time::DeltaTime;
+use crate::display::gl_context::ContextInfo;
+use crate::display::types::Size;
 use crate::display::window::WindowSettings;
 use crate::events::event::Event;
+use crate::gl::capabilities::GpuCapabilities;
 
 pub trait ApplicationContext {
     fn events(&self) -> &[Event];
     fn delta_time(&self) -> f64;
     fn window_settings(&self) -> &WindowSettings;
+    /// Returns the OpenGL context that was actually created, or `None` before the window
+    /// exists.
+    fn context_info(&self) -> Option<&ContextInfo>;
+    /// Returns the limits and features of the GPU, or `None` before the window exists.
+    fn gpu_capabilities(&self) -> Option<&GpuCapabilities>;
+    /// Returns the size of the default framebuffer in pixels, which is larger than the
+    /// window size in `window_settings` on HiDPI displays.
+    fn framebuffer_size(&self) -> Size;
+    /// Returns the ratio of framebuffer to logical pixels.
+    fn scale_factor(&self) -> f64;
     fn exit(&mut self);
     fn should_exit(&self) -> bool;
 }
 
-#[derive(Default)]
 pub(crate) struct ApplicationContextImpl {
     pub delta_time: DeltaTime,
     pub should_exit: bool,
     pub window_settings: WindowSettings,
+    pub context_info: Option<ContextInfo>,
+    pub gpu_capabilities: Option<GpuCapabilities>,
+    pub framebuffer_size: Size,
+    pub scale_factor: f64,
     pub events: Vec<Event>,
 }
 
-pub type SharedApplicationContext = Rc<RefCell<ApplicationContextImpl>>;
+pub(crate) type SharedApplicationContext = Rc<RefCell<ApplicationContextImpl>>;
+
+impl Default for ApplicationContextImpl {
+    fn default() -> Self {
+        Self {
+            delta_time: DeltaTime::default(),
+            should_exit: false,
+            window_settings: WindowSettings::default(),
+            context_info: None,
+            gpu_capabilities: None,
+            framebuffer_size: Size::default(),
+            scale_factor: 1.0,
+            events: Vec::new(),
+        }
+    }
+}
 
 impl ApplicationContextImpl {
     pub fn new() -> SharedApplicationContext {
@@ -48,6 +79,22 @@ impl ApplicationContext for ApplicationContextImpl {
         &self.window_settings
     }
 
+    fn context_info(&self) -> Option<&ContextInfo> {
+        self.context_info.as_ref()
+    }
+
+    fn gpu_capabilities(&self) -> Option<&GpuCapabilities> {
+        self.gpu_capabilities.as_ref()
+    }
+
+    fn framebuffer_size(&self) -> Size {
+        self.framebuffer_size
+    }
+
+    fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
     fn exit(&mut self) {
         self.should_exit = true;
     }
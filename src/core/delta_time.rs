@@ -14,11 +14,9 @@ use std::time::Instant;
 /// # Example
 ///
 /// ```no_run
-/// let mut delta_time = DeltaTime {
-///     previous_time: Instant::now(),
-///     actual: 0.0,
-/// };
+/// use diego::core::delta_time::DeltaTime;
 ///
+/// let mut delta_time = DeltaTime::default();
 /// ```
 ///
 /// Call delta_time.update() to calculate time deltas during your game loop or application update.
@@ -55,6 +53,8 @@ impl DeltaTime {
     ///
     /// # Example
     /// ```
+    /// # use diego::core::delta_time::DeltaTime;
+    /// # let mut delta_time = DeltaTime::default();
     /// let elapsed_time = delta_time.update();
     /// println!("Elapsed time: {} seconds", elapsed_time);
     /// ```
@@ -70,8 +70,8 @@ impl DeltaTime {
     }
 }
 
-impl Into<f64> for DeltaTime { 
-    fn into(self) -> f64 {
-        self.actual
+impl From<DeltaTime> for f64 {
+    fn from(value: DeltaTime) -> Self {
+        value.actual
     }
 }
@@ -1,7 +1,12 @@
-use crate::logging::log_manager::{LogError, LogManager};
+use crate::logging::log_manager::{LogError, LogManager, LogMessage};
+use crate::assets::config_asset::ConfigAsset;
+use crate::logging::log_filter::{LogFilter, LOG_FILTER_ENV_VAR};
+use crate::logging::log_queue::OverflowPolicy;
 use crate::logging::{LogLevel, LogTarget};
 use lazy_static::lazy_static;
-use std::sync::{Arc, Mutex};
+use std::env;
+use std::panic;
+use std::sync::{Arc, Mutex, Once};
 use crate::core::diego_runtime_internal::DiegoRuntimeInternal;
 
 lazy_static! {
@@ -21,6 +26,8 @@ lazy_static! {
 ///
 /// # Example
 /// ```no_run
+/// use diego::core::diego_runtime::set_check_opengl_errors;
+///
 /// set_check_opengl_errors(true); // Enables OpenGL error checking.
 /// set_check_opengl_errors(false); // Disables OpenGL error checking.
 /// ```
@@ -74,7 +81,199 @@ pub fn clear_all_loggers() -> u32 {
 }
 
 pub fn add_log_message<T: AsRef<str>>(log_level: LogLevel, log_message: T) {
-    let message = log_message.as_ref().to_owned();
+    push_log_message(LogMessage::new(log_level, log_message));
+}
+
+/// Queues a message built by the caller, e.g. by the `log_*!` macros, for all log targets.
+///
+/// The manager's lock is released before pushing: with `OverflowPolicy::Block` the push may
+/// wait for the worker, and the worker takes the lock when a target logs itself.
+pub fn push_log_message(log_message: LogMessage) {
+    let queue_handle = LOG_MANAGER.lock().unwrap().queue_handle();
+    if let Some(queue_handle) = queue_handle {
+        queue_handle.push_message(log_message);
+    }
+}
+
+/// Sets the filter for all loggers that have no filter of their own. It replaces the levels
+/// of the log targets; `None` lets the targets filter by their levels again.
+///
+/// # Example
+/// ```no_run
+/// use diego::core::diego_runtime::set_log_filter;
+///
+/// set_log_filter(Some("diego::gl=trace,diego::logging=warn,info".parse().unwrap()));
+/// ```
+pub fn set_log_filter(filter: Option<LogFilter>) {
     let mut log_manager = LOG_MANAGER.lock().unwrap();
-    log_manager.add_log_message(log_level, message);
+    log_manager.set_filter(filter);
+}
+
+/// Sets the filter of a logger by name; it also applies if the logger is added later. `None`
+/// falls back to the global filter.
+pub fn set_logger_filter<N: AsRef<str>>(name: N, filter: Option<LogFilter>) -> Result<(), LogError> {
+    let mut log_manager = LOG_MANAGER.lock().unwrap();
+    log_manager.set_logger_filter(name, filter)
+}
+
+/// Sets the global log filter from the `DIEGO_LOG` environment variable, e.g.
+/// `DIEGO_LOG=diego::gl=trace,info`.
+///
+/// # Returns
+/// Whether the variable was set.
+pub fn load_log_filter_from_env() -> Result<bool, LogError> {
+    match env::var(LOG_FILTER_ENV_VAR) {
+        Ok(filter) => {
+            set_log_filter(Some(filter.parse()?));
+            Ok(true)
+        }
+        Err(_) => Ok(false),
+    }
+}
+
+/// Sets the log filters from a config file: `filter` in the `[logging]` section is the global
+/// filter, `filter.<logger>` the filter of a single logger.
+///
+/// ```ini
+/// [logging]
+/// filter = diego::gl=trace,info
+/// filter.file = warn
+/// ```
+///
+/// # Returns
+/// Whether the config contained any filter. Nothing is changed if one of them is invalid.
+pub fn load_log_filter_from_config(config: &ConfigAsset) -> Result<bool, LogError> {
+    let mut filters = Vec::new();
+    for (key, value) in config.values() {
+        if key == "logging.filter" {
+            filters.push((None, value.parse::<LogFilter>()?));
+        } else if let Some(logger) = key.strip_prefix("logging.filter.") {
+            filters.push((Some(logger.to_string()), value.parse()?));
+        }
+    }
+
+    let mut log_manager = LOG_MANAGER.lock().unwrap();
+    let found = !filters.is_empty();
+    for (logger, filter) in filters {
+        match logger {
+            Some(logger) => log_manager.set_logger_filter(logger, Some(filter))?,
+            None => log_manager.set_filter(Some(filter)),
+        }
+    }
+    Ok(found)
+}
+
+/// Sets the number of messages the log queue holds and what happens to messages logged while
+/// it is full. Applies to the running queue right away.
+///
+/// # Example
+/// ```no_run
+/// use diego::core::diego_runtime::set_log_queue_capacity;
+/// use diego::logging::log_queue::OverflowPolicy;
+///
+/// // Never stall a frame because of logging.
+/// set_log_queue_capacity(50_000, OverflowPolicy::DropOldest);
+/// ```
+pub fn set_log_queue_capacity(capacity: usize, overflow_policy: OverflowPolicy) {
+    let mut log_manager = LOG_MANAGER.lock().unwrap();
+    log_manager.set_queue_capacity(capacity, overflow_policy);
+}
+
+/// Waits until all messages logged so far are written and the log targets are flushed.
+pub fn flush_logs() {
+    let queue_handle = LOG_MANAGER.lock().unwrap().queue_handle();
+    if let Some(queue_handle) = queue_handle {
+        queue_handle.flush();
+    }
+}
+
+/// Writes all pending messages and stops the log worker thread; call it before the process
+/// exits. Messages logged afterwards are discarded until a logger is added or removed.
+pub fn shutdown_logging() {
+    let log_queue = LOG_MANAGER.lock().unwrap().take_queue();
+    if let Some(mut log_queue) = log_queue {
+        log_queue.shutdown();
+    }
+}
+
+/// Installs a panic hook that logs the panic as a fatal message and writes all pending
+/// messages before the previous hook runs, so the messages leading up to a panic are not
+/// lost. Installing it more than once has no effect.
+pub fn install_log_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            // The panicking thread may hold the lock; skip logging rather than deadlock.
+            let queue_handle = LOG_MANAGER.try_lock().ok().and_then(|log_manager| log_manager.queue_handle());
+            if let Some(queue_handle) = queue_handle {
+                let location = info.location();
+                queue_handle.push_message(LogMessage::new(LogLevel::Fatal, info.to_string()).with_location(
+                    None,
+                    location.map(|location| location.file().to_string()),
+                    location.map(|location| location.line()),
+                ));
+                queue_handle.flush();
+            }
+            previous_hook(info);
+        }));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log_warn;
+    use crate::logging::log_manager::LogError;
+    use std::any::Any;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    /// Logs a warning of its own for every message it writes.
+    struct EchoTarget;
+
+    impl LogTarget for EchoTarget {
+        fn update(&mut self, _log_target: Box<dyn LogTarget>) -> bool {
+            false
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn target_name(&self) -> &'static str {
+            "EchoTarget"
+        }
+
+        fn is_logging_enabled(&self, _level: LogLevel) -> bool {
+            true
+        }
+
+        fn log(&self, log_message: &LogMessage) -> Result<(), LogError> {
+            if !log_message.message.starts_with("echo") {
+                log_warn!("echo of {}", log_message.message);
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_target_logging_into_a_full_queue_does_not_deadlock() {
+        set_log_queue_capacity(1, OverflowPolicy::Block);
+        add_logger("echo", EchoTarget).unwrap();
+
+        let (done_sender, done_receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            for index in 0..200 {
+                add_log_message(LogLevel::Info, format!("message {}", index));
+            }
+            flush_logs();
+            done_sender.send(()).unwrap();
+        });
+        let result = done_receiver.recv_timeout(Duration::from_secs(10));
+
+        remove_logger("echo").unwrap();
+        set_log_queue_capacity(crate::logging::log_queue::DEFAULT_QUEUE_CAPACITY, OverflowPolicy::Block);
+        assert!(result.is_ok(), "logging deadlocked");
+    }
 }
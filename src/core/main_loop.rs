@@ -56,7 +56,7 @@ pub trait ApplicationMainLoop {
 /// use std::cell::RefCell;
 /// use std::rc::Rc;
 ///
-/// use diego::core::main_loop::ApplicationMainLoop;
+/// use diego::core::main_loop::{ApplicationMainLoop, SharedApplicationMainLoop};
 ///
 /// struct MyMainLoop;
 ///
@@ -80,6 +80,7 @@ pub type SharedApplicationMainLoop = Rc<RefCell<dyn ApplicationMainLoop>>;
 pub struct DefaultMainLoop;
 
 impl DefaultMainLoop {
+    #[allow(clippy::new_ret_no_self)]
     pub(crate) fn new() -> SharedApplicationMainLoop {
         Rc::new(RefCell::new(DefaultMainLoop {}))
     }
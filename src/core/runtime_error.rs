@@ -9,4 +9,6 @@ pub enum RuntimeError {
     InvalidBufferSize(RuntimeInfo),
     #[error("OpenGL error: {0}, error code: {1:?}")]
     OpenGLError(RuntimeInfo, Option<u32>),
+    #[error("Unsupported OpenGL function: {0}")]
+    UnsupportedFunction(RuntimeInfo),
 }
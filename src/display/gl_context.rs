@@ -0,0 +1,312 @@
+use crate::display::window::WindowSettings;
+use crate::gl::state::{get_integer_v, get_string};
+use crate::gl::types::{GlGetParameter, GlStringName};
+use crate::log_debug;
+use glutin::event_loop::EventLoop;
+use glutin::window::WindowBuilder;
+use glutin::{ContextBuilder, CreationError, GlRequest, NotCurrent, WindowedContext};
+use std::fmt::{Display, Formatter};
+use thiserror::Error;
+
+/// Desktop OpenGL versions, newest first.
+const OPENGL_VERSIONS: [GlVersion; 12] = [
+    GlVersion::new(4, 6),
+    GlVersion::new(4, 5),
+    GlVersion::new(4, 4),
+    GlVersion::new(4, 3),
+    GlVersion::new(4, 2),
+    GlVersion::new(4, 1),
+    GlVersion::new(4, 0),
+    GlVersion::new(3, 3),
+    GlVersion::new(3, 2),
+    GlVersion::new(3, 1),
+    GlVersion::new(3, 0),
+    GlVersion::new(2, 1),
+];
+
+/// OpenGL ES versions, newest first.
+const OPENGL_ES_VERSIONS: [GlVersion; 4] = [
+    GlVersion::new(3, 2),
+    GlVersion::new(3, 1),
+    GlVersion::new(3, 0),
+    GlVersion::new(2, 0),
+];
+
+/// The graphics API of the OpenGL context.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GraphicsApi {
+    /// Desktop OpenGL.
+    OpenGl,
+    /// OpenGL ES, e.g. on embedded devices or through ANGLE.
+    OpenGlEs,
+}
+
+impl Display for GraphicsApi {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GraphicsApi::OpenGl => write!(f, "OpenGL"),
+            GraphicsApi::OpenGlEs => write!(f, "OpenGL ES"),
+        }
+    }
+}
+
+impl From<GraphicsApi> for glutin::Api {
+    fn from(value: GraphicsApi) -> Self {
+        match value {
+            GraphicsApi::OpenGl => glutin::Api::OpenGl,
+            GraphicsApi::OpenGlEs => glutin::Api::OpenGlEs,
+        }
+    }
+}
+
+/// The profile of a desktop OpenGL context (3.2 and newer).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GlProfile {
+    /// Only the non-deprecated functionality.
+    Core,
+    /// Includes the deprecated fixed-function pipeline.
+    Compatibility,
+}
+
+impl Display for GlProfile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GlProfile::Core => write!(f, "core"),
+            GlProfile::Compatibility => write!(f, "compatibility"),
+        }
+    }
+}
+
+impl From<GlProfile> for glutin::GlProfile {
+    fn from(value: GlProfile) -> Self {
+        match value {
+            GlProfile::Core => glutin::GlProfile::Core,
+            GlProfile::Compatibility => glutin::GlProfile::Compatibility,
+        }
+    }
+}
+
+/// An OpenGL or OpenGL ES version. Versions are ordered by major, then minor number.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GlVersion {
+    pub major: u8,
+    pub minor: u8,
+}
+
+impl GlVersion {
+    pub const fn new(major: u8, minor: u8) -> Self {
+        Self { major, minor }
+    }
+
+    /// Parses the version from a `GL_VERSION` string, e.g. `4.6 (Core Profile) Mesa 24.0.5`
+    /// or `OpenGL ES 3.2 NVIDIA 550.54`.
+    pub fn parse(version_string: &str) -> Option<Self> {
+        let version = version_string
+            .split_whitespace()
+            .find(|word| word.starts_with(|c: char| c.is_ascii_digit()))?;
+        let mut numbers = version.split('.');
+        let major = numbers.next()?.parse().ok()?;
+        let minor = numbers
+            .next()?
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .collect::<String>()
+            .parse()
+            .ok()?;
+        Some(Self::new(major, minor))
+    }
+}
+
+impl Display for GlVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+impl From<(u8, u8)> for GlVersion {
+    fn from(value: (u8, u8)) -> Self {
+        Self::new(value.0, value.1)
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ContextError {
+    #[error("No {api} version between {min} and {max} is known")]
+    EmptyVersionRange { api: GraphicsApi, min: GlVersion, max: GlVersion },
+    #[error("Failed to create an {api} context with a version between {min} and {max}: {source}")]
+    CreationFailed {
+        api: GraphicsApi,
+        min: GlVersion,
+        max: GlVersion,
+        source: CreationError,
+    },
+}
+
+/// Describes the OpenGL context that was actually created, which may differ from the
+/// requested one, e.g. an older version after falling back or fewer MSAA samples.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextInfo {
+    /// The graphics API of the context.
+    pub api: GraphicsApi,
+    /// The version reported by the driver.
+    pub version: GlVersion,
+    /// The version that was requested when the context was created.
+    pub requested_version: GlVersion,
+    /// The profile of a desktop OpenGL 3.2+ context; `None` for older or OpenGL ES contexts.
+    pub profile: Option<GlProfile>,
+    /// Whether the context was created with the debug flag.
+    pub debug: bool,
+    /// The number of MSAA samples; `0` if multisampling is disabled.
+    pub samples: u16,
+    /// The number of depth buffer bits.
+    pub depth_bits: u8,
+    /// The number of stencil buffer bits.
+    pub stencil_bits: u8,
+    /// Whether the default framebuffer is sRGB capable.
+    pub srgb: bool,
+    /// The full `GL_VERSION` string, including vendor specific details.
+    pub version_string: String,
+}
+
+impl Display for ContextInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.api, self.version)?;
+        if let Some(profile) = self.profile {
+            write!(f, " {}", profile)?;
+        }
+        if self.debug {
+            write!(f, " debug")?;
+        }
+        write!(f, " context, depth {}, stencil {}", self.depth_bits, self.stencil_bits)?;
+        if self.samples > 0 {
+            write!(f, ", {}x MSAA", self.samples)?;
+        }
+        if self.srgb {
+            write!(f, ", sRGB")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the versions to try for `api`, from `max` down to `min`.
+pub fn version_ladder(api: GraphicsApi, min: GlVersion, max: GlVersion) -> Vec<GlVersion> {
+    let versions: &[GlVersion] = match api {
+        GraphicsApi::OpenGl => &OPENGL_VERSIONS,
+        GraphicsApi::OpenGlEs => &OPENGL_ES_VERSIONS,
+    };
+    versions
+        .iter()
+        .copied()
+        .filter(|version| (min..=max).contains(version))
+        .collect()
+}
+
+/// Creates a window with an OpenGL context matching `settings`.
+///
+/// The versions of the requested range are tried from newest to oldest, until the driver
+/// accepts one. The context is not current yet.
+///
+/// # Returns
+/// The context and the version it was requested with.
+pub(crate) fn create_windowed_context(
+    settings: &WindowSettings,
+    window_builder: WindowBuilder,
+    event_loop: &EventLoop<()>,
+) -> Result<(WindowedContext<NotCurrent>, GlVersion), ContextError> {
+    let api = settings.get_api();
+    let (min, max) = settings.get_gl_version_range();
+    let mut last_error = None;
+    for version in version_ladder(api, min, max) {
+        let mut builder = ContextBuilder::new()
+            .with_gl(GlRequest::Specific(api.into(), (version.major, version.minor)))
+            .with_gl_debug_flag(settings.get_debug_context())
+            .with_double_buffer(Some(true))
+            .with_vsync(settings.get_vsync())
+            .with_multisampling(settings.get_samples())
+            .with_depth_buffer(settings.get_depth_bits())
+            .with_stencil_buffer(settings.get_stencil_bits())
+            .with_srgb(settings.get_srgb());
+        if api == GraphicsApi::OpenGl && version >= GlVersion::new(3, 2) {
+            builder = builder.with_gl_profile(settings.get_profile().into());
+        }
+
+        match builder.build_windowed(window_builder.clone(), event_loop) {
+            Ok(context) => return Ok((context, version)),
+            Err(e) => {
+                log_debug!("Could not create an {} {} context: {}", api, version, e);
+                last_error = Some(e);
+            }
+        }
+    }
+
+    Err(match last_error {
+        Some(source) => ContextError::CreationFailed { api, min, max, source },
+        None => ContextError::EmptyVersionRange { api, min, max },
+    })
+}
+
+/// Queries the properties of the current context. The OpenGL function pointers must have
+/// been loaded.
+pub(crate) fn query_context_info(
+    api: GraphicsApi,
+    requested_version: GlVersion,
+    pixel_format: &glutin::PixelFormat,
+) -> ContextInfo {
+    let version_string = get_string(GlStringName::Version);
+    let version = GlVersion::parse(&version_string).unwrap_or(requested_version);
+
+    let profile = (api == GraphicsApi::OpenGl && version >= GlVersion::new(3, 2)).then(|| {
+        let mask = get_integer_v(GlGetParameter::ContextProfileMask) as u32;
+        if mask & gl::CONTEXT_COMPATIBILITY_PROFILE_BIT != 0 {
+            GlProfile::Compatibility
+        } else {
+            GlProfile::Core
+        }
+    });
+    let has_context_flags = match api {
+        GraphicsApi::OpenGl => version >= GlVersion::new(3, 0),
+        GraphicsApi::OpenGlEs => version >= GlVersion::new(3, 2),
+    };
+    let debug = has_context_flags
+        && get_integer_v(GlGetParameter::ContextFlags) as u32 & gl::CONTEXT_FLAG_DEBUG_BIT != 0;
+
+    ContextInfo {
+        api,
+        version,
+        requested_version,
+        profile,
+        debug,
+        samples: pixel_format.multisampling.unwrap_or(0),
+        depth_bits: pixel_format.depth_bits,
+        stencil_bits: pixel_format.stencil_bits,
+        srgb: pixel_format.srgb,
+        version_string,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_ladder() {
+        let ladder = version_ladder(GraphicsApi::OpenGl, GlVersion::new(3, 3), GlVersion::new(4, 5));
+        assert_eq!(ladder.first(), Some(&GlVersion::new(4, 5)));
+        assert_eq!(ladder.last(), Some(&GlVersion::new(3, 3)));
+        assert_eq!(ladder.len(), 7);
+
+        let ladder = version_ladder(GraphicsApi::OpenGlEs, GlVersion::new(3, 0), GlVersion::new(4, 6));
+        assert_eq!(ladder, vec![GlVersion::new(3, 2), GlVersion::new(3, 1), GlVersion::new(3, 0)]);
+
+        assert!(version_ladder(GraphicsApi::OpenGl, GlVersion::new(4, 6), GlVersion::new(3, 3)).is_empty());
+    }
+
+    #[test]
+    fn test_parse_version_string() {
+        assert_eq!(GlVersion::parse("4.6 (Core Profile) Mesa 24.0.5"), Some(GlVersion::new(4, 6)));
+        assert_eq!(GlVersion::parse("3.3.0 NVIDIA 550.54.14"), Some(GlVersion::new(3, 3)));
+        assert_eq!(GlVersion::parse("OpenGL ES 3.2 Mesa 23.2.1"), Some(GlVersion::new(3, 2)));
+        assert_eq!(GlVersion::parse("OpenGL ES 2.0"), Some(GlVersion::new(2, 0)));
+        assert_eq!(GlVersion::parse("unknown"), None);
+    }
+}
@@ -1,2 +1,3 @@
 pub mod types;
+pub mod gl_context;
 pub mod window;
@@ -15,7 +15,7 @@
 ///
 /// * `width`: The width of the window in pixels.
 /// * `height`: The height of the window in pixels.
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
 pub struct Size {
     /// The width of the display in pixels.
     pub width: u32,
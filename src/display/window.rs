@@ -1,26 +1,59 @@
+use crate::display::gl_context::{
+    create_windowed_context, query_context_info, ContextError, ContextInfo, GlProfile, GlVersion,
+    GraphicsApi,
+};
 use crate::display::types::Size;
 use glutin::event_loop::EventLoop;
 use glutin::window::WindowBuilder;
-use glutin::{Api, ContextBuilder, ContextWrapper, GlRequest, PossiblyCurrent};
+use glutin::{ContextWrapper, PossiblyCurrent};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use glutin::dpi::LogicalSize;
+use glutin::dpi::{LogicalSize, PhysicalSize};
 
 const DEFAULT_WINDOW_TITLE: &str = "DIEGO";
 const DEFAULT_WINDOW_WIDTH: u32 = 800;
 const DEFAULT_WINDOW_HEIGHT: u32 = 600;
-const OPENGL_API_VERSION: GlRequest = GlRequest::Specific(Api::OpenGl, (4, 5));
+const DEFAULT_MIN_GL_VERSION: GlVersion = GlVersion::new(3, 3);
+const DEFAULT_MAX_GL_VERSION: GlVersion = GlVersion::new(4, 6);
+const DEFAULT_DEPTH_BITS: u8 = 24;
+const DEFAULT_STENCIL_BITS: u8 = 8;
 
 pub trait Window {
     fn show(&self);
     fn close(&self);
     fn center(&self);
+    /// Returns the size of the drawable area in logical pixels.
+    fn logical_size(&self) -> Size;
+    /// Returns the size of the default framebuffer in pixels. On HiDPI displays this is the
+    /// logical size multiplied by the scale factor.
+    fn framebuffer_size(&self) -> Size;
+    /// Returns the ratio of framebuffer to logical pixels, e.g. `2.0` on a Retina display.
+    fn scale_factor(&self) -> f64;
 }
 
 pub trait GLWindow : Window {
     fn swap_buffers(&mut self);
+    /// Resizes the default framebuffer; required on some platforms after the window size
+    /// changed.
+    fn resize(&mut self, framebuffer_size: Size);
 }
 
+/// Settings of the application window and its OpenGL context.
+///
+/// By default the newest desktop OpenGL core profile context between 3.3 and 4.6 is created.
+/// If the driver rejects a version, the next older one in the range is tried; the context that
+/// was actually created is reported by `ApplicationContext::context_info`.
+///
+/// # Example
+/// ```no_run
+/// use diego::display::gl_context::{GlVersion, GraphicsApi};
+/// use diego::display::window::WindowSettings;
+///
+/// let settings = WindowSettings::default()
+///     .with_api(GraphicsApi::OpenGlEs)
+///     .with_gl_version_range(GlVersion::new(3, 0), GlVersion::new(3, 2))
+///     .with_samples(4);
+/// ```
 #[derive(Debug, Clone)]
 pub struct WindowSettings {
     title: String,
@@ -29,6 +62,15 @@ pub struct WindowSettings {
     resizable: bool,
     visible: bool,
     exit_on_esc: bool,
+    api: GraphicsApi,
+    min_gl_version: GlVersion,
+    max_gl_version: GlVersion,
+    profile: GlProfile,
+    debug_context: bool,
+    samples: u16,
+    depth_bits: u8,
+    stencil_bits: u8,
+    srgb: bool,
 }
 
 impl Default for WindowSettings {
@@ -40,6 +82,15 @@ impl Default for WindowSettings {
             resizable: false,
             visible: true,
             exit_on_esc: true,
+            api: GraphicsApi::OpenGl,
+            min_gl_version: DEFAULT_MIN_GL_VERSION,
+            max_gl_version: DEFAULT_MAX_GL_VERSION,
+            profile: GlProfile::Core,
+            debug_context: false,
+            samples: 0,
+            depth_bits: DEFAULT_DEPTH_BITS,
+            stencil_bits: DEFAULT_STENCIL_BITS,
+            srgb: true,
         }
     }
 }
@@ -75,6 +126,63 @@ impl WindowSettings {
         self
     }
 
+    /// Selects desktop OpenGL or OpenGL ES. Remember to adjust the version range, since the
+    /// default range only contains desktop OpenGL versions.
+    pub fn with_api(mut self, api: GraphicsApi) -> Self {
+        self.api = api;
+        self
+    }
+
+    /// Sets the range of acceptable context versions; the newest one the driver supports is
+    /// used.
+    pub fn with_gl_version_range<V: Into<GlVersion>>(mut self, min: V, max: V) -> Self {
+        self.min_gl_version = min.into();
+        self.max_gl_version = max.into();
+        self
+    }
+
+    /// Requests exactly one context version, without fallback.
+    pub fn with_gl_version<V: Into<GlVersion>>(mut self, version: V) -> Self {
+        let version = version.into();
+        self.min_gl_version = version;
+        self.max_gl_version = version;
+        self
+    }
+
+    /// Sets the profile of desktop OpenGL 3.2+ contexts; ignored for other contexts.
+    pub fn with_profile(mut self, profile: GlProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Requests a debug context, which makes the driver report detailed errors and warnings.
+    pub fn with_debug_context(mut self, debug_context: bool) -> Self {
+        self.debug_context = debug_context;
+        self
+    }
+
+    /// Sets the number of MSAA samples; `0` disables multisampling. Must be a power of two.
+    pub fn with_samples(mut self, samples: u16) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    pub fn with_depth_bits(mut self, depth_bits: u8) -> Self {
+        self.depth_bits = depth_bits;
+        self
+    }
+
+    pub fn with_stencil_bits(mut self, stencil_bits: u8) -> Self {
+        self.stencil_bits = stencil_bits;
+        self
+    }
+
+    /// Requests an sRGB capable default framebuffer.
+    pub fn with_srgb(mut self, srgb: bool) -> Self {
+        self.srgb = srgb;
+        self
+    }
+
     pub fn get_title(&self) -> &str {
         self.title.as_str()
     }
@@ -94,15 +202,53 @@ impl WindowSettings {
     pub fn get_exit_on_esc(&self) -> bool {
         self.exit_on_esc
     }
+
+    pub fn get_vsync(&self) -> bool {
+        self.vsync
+    }
+
+    pub fn get_api(&self) -> GraphicsApi {
+        self.api
+    }
+
+    /// Returns the minimum and maximum acceptable context version.
+    pub fn get_gl_version_range(&self) -> (GlVersion, GlVersion) {
+        (self.min_gl_version, self.max_gl_version)
+    }
+
+    pub fn get_profile(&self) -> GlProfile {
+        self.profile
+    }
+
+    pub fn get_debug_context(&self) -> bool {
+        self.debug_context
+    }
+
+    pub fn get_samples(&self) -> u16 {
+        self.samples
+    }
+
+    pub fn get_depth_bits(&self) -> u8 {
+        self.depth_bits
+    }
+
+    pub fn get_stencil_bits(&self) -> u8 {
+        self.stencil_bits
+    }
+
+    pub fn get_srgb(&self) -> bool {
+        self.srgb
+    }
 }
 
 pub(crate) struct WinitWindow {
     context:  ContextWrapper<PossiblyCurrent, glutin::window::Window>,
+    context_info: ContextInfo,
     close_requested: Arc<AtomicBool>,
 }
 
 impl WinitWindow {
-    pub(crate) fn create(settings: WindowSettings, event_loop: &EventLoop<()>) -> Self {
+    pub(crate) fn create(settings: WindowSettings, event_loop: &EventLoop<()>) -> Result<Self, ContextError> {
 
         // winit 0.30.5 stuff *not working*
         // ==================
@@ -129,12 +275,8 @@ impl WinitWindow {
             .with_resizable(settings.resizable)
             .with_visible(false);
 
-        let windowed_context = ContextBuilder::new()
-            .with_double_buffer(Some(true))
-            .with_gl(OPENGL_API_VERSION)
-            .with_vsync(settings.vsync)
-            .build_windowed(window_builder, &event_loop)
-            .unwrap();
+        let (windowed_context, requested_version) =
+            create_windowed_context(&settings, window_builder, event_loop)?;
 
         // Make the context current
         let windowed_context : ContextWrapper<PossiblyCurrent, glutin::window::Window> =
@@ -143,9 +285,17 @@ impl WinitWindow {
         // Load the OpenGL function pointers using the window's current context.
         gl::load_with(|s| windowed_context.get_proc_address(s));
 
-        WinitWindow {
-            context: windowed_context, close_requested: Arc::new(AtomicBool::new(false))
-        }
+        let context_info =
+            query_context_info(settings.api, requested_version, &windowed_context.get_pixel_format());
+
+        Ok(WinitWindow {
+            context: windowed_context, context_info, close_requested: Arc::new(AtomicBool::new(false))
+        })
+    }
+
+    /// Returns the properties of the OpenGL context that was actually created.
+    pub(crate) fn context_info(&self) -> &ContextInfo {
+        &self.context_info
     }
 }
 
@@ -165,10 +315,29 @@ impl Window for WinitWindow {
     fn center(&self) {
         todo!()
     }
+
+    fn logical_size(&self) -> Size {
+        let window = self.context.window();
+        let size: LogicalSize<u32> = window.inner_size().to_logical(window.scale_factor());
+        Size::new(size.width, size.height)
+    }
+
+    fn framebuffer_size(&self) -> Size {
+        let size = self.context.window().inner_size();
+        Size::new(size.width, size.height)
+    }
+
+    fn scale_factor(&self) -> f64 {
+        self.context.window().scale_factor()
+    }
 }
 
 impl GLWindow for WinitWindow {
     fn swap_buffers(&mut self) {
         self.context.swap_buffers().unwrap();
     }
+
+    fn resize(&mut self, framebuffer_size: Size) {
+        self.context.resize(PhysicalSize::new(framebuffer_size.width, framebuffer_size.height));
+    }
 }
@@ -1,3 +1,12 @@
+use crate::display::types::Size;
+
 pub enum Event {
     WindowCloseRequested,
+    /// The window was resized or moved to a display with a different scale factor.
+    WindowResized {
+        /// The new size of the default framebuffer in pixels.
+        framebuffer_size: Size,
+        /// The ratio of framebuffer to logical pixels.
+        scale_factor: f64,
+    },
 }
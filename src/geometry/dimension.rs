@@ -15,12 +15,14 @@ use std::fmt::{Display, Formatter};
 /// The struct implements the following traits:
 /// * `Debug` - Allows for formatting and printing the dimension.
 /// * `Default` - Provides a default value where both width and height are set to their
-///    default value of `T`.
+///   default value of `T`.
 /// * `Copy` and `Clone` - Enables easy copying and cloning of the struct.
 ///
 /// # Example
 ///
 /// ```no_run
+/// use diego::geometry::dimension::Dimension2D;
+///
 /// let dimension: Dimension2D<i32> = Dimension2D { width: 1920, height: 1080 };
 /// println!("Dimension: {}x{}", dimension.width, dimension.height);
 ///
@@ -61,12 +63,14 @@ impl<T: Display> Display for Dimension2D<T> {
 /// The struct implements the following traits:
 /// * `Debug` - Allows for formatting and printing the dimension.
 /// * `Default` - Provides a default value where width, height, and depth are set to their
-///    default value of `T`.
+///   default value of `T`.
 /// * `Copy` and `Clone` - Enables easy copying and cloning of the struct.
 ///
 /// # Example
 ///
 /// ```no_run
+/// use diego::geometry::dimension::Dimension3D;
+///
 /// let dimension: Dimension3D<i32> = Dimension3D { width: 1920, height: 1080, depth: 500 };
 /// println!("3D Dimension: {}x{}x{}", dimension.width, dimension.height, dimension.depth);
 ///
@@ -1,9 +1,11 @@
 use crate::core::runtime_error::RuntimeError;
 use crate::core::runtime_info::RuntimeInfo;
 use crate::create_runtime_info;
+use crate::gl::state_cache::with_state_cache;
 use crate::gl::types::{BufferType, BufferUsage};
 use crate::gl::GLConstant;
-use ogl::types::{GLsizei, GLsizeiptr, GLuint, GLvoid};
+use ogl::types::{GLint, GLintptr, GLsizei, GLsizeiptr, GLuint, GLvoid};
+use std::ptr;
 
 /// Generates a specified number of buffer IDs and returns them as a vector.
 /// # Arguments
@@ -17,7 +19,7 @@ pub fn gen_buffers(count: u32) -> Result<Vec<u32>, RuntimeError> {
     }
 
     // Check whether the buffer IDs are correct
-    if buffers.iter().any(|&id| id == 0) {
+    if buffers.contains(&0) {
         unsafe {
             let error_code = gl::GetError();
             if error_code != gl::NO_ERROR {
@@ -38,9 +40,13 @@ pub fn gen_buffers(count: u32) -> Result<Vec<u32>, RuntimeError> {
 /// # Arguments
 /// * `target` - The target to which the buffer should be bound (e.g., `ARRAY_BUFFER`).
 /// * `buffer_id` - The ID of the buffer to bind.
+///
+/// Skipped if the buffer is already bound, see `StateCache`.
 pub fn bind_buffer(target: BufferType, buffer_id: u32) {
-    unsafe {
-        gl::BindBuffer(target.to_gl_constant(), buffer_id);
+    if with_state_cache(|cache| cache.bind_buffer(target, buffer_id)) {
+        unsafe {
+            gl::BindBuffer(target.to_gl_constant(), buffer_id);
+        }
     }
 }
 
@@ -55,7 +61,7 @@ where
     D: AsRef<[T]>,
 {
     let slice = dat.as_ref();
-    let size = (slice.len() * size_of::<T>()) as GLsizeiptr;
+    let size = size_of_val(slice) as GLsizeiptr;
     unsafe {
         gl::BufferData(
             target.to_gl_constant(),
@@ -66,6 +72,83 @@ where
     }
 }
 
+/// Allocates `size` bytes of uninitialized storage for the buffer bound to `target`.
+/// Calling this on a buffer that already has storage orphans the old storage, so pending
+/// draw calls can keep using it while new data is written.
+/// # Arguments
+/// * `target` - The buffer target (e.g., `ARRAY_BUFFER`).
+/// * `size` - The size of the new data store in bytes.
+/// * `usage` - The intended usage pattern of the buffer (e.g., `STATIC_DRAW`).
+pub fn buffer_storage_uninitialized(target: BufferType, size: usize, usage: BufferUsage) {
+    unsafe {
+        gl::BufferData(target.to_gl_constant(), size as GLsizeiptr, ptr::null(), usage.to_gl_constant());
+    }
+}
+
+/// Replaces a part of the data store of the buffer bound to `target`.
+/// # Arguments
+/// * `target` - The buffer target (e.g., `ARRAY_BUFFER`).
+/// * `offset` - The byte offset into the data store where the replacement starts.
+/// * `data` - The new data. `offset` plus the size of `data` must not exceed the buffer size.
+pub fn buffer_sub_data<T>(target: BufferType, offset: usize, data: &[T]) {
+    unsafe {
+        gl::BufferSubData(
+            target.to_gl_constant(),
+            offset as GLintptr,
+            size_of_val(data) as GLsizeiptr,
+            data.as_ptr() as *const GLvoid,
+        );
+    }
+}
+
+/// Copies a part of the data store of the buffer bound to `target` into `data`.
+/// # Arguments
+/// * `target` - The buffer target (e.g., `ARRAY_BUFFER`).
+/// * `offset` - The byte offset into the data store where reading starts.
+/// * `data` - The destination; its size determines how many bytes are read.
+pub fn get_buffer_sub_data<T>(target: BufferType, offset: usize, data: &mut [T]) {
+    unsafe {
+        gl::GetBufferSubData(
+            target.to_gl_constant(),
+            offset as GLintptr,
+            size_of_val(data) as GLsizeiptr,
+            data.as_mut_ptr() as *mut GLvoid,
+        );
+    }
+}
+
+/// Returns the ID of the buffer currently bound to `target`, or `0` if none is bound.
+/// The driver is only queried if the binding is not known to the `StateCache`.
+pub fn get_bound_buffer(target: BufferType) -> u32 {
+    if let Some(buffer_id) = with_state_cache(|cache| cache.buffer(target)) {
+        return buffer_id;
+    }
+    let binding = match target {
+        BufferType::ArrayBuffer => gl::ARRAY_BUFFER_BINDING,
+        BufferType::ElementArrayBuffer => gl::ELEMENT_ARRAY_BUFFER_BINDING,
+        BufferType::UniformBuffer => gl::UNIFORM_BUFFER_BINDING,
+        BufferType::TextureBuffer => gl::TEXTURE_BUFFER_BINDING,
+        BufferType::Framebuffer => gl::FRAMEBUFFER_BINDING,
+        BufferType::Renderbuffer => gl::RENDERBUFFER_BINDING,
+        BufferType::CopyReadBuffer => gl::COPY_READ_BUFFER_BINDING,
+        BufferType::CopyWriteBuffer => gl::COPY_WRITE_BUFFER_BINDING,
+        BufferType::PixelPackBuffer => gl::PIXEL_PACK_BUFFER_BINDING,
+        BufferType::PixelUnpackBuffer => gl::PIXEL_UNPACK_BUFFER_BINDING,
+        BufferType::TransformFeedbackBuffer => gl::TRANSFORM_FEEDBACK_BUFFER_BINDING,
+        BufferType::AtomicCounterBuffer => gl::ATOMIC_COUNTER_BUFFER_BINDING,
+        BufferType::DrawIndirectBuffer => gl::DRAW_INDIRECT_BUFFER_BINDING,
+        BufferType::DispatchIndirectBuffer => gl::DISPATCH_INDIRECT_BUFFER_BINDING,
+        BufferType::ShaderStorageBuffer => gl::SHADER_STORAGE_BUFFER_BINDING,
+    };
+    let mut buffer_id: GLint = 0;
+    unsafe {
+        gl::GetIntegerv(binding, &mut buffer_id);
+    }
+    let buffer_id = buffer_id.max(0) as u32;
+    with_state_cache(|cache| cache.remember_buffer(target, buffer_id));
+    buffer_id
+}
+
 /// Deletes the specified buffers from OpenGL.
 /// # Arguments
 /// * `buffers` - A slice or vector of buffer IDs to delete.
@@ -74,6 +157,7 @@ pub fn delete_buffers<T: AsRef<[u32]>>(buffers: T) {
     unsafe {
         gl::DeleteBuffers(slice.len() as GLsizei, slice.as_ptr());
     }
+    with_state_cache(|cache| cache.buffers_deleted(slice));
 }
 
 /// Checks if a buffer ID represents a valid buffer in OpenGL.
@@ -86,3 +170,104 @@ pub fn is_buffer(buffer_id: u32) -> bool {
         gl::IsBuffer(buffer_id as GLuint) > 0
     }
 }
+
+/// Returns the size in bytes of the buffer currently bound to `target`.
+/// # Arguments
+/// * `target` - The buffer target to query (e.g., `ELEMENT_ARRAY_BUFFER`).
+/// # Returns
+/// The size of the data store in bytes, or `0` if no buffer is bound.
+pub fn get_buffer_size(target: BufferType) -> usize {
+    let mut size: GLint = 0;
+    unsafe {
+        gl::GetBufferParameteriv(target.to_gl_constant(), gl::BUFFER_SIZE, &mut size);
+    }
+    size.max(0) as usize
+}
+
+/// Creates a specified number of buffer objects with Direct State Access (OpenGL 4.5).
+///
+/// Unlike `gen_buffers`, the buffers are initialized right away, so they can be used with the
+/// `named_*` functions without being bound first.
+/// # Arguments
+/// * `count` - The number of buffers to create.
+/// # Returns
+/// A vector containing the IDs of the created buffers.
+pub fn create_buffers(count: u32) -> Result<Vec<u32>, RuntimeError> {
+    let mut buffers: Vec<u32> = vec![0; count as usize];
+    unsafe {
+        gl::CreateBuffers(count as GLsizei, buffers.as_mut_ptr());
+    }
+
+    if buffers.contains(&0) {
+        let error_code = unsafe { gl::GetError() };
+        if error_code != gl::NO_ERROR {
+            let err_msg = format!("OpenGL error code: {}", error_code);
+            return Err(RuntimeError::OpenGLError(create_runtime_info!(&err_msg), Some(error_code)));
+        }
+        return Err(RuntimeError::ObjectCreationError(create_runtime_info!(
+            "Failed to create buffer objects"
+        )));
+    }
+
+    Ok(buffers)
+}
+
+/// Replaces the data store of a buffer with `data`, without binding it.
+/// # Arguments
+/// * `buffer_id` - The ID of a buffer created with `create_buffers`.
+/// * `data` - The data to upload.
+/// * `usage` - The intended usage pattern of the buffer (e.g., `STATIC_DRAW`).
+pub fn named_buffer_data<T>(buffer_id: u32, data: &[T], usage: BufferUsage) {
+    unsafe {
+        gl::NamedBufferData(
+            buffer_id,
+            size_of_val(data) as GLsizeiptr,
+            data.as_ptr() as *const GLvoid,
+            usage.to_gl_constant(),
+        );
+    }
+}
+
+/// Allocates `size` bytes of uninitialized storage for a buffer, without binding it.
+/// Like `buffer_storage_uninitialized`, this orphans the previous storage.
+/// # Arguments
+/// * `buffer_id` - The ID of a buffer created with `create_buffers`.
+/// * `size` - The size of the new data store in bytes.
+/// * `usage` - The intended usage pattern of the buffer (e.g., `STATIC_DRAW`).
+pub fn named_buffer_storage_uninitialized(buffer_id: u32, size: usize, usage: BufferUsage) {
+    unsafe {
+        gl::NamedBufferData(buffer_id, size as GLsizeiptr, ptr::null(), usage.to_gl_constant());
+    }
+}
+
+/// Replaces a part of the data store of a buffer, without binding it.
+/// # Arguments
+/// * `buffer_id` - The ID of a buffer created with `create_buffers`.
+/// * `offset` - The byte offset into the data store where the replacement starts.
+/// * `data` - The new data. `offset` plus the size of `data` must not exceed the buffer size.
+pub fn named_buffer_sub_data<T>(buffer_id: u32, offset: usize, data: &[T]) {
+    unsafe {
+        gl::NamedBufferSubData(
+            buffer_id,
+            offset as GLintptr,
+            size_of_val(data) as GLsizeiptr,
+            data.as_ptr() as *const GLvoid,
+        );
+    }
+}
+
+/// Copies a part of the data store of a buffer into `data`, without binding it.
+/// # Arguments
+/// * `buffer_id` - The ID of a buffer created with `create_buffers`.
+/// * `offset` - The byte offset into the data store where reading starts.
+/// * `data` - The destination; its size determines how many bytes are read.
+pub fn get_named_buffer_sub_data<T>(buffer_id: u32, offset: usize, data: &mut [T]) {
+    unsafe {
+        gl::GetNamedBufferSubData(
+            buffer_id,
+            offset as GLintptr,
+            size_of_val(data) as GLsizeiptr,
+            data.as_mut_ptr() as *mut GLvoid,
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use crate::gl::dsa::is_dsa_supported;
+use crate::gl::shader::is_program_binary_supported;
+use crate::gl::state::{get_extensions, get_integer_i_v, get_integer_v, get_integer_v_array, get_string};
+use crate::gl::types::{GlGetParameter, GlStringName};
+use crate::{log_debug, log_info};
+use serde::Serialize;
+
+/// Implementation limits of the driver, as reported by `glGetIntegerv`.
+///
+/// Limits of features the context does not support, e.g. compute shaders on OpenGL 3.3,
+/// are `0`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct GpuLimits {
+    pub max_texture_size: i32,
+    pub max_3d_texture_size: i32,
+    pub max_cube_map_texture_size: i32,
+    pub max_array_texture_layers: i32,
+    pub max_renderbuffer_size: i32,
+    pub max_viewport_dims: [i32; 2],
+    pub max_samples: i32,
+    pub max_color_attachments: i32,
+    pub max_draw_buffers: i32,
+    pub max_vertex_attribs: i32,
+    pub max_texture_image_units: i32,
+    pub max_vertex_texture_image_units: i32,
+    pub max_combined_texture_image_units: i32,
+    pub max_vertex_uniform_components: i32,
+    pub max_fragment_uniform_components: i32,
+    pub max_varying_components: i32,
+    pub max_uniform_block_size: i32,
+    pub max_uniform_buffer_bindings: i32,
+    pub max_elements_vertices: i32,
+    pub max_elements_indices: i32,
+    pub max_shader_storage_block_size: i32,
+    pub max_shader_storage_buffer_bindings: i32,
+    pub max_compute_work_group_invocations: i32,
+    pub max_compute_work_group_count: [i32; 3],
+    pub max_compute_work_group_size: [i32; 3],
+}
+
+/// Optional features the renderer can make use of.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct GpuFeatures {
+    /// Direct State Access (OpenGL 4.5 or `GL_ARB_direct_state_access`).
+    pub direct_state_access: bool,
+    /// Debug message callbacks (OpenGL 4.3 or `GL_KHR_debug`).
+    pub debug_output: bool,
+    /// Compute shaders and shader storage buffers (OpenGL 4.3, OpenGL ES 3.1 or
+    /// `GL_ARB_compute_shader`).
+    pub compute_shaders: bool,
+    /// Bindless textures (`GL_ARB_bindless_texture` or `GL_NV_bindless_texture`).
+    pub bindless_textures: bool,
+    /// Retrieving and loading linked program binaries, see `ProgramCache`.
+    pub program_binaries: bool,
+}
+
+/// Describes the GPU, driver and the limits and features of the current OpenGL context.
+///
+/// The capabilities are gathered once after the context was created and are available through
+/// `ApplicationContext::gpu_capabilities`. `to_json` produces a report to attach to bug reports.
+///
+/// # Example
+/// ```no_run
+/// use diego::gl::capabilities::GpuCapabilities;
+///
+/// let capabilities = GpuCapabilities::query();
+/// if !capabilities.features.compute_shaders {
+///     println!("{} does not support compute shaders", capabilities.renderer);
+/// }
+/// std::fs::write("gpu.json", capabilities.to_json().unwrap()).unwrap();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct GpuCapabilities {
+    pub vendor: String,
+    pub renderer: String,
+    pub version: String,
+    pub shading_language_version: String,
+    pub extensions: Vec<String>,
+    pub limits: GpuLimits,
+    pub features: GpuFeatures,
+}
+
+impl GpuCapabilities {
+    /// Queries the capabilities of the current context. The OpenGL function pointers must
+    /// have been loaded.
+    pub fn query() -> Self {
+        let version = get_string(GlStringName::Version);
+        let extensions = get_extensions();
+        let has_extension = |name: &str| extensions.iter().any(|extension| extension == name);
+
+        let context_version = (get_integer_v(GlGetParameter::MajorVersion), get_integer_v(GlGetParameter::MinorVersion));
+        let is_gles = version.starts_with("OpenGL ES");
+        let compute_shaders = if is_gles { context_version >= (3, 1) } else { context_version >= (4, 3) }
+            || has_extension("GL_ARB_compute_shader");
+        let features = GpuFeatures {
+            direct_state_access: is_dsa_supported(),
+            debug_output: (context_version >= (4, 3) || has_extension("GL_KHR_debug"))
+                && gl::DebugMessageCallback::is_loaded(),
+            compute_shaders,
+            bindless_textures: has_extension("GL_ARB_bindless_texture")
+                || has_extension("GL_NV_bindless_texture"),
+            program_binaries: is_program_binary_supported(),
+        };
+
+        Self {
+            vendor: get_string(GlStringName::Vendor),
+            renderer: get_string(GlStringName::Renderer),
+            version,
+            shading_language_version: get_string(GlStringName::ShadingLanguageVersion),
+            limits: query_limits(compute_shaders),
+            features,
+            extensions,
+        }
+    }
+
+    /// Returns whether the driver reports the extension, e.g. `GL_ARB_bindless_texture`.
+    pub fn has_extension(&self, name: &str) -> bool {
+        self.extensions.iter().any(|extension| extension == name)
+    }
+
+    /// Serializes the capabilities to pretty-printed JSON.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Logs the driver and features at info level and the limits and extensions at debug level.
+    pub fn log(&self) {
+        log_info!("GPU: {} ({})", self.renderer, self.vendor);
+        log_info!("OpenGL {}, GLSL {}", self.version, self.shading_language_version);
+        log_info!("GPU features: {:?}", self.features);
+        log_debug!("GPU limits: {:?}", self.limits);
+        log_debug!("{} extensions: {}", self.extensions.len(), self.extensions.join(" "));
+    }
+}
+
+fn query_limits(compute_shaders: bool) -> GpuLimits {
+    let viewport_dims = get_integer_v_array(GlGetParameter::MaxViewPortDims, 2);
+    let indexed = |parameter: GlGetParameter| {
+        [get_integer_i_v(parameter, 0), get_integer_i_v(parameter, 1), get_integer_i_v(parameter, 2)]
+    };
+    let mut limits = GpuLimits {
+        max_texture_size: get_integer_v(GlGetParameter::MaxTextureSize),
+        max_3d_texture_size: get_integer_v(GlGetParameter::Max3DTextureSize),
+        max_cube_map_texture_size: get_integer_v(GlGetParameter::MaxCubeMapTextureSize),
+        max_array_texture_layers: get_integer_v(GlGetParameter::MaxArrayTextureLayers),
+        max_renderbuffer_size: get_integer_v(GlGetParameter::MaxRenderbufferSize),
+        max_viewport_dims: [viewport_dims[0], viewport_dims[1]],
+        max_samples: get_integer_v(GlGetParameter::MaxSamples),
+        max_color_attachments: get_integer_v(GlGetParameter::MaxColorAttachments),
+        max_draw_buffers: get_integer_v(GlGetParameter::MaxDrawBuffers),
+        max_vertex_attribs: get_integer_v(GlGetParameter::MaxVertexAttribs),
+        max_texture_image_units: get_integer_v(GlGetParameter::MaxTextureImageUnits),
+        max_vertex_texture_image_units: get_integer_v(GlGetParameter::MaxVertexTextureImageUnits),
+        max_combined_texture_image_units: get_integer_v(GlGetParameter::MaxCombinedTextureImageUnits),
+        max_vertex_uniform_components: get_integer_v(GlGetParameter::MaxVertexUniformComponents),
+        max_fragment_uniform_components: get_integer_v(GlGetParameter::MaxFragmentUniformComponents),
+        max_varying_components: get_integer_v(GlGetParameter::MaxVaryingComponents),
+        max_uniform_block_size: get_integer_v(GlGetParameter::MaxUniformBlockSize),
+        max_uniform_buffer_bindings: get_integer_v(GlGetParameter::MaxUniformBufferBindings),
+        max_elements_vertices: get_integer_v(GlGetParameter::MaxElementsVertices),
+        max_elements_indices: get_integer_v(GlGetParameter::MaxElementsIndices),
+        ..GpuLimits::default()
+    };
+
+    // Querying these on older contexts only raises GL_INVALID_ENUM.
+    if compute_shaders {
+        limits.max_shader_storage_block_size = get_integer_v(GlGetParameter::MaxShaderStorageBlockSize);
+        limits.max_shader_storage_buffer_bindings = get_integer_v(GlGetParameter::MaxShaderStorageBufferBindings);
+        limits.max_compute_work_group_invocations = get_integer_v(GlGetParameter::MaxComputeWorkGroupInvocations);
+        limits.max_compute_work_group_count = indexed(GlGetParameter::MaxComputeWorkGroupCount);
+        limits.max_compute_work_group_size = indexed(GlGetParameter::MaxComputeWorkGroupSize);
+    }
+    limits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_report() {
+        let capabilities = GpuCapabilities {
+            vendor: "Mesa".to_string(),
+            renderer: "llvmpipe".to_string(),
+            extensions: vec!["GL_KHR_debug".to_string()],
+            limits: GpuLimits { max_texture_size: 16384, ..GpuLimits::default() },
+            features: GpuFeatures { debug_output: true, ..GpuFeatures::default() },
+            ..GpuCapabilities::default()
+        };
+        assert!(capabilities.has_extension("GL_KHR_debug"));
+        assert!(!capabilities.has_extension("GL_KHR"));
+
+        let json: serde_json::Value = serde_json::from_str(&capabilities.to_json().unwrap()).unwrap();
+        assert_eq!(json["renderer"], "llvmpipe");
+        assert_eq!(json["limits"]["max_texture_size"], 16384);
+        assert_eq!(json["features"]["debug_output"], true);
+        assert_eq!(json["extensions"][0], "GL_KHR_debug");
+    }
+}
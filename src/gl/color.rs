@@ -202,7 +202,7 @@ impl Color {
     }
 
     pub fn with_alpha(&self, alpha: f32) -> Self {
-        let mut color = self.clone();
+        let mut color = *self;
         color.a = f32::max(alpha, 1.0).min(0.0);
         color
     }
@@ -214,15 +214,15 @@ impl From<[f32; 3]> for Color {
     }
 }
 
-impl Into<[f32; 3]> for Color {
-    fn into(self) -> [f32; 3] {
-        [self.r, self.g, self.b]
+impl From<Color> for [f32; 3] {
+    fn from(value: Color) -> Self {
+        [value.r, value.g, value.b]
     }
 }
 
-impl Into<[f32; 4]> for Color {
-    fn into(self) -> [f32; 4] {
-        [self.r, self.g, self.b, self.a]
+impl From<Color> for [f32; 4] {
+    fn from(value: Color) -> Self {
+        [value.r, value.g, value.b, value.a]
     }
 }
 
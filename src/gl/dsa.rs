@@ -0,0 +1,78 @@
+use crate::gl::state::{get_extensions, get_integer_v};
+use crate::gl::types::GlGetParameter;
+use crate::log_info;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+const UNDETECTED: u8 = 0;
+const DISABLED: u8 = 1;
+const ENABLED: u8 = 2;
+
+static DSA_STATE: AtomicU8 = AtomicU8::new(UNDETECTED);
+
+/// Checks whether the current context supports Direct State Access (DSA).
+///
+/// DSA is core in OpenGL 4.5 and available on older contexts through the
+/// `GL_ARB_direct_state_access` extension. Additionally, every DSA entry point used by the
+/// `glx` objects has to be loaded.
+pub fn is_dsa_supported() -> bool {
+    let entry_points_loaded = gl::CreateBuffers::is_loaded()
+        && gl::NamedBufferData::is_loaded()
+        && gl::NamedBufferSubData::is_loaded()
+        && gl::GetNamedBufferSubData::is_loaded()
+        && gl::CreateVertexArrays::is_loaded()
+        && gl::VertexArrayVertexBuffer::is_loaded()
+        && gl::VertexArrayElementBuffer::is_loaded()
+        && gl::VertexArrayAttribFormat::is_loaded()
+        && gl::VertexArrayAttribIFormat::is_loaded()
+        && gl::VertexArrayAttribLFormat::is_loaded()
+        && gl::VertexArrayAttribBinding::is_loaded()
+        && gl::VertexArrayBindingDivisor::is_loaded()
+        && gl::EnableVertexArrayAttrib::is_loaded()
+        && gl::CreateTextures::is_loaded()
+        && gl::TextureStorage2D::is_loaded()
+        && gl::TextureSubImage2D::is_loaded()
+        && gl::TextureParameteri::is_loaded()
+        && gl::GenerateTextureMipmap::is_loaded()
+        && gl::BindTextureUnit::is_loaded();
+    if !entry_points_loaded {
+        return false;
+    }
+
+    let version = (get_integer_v(GlGetParameter::MajorVersion), get_integer_v(GlGetParameter::MinorVersion));
+    version >= (4, 5) || get_extensions().iter().any(|name| name == "GL_ARB_direct_state_access")
+}
+
+/// Returns whether the `glx` objects use the DSA code path.
+///
+/// Support is detected on the first call, so the OpenGL context has to be current by then.
+/// Without DSA, objects are edited by binding them first (bind-to-edit).
+pub fn is_dsa_enabled() -> bool {
+    match DSA_STATE.load(Ordering::Relaxed) {
+        ENABLED => true,
+        DISABLED => false,
+        _ => {
+            let supported = is_dsa_supported();
+            DSA_STATE.store(if supported { ENABLED } else { DISABLED }, Ordering::Relaxed);
+            if supported {
+                log_info!("Using the Direct State Access code path");
+            } else {
+                log_info!("Direct State Access is unavailable, using the bind-to-edit code path");
+            }
+            supported
+        }
+    }
+}
+
+/// Selects the code path of the `glx` objects.
+///
+/// Passing `false` forces the bind-to-edit path, e.g. to test it on a GL 4.5 driver. Passing
+/// `true` enables DSA only if the context supports it. Call this before creating any `glx`
+/// objects: objects generated for the bind-to-edit path cannot be edited with DSA functions.
+///
+/// # Returns
+/// Whether DSA is enabled afterward.
+pub fn set_dsa_enabled(enabled: bool) -> bool {
+    let enabled = enabled && is_dsa_supported();
+    DSA_STATE.store(if enabled { ENABLED } else { DISABLED }, Ordering::Relaxed);
+    enabled
+}
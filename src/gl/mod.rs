@@ -19,7 +19,15 @@
 //! - `rendering`: Handles rendering-related functions.
 //! - `vao`: Functions for creating and managing vertex array objects.
 //! - `state`: Manages and queries OpenGL state, including retrieving values
-//!    of specific OpenGL parameters.
+//!   of specific OpenGL parameters.
+//! - `buffer`: Functions for creating and filling buffer objects.
+//! - `shader`: Functions for compiling shaders and linking shader programs.
+//! - `texture`: Functions for creating and configuring texture objects.
+//! - `dsa`: Detects Direct State Access (OpenGL 4.5) support and selects the code path of
+//!   the `glx` objects.
+//! - `capabilities`: Gathers the driver strings, limits and optional features of the context.
+//! - `state_cache`: Shadows the OpenGL state on the CPU to skip redundant state changes.
+//! - `viewport`: Follows the framebuffer size and provides nested viewports and scissor boxes.
 
 use ogl::types::GLuint;
 
@@ -31,6 +39,12 @@ pub mod rendering;
 pub mod vao;
 pub mod state;
 pub mod buffer;
+pub mod shader;
+pub mod texture;
+pub mod dsa;
+pub mod capabilities;
+pub mod state_cache;
+pub mod viewport;
 
 pub trait GLConstant {
     fn to_gl_constant(self) -> GLuint;
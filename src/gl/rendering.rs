@@ -1,4 +1,9 @@
-use crate::gl::types::ClearBufferMask;
+use crate::core::runtime_error::RuntimeError;
+use crate::core::runtime_info::RuntimeInfo;
+use crate::create_runtime_info;
+use crate::gl::types::{ClearBufferMask, IndexType, PrimitiveTopology};
+use crate::gl::GLConstant;
+use ogl::types::{GLint, GLsizei, GLvoid};
 
 /// Clears the color and depth buffers of the OpenGL context.
 ///
@@ -12,7 +17,7 @@ use crate::gl::types::ClearBufferMask;
 /// initialized and active when this function is called.
 ///
 /// # Example
-/// ```
+/// ```no_run
 /// use diego::gl::rendering::clear;
 /// 
 /// clear();
@@ -41,7 +46,7 @@ pub fn clear() {
 /// when this function is called.
 ///
 /// # Example
-/// ```
+/// ```no_run
 /// use diego::gl::rendering::clear_with_mask;
 /// use diego::gl::types::ClearBufferMask;
 ///
@@ -54,3 +59,562 @@ pub fn clear_with_mask(mask: ClearBufferMask) {
         gl::Clear(mask.bits());
     }
 }
+
+/// The parameters of a single `draw_arrays_indirect` call as stored in the draw indirect buffer.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct DrawArraysIndirectCommand {
+    pub count: u32,
+    pub instance_count: u32,
+    pub first: u32,
+    pub base_instance: u32,
+}
+
+/// The parameters of a single `draw_elements_indirect` call as stored in the draw indirect
+/// buffer.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct DrawElementsIndirectCommand {
+    pub count: u32,
+    pub instance_count: u32,
+    pub first_index: u32,
+    pub base_vertex: i32,
+    pub base_instance: u32,
+}
+
+/// Renders primitives from the vertices of the currently bound vertex array object.
+///
+/// # Parameters
+/// - `topology`: The kind of primitives to render.
+/// - `first`: The index of the first vertex.
+/// - `count`: The number of vertices to render.
+///
+/// # Panics
+/// In debug builds, if no VAO or program is bound or the vertices exceed the bound buffers.
+pub fn draw_arrays(topology: PrimitiveTopology, first: usize, count: usize) {
+    #[cfg(debug_assertions)]
+    checks::check_arrays(first + count, 1);
+    unsafe {
+        gl::DrawArrays(topology.to_gl_constant(), first as GLint, count as GLsizei);
+    }
+}
+
+/// Renders `instance_count` instances of a range of vertices.
+///
+/// # Panics
+/// In debug builds, if no VAO or program is bound or the vertices exceed the bound buffers.
+pub fn draw_arrays_instanced(topology: PrimitiveTopology,
+                             first: usize,
+                             count: usize,
+                             instance_count: usize) {
+    #[cfg(debug_assertions)]
+    checks::check_arrays(first + count, instance_count);
+    unsafe {
+        gl::DrawArraysInstanced(
+            topology.to_gl_constant(),
+            first as GLint,
+            count as GLsizei,
+            instance_count as GLsizei,
+        );
+    }
+}
+
+/// Renders primitives using the element array buffer of the currently bound vertex array
+/// object.
+///
+/// # Parameters
+/// - `topology`: The kind of primitives to render.
+/// - `count`: The number of indices to render.
+/// - `index_type`: The data type of the indices in the element array buffer.
+/// - `offset`: The byte offset of the first index in the element array buffer.
+///
+/// # Panics
+/// In debug builds, if no VAO or program is bound or the indices exceed the element buffer.
+pub fn draw_elements(topology: PrimitiveTopology, count: usize, index_type: IndexType, offset: usize) {
+    #[cfg(debug_assertions)]
+    checks::check_elements(count, index_type, offset);
+    unsafe {
+        gl::DrawElements(
+            topology.to_gl_constant(),
+            count as GLsizei,
+            index_type.to_gl_constant(),
+            offset as *const GLvoid,
+        );
+    }
+}
+
+/// Renders `instance_count` instances of a range of indices.
+///
+/// # Panics
+/// In debug builds, if no VAO or program is bound or the indices exceed the element buffer.
+pub fn draw_elements_instanced(topology: PrimitiveTopology,
+                               count: usize,
+                               index_type: IndexType,
+                               offset: usize,
+                               instance_count: usize) {
+    #[cfg(debug_assertions)]
+    checks::check_elements(count, index_type, offset);
+    unsafe {
+        gl::DrawElementsInstanced(
+            topology.to_gl_constant(),
+            count as GLsizei,
+            index_type.to_gl_constant(),
+            offset as *const GLvoid,
+            instance_count as GLsizei,
+        );
+    }
+}
+
+/// Renders indexed primitives, adding `base_vertex` to every index before fetching the vertex.
+/// This allows several meshes to share one vertex and one element buffer.
+///
+/// # Panics
+/// In debug builds, if no VAO or program is bound or the indices exceed the element buffer.
+pub fn draw_elements_base_vertex(topology: PrimitiveTopology,
+                                 count: usize,
+                                 index_type: IndexType,
+                                 offset: usize,
+                                 base_vertex: i32) {
+    #[cfg(debug_assertions)]
+    checks::check_elements(count, index_type, offset);
+    unsafe {
+        gl::DrawElementsBaseVertex(
+            topology.to_gl_constant(),
+            count as GLsizei,
+            index_type.to_gl_constant(),
+            offset as *const GLvoid,
+            base_vertex,
+        );
+    }
+}
+
+/// The instanced form of `draw_elements_base_vertex`.
+///
+/// # Panics
+/// In debug builds, if no VAO or program is bound or the indices exceed the element buffer.
+pub fn draw_elements_instanced_base_vertex(topology: PrimitiveTopology,
+                                           count: usize,
+                                           index_type: IndexType,
+                                           offset: usize,
+                                           instance_count: usize,
+                                           base_vertex: i32) {
+    #[cfg(debug_assertions)]
+    checks::check_elements(count, index_type, offset);
+    unsafe {
+        gl::DrawElementsInstancedBaseVertex(
+            topology.to_gl_constant(),
+            count as GLsizei,
+            index_type.to_gl_constant(),
+            offset as *const GLvoid,
+            instance_count as GLsizei,
+            base_vertex,
+        );
+    }
+}
+
+/// Renders several ranges of vertices with a single call.
+///
+/// # Parameters
+/// - `firsts`: The first vertex of every range.
+/// - `counts`: The number of vertices of every range; must have the same length as `firsts`.
+///
+/// # Panics
+/// If `firsts` and `counts` differ in length. In debug builds also if no VAO or program is
+/// bound or a range exceeds the bound buffers.
+///
+/// # Errors
+/// `RuntimeError::UnsupportedFunction` if the driver does not provide `glMultiDrawArrays`.
+pub fn multi_draw_arrays(topology: PrimitiveTopology, firsts: &[i32], counts: &[i32])
+    -> Result<(), RuntimeError>
+{
+    assert_eq!(firsts.len(), counts.len(), "firsts and counts must have the same length");
+    require_loaded(gl::MultiDrawArrays::is_loaded(), "glMultiDrawArrays")?;
+    #[cfg(debug_assertions)]
+    {
+        let end = firsts.iter().zip(counts).map(|(&first, &count)| (first + count).max(0)).max();
+        checks::check_arrays(end.unwrap_or(0) as usize, 1);
+    }
+    unsafe {
+        gl::MultiDrawArrays(
+            topology.to_gl_constant(),
+            firsts.as_ptr(),
+            counts.as_ptr(),
+            firsts.len() as GLsizei,
+        );
+    }
+    Ok(())
+}
+
+/// Renders several ranges of indices with a single call.
+///
+/// # Parameters
+/// - `counts`: The number of indices of every range.
+/// - `offsets`: The byte offset of every range in the element array buffer; must have the
+///   same length as `counts`.
+///
+/// # Panics
+/// If `counts` and `offsets` differ in length. In debug builds also if no VAO or program is
+/// bound or a range exceeds the element buffer.
+///
+/// # Errors
+/// `RuntimeError::UnsupportedFunction` if the driver does not provide `glMultiDrawElements`.
+pub fn multi_draw_elements(topology: PrimitiveTopology,
+                           counts: &[i32],
+                           index_type: IndexType,
+                           offsets: &[usize]) -> Result<(), RuntimeError> {
+    multi_draw_elements_base_vertex(topology, counts, index_type, offsets, None)
+}
+
+/// Renders several ranges of indices with a single call, each with its own base vertex.
+///
+/// # Parameters
+/// - `base_vertices`: Optional base vertex of every range. `None` uses `0` for all ranges.
+///
+/// # Panics
+/// If the slices differ in length. In debug builds also if no VAO or program is bound or a
+/// range exceeds the element buffer.
+///
+/// # Errors
+/// `RuntimeError::UnsupportedFunction` if the driver does not provide
+/// `glMultiDrawElementsBaseVertex`, or `glMultiDrawElements` without base vertices.
+pub fn multi_draw_elements_base_vertex(topology: PrimitiveTopology,
+                                       counts: &[i32],
+                                       index_type: IndexType,
+                                       offsets: &[usize],
+                                       base_vertices: Option<&[i32]>) -> Result<(), RuntimeError> {
+    assert_eq!(counts.len(), offsets.len(), "counts and offsets must have the same length");
+    if let Some(base_vertices) = base_vertices {
+        assert_eq!(counts.len(), base_vertices.len(), "counts and base_vertices must have the same length");
+        require_loaded(gl::MultiDrawElementsBaseVertex::is_loaded(), "glMultiDrawElementsBaseVertex")?;
+    } else {
+        require_loaded(gl::MultiDrawElements::is_loaded(), "glMultiDrawElements")?;
+    }
+    #[cfg(debug_assertions)]
+    for (&count, &offset) in counts.iter().zip(offsets) {
+        checks::check_elements(count.max(0) as usize, index_type, offset);
+    }
+
+    let offsets: Vec<*const GLvoid> = offsets.iter().map(|&offset| offset as *const GLvoid).collect();
+    unsafe {
+        match base_vertices {
+            Some(base_vertices) => gl::MultiDrawElementsBaseVertex(
+                topology.to_gl_constant(),
+                counts.as_ptr(),
+                index_type.to_gl_constant(),
+                offsets.as_ptr(),
+                counts.len() as GLsizei,
+                base_vertices.as_ptr() as *mut GLint,
+            ),
+            None => gl::MultiDrawElements(
+                topology.to_gl_constant(),
+                counts.as_ptr(),
+                index_type.to_gl_constant(),
+                offsets.as_ptr(),
+                counts.len() as GLsizei,
+            ),
+        }
+    }
+    Ok(())
+}
+
+/// Renders vertices with the parameters read from a `DrawArraysIndirectCommand` in the
+/// bound draw indirect buffer.
+///
+/// # Parameters
+/// - `offset`: The byte offset of the command in the draw indirect buffer.
+///
+/// # Panics
+/// In debug builds, if no VAO, program or draw indirect buffer is bound or the command lies
+/// outside of the indirect buffer.
+///
+/// # Errors
+/// `RuntimeError::UnsupportedFunction` if the driver does not provide `glDrawArraysIndirect`.
+pub fn draw_arrays_indirect(topology: PrimitiveTopology, offset: usize) -> Result<(), RuntimeError> {
+    multi_draw_arrays_indirect(topology, offset, 1, 0)
+}
+
+/// Renders indices with the parameters read from a `DrawElementsIndirectCommand` in the
+/// bound draw indirect buffer.
+///
+/// # Panics
+/// In debug builds, if no VAO, program, element buffer or draw indirect buffer is bound or
+/// the command lies outside of the indirect buffer.
+///
+/// # Errors
+/// `RuntimeError::UnsupportedFunction` if the driver does not provide `glDrawElementsIndirect`.
+pub fn draw_elements_indirect(topology: PrimitiveTopology, index_type: IndexType, offset: usize)
+    -> Result<(), RuntimeError>
+{
+    multi_draw_elements_indirect(topology, index_type, offset, 1, 0)
+}
+
+/// Executes `draw_count` consecutive `DrawArraysIndirectCommand`s from the draw indirect buffer.
+///
+/// # Parameters
+/// - `offset`: The byte offset of the first command.
+/// - `draw_count`: The number of commands to execute.
+/// - `stride`: The distance in bytes between two commands, `0` for tightly packed commands.
+///
+/// # Errors
+/// `RuntimeError::UnsupportedFunction` if the driver does not provide
+/// `glMultiDrawArraysIndirect`, or `glDrawArraysIndirect` for a single command.
+pub fn multi_draw_arrays_indirect(topology: PrimitiveTopology,
+                                  offset: usize,
+                                  draw_count: usize,
+                                  stride: usize) -> Result<(), RuntimeError> {
+    if draw_count == 1 {
+        require_loaded(gl::DrawArraysIndirect::is_loaded(), "glDrawArraysIndirect")?;
+    } else {
+        require_loaded(gl::MultiDrawArraysIndirect::is_loaded(), "glMultiDrawArraysIndirect")?;
+    }
+    #[cfg(debug_assertions)]
+    checks::check_indirect(size_of::<DrawArraysIndirectCommand>(), offset, draw_count, stride, false);
+    unsafe {
+        if draw_count == 1 {
+            gl::DrawArraysIndirect(topology.to_gl_constant(), offset as *const GLvoid);
+        } else {
+            gl::MultiDrawArraysIndirect(
+                topology.to_gl_constant(),
+                offset as *const GLvoid,
+                draw_count as GLsizei,
+                stride as GLsizei,
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Executes `draw_count` consecutive `DrawElementsIndirectCommand`s from the draw indirect
+/// buffer.
+///
+/// # Parameters
+/// - `offset`: The byte offset of the first command.
+/// - `draw_count`: The number of commands to execute.
+/// - `stride`: The distance in bytes between two commands, `0` for tightly packed commands.
+///
+/// # Errors
+/// `RuntimeError::UnsupportedFunction` if the driver does not provide
+/// `glMultiDrawElementsIndirect`, or `glDrawElementsIndirect` for a single command.
+pub fn multi_draw_elements_indirect(topology: PrimitiveTopology,
+                                    index_type: IndexType,
+                                    offset: usize,
+                                    draw_count: usize,
+                                    stride: usize) -> Result<(), RuntimeError> {
+    if draw_count == 1 {
+        require_loaded(gl::DrawElementsIndirect::is_loaded(), "glDrawElementsIndirect")?;
+    } else {
+        require_loaded(gl::MultiDrawElementsIndirect::is_loaded(), "glMultiDrawElementsIndirect")?;
+    }
+    #[cfg(debug_assertions)]
+    checks::check_indirect(size_of::<DrawElementsIndirectCommand>(), offset, draw_count, stride, true);
+    unsafe {
+        if draw_count == 1 {
+            gl::DrawElementsIndirect(
+                topology.to_gl_constant(),
+                index_type.to_gl_constant(),
+                offset as *const GLvoid,
+            );
+        } else {
+            gl::MultiDrawElementsIndirect(
+                topology.to_gl_constant(),
+                index_type.to_gl_constant(),
+                offset as *const GLvoid,
+                draw_count as GLsizei,
+                stride as GLsizei,
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Fails with `RuntimeError::UnsupportedFunction` unless the GL function `name` is loaded,
+/// e.g. `glMultiDrawElementsIndirect` before OpenGL 4.3.
+fn require_loaded(loaded: bool, name: &str) -> Result<(), RuntimeError> {
+    if loaded {
+        Ok(())
+    } else {
+        Err(RuntimeError::UnsupportedFunction(create_runtime_info!(format!("{} is not available", name))))
+    }
+}
+
+/// Sanity checks run before every draw call in debug builds. Drawing with missing bindings or
+/// out of range counts is undefined behavior in OpenGL and frequently crashes the driver, so
+/// these fail loudly instead.
+#[cfg(debug_assertions)]
+mod checks {
+    use crate::gl::buffer::get_buffer_size;
+    use crate::gl::dsa::is_dsa_enabled;
+    use crate::gl::state::get_integer_v;
+    use crate::gl::types::{BufferType, GlGetParameter, IndexType};
+    use ogl::types::{GLenum, GLint, GLint64};
+
+    pub(super) fn check_arrays(vertex_end: usize, instance_count: usize) {
+        check_bindings();
+        let attribute_count = get_integer_v(GlGetParameter::MaxVertexAttribs).max(0) as u32;
+        for index in 0..attribute_count {
+            if get_vertex_attrib(index, gl::VERTEX_ATTRIB_ARRAY_ENABLED) == 0 {
+                continue;
+            }
+            let buffer_id = get_vertex_attrib(index, gl::VERTEX_ATTRIB_ARRAY_BUFFER_BINDING) as u32;
+            if buffer_id == 0 {
+                continue;
+            }
+
+            let divisor = get_vertex_attrib(index, gl::VERTEX_ATTRIB_ARRAY_DIVISOR).max(0) as usize;
+            let elements = match divisor {
+                0 => vertex_end,
+                divisor => instance_count.div_ceil(divisor),
+            };
+            if elements == 0 {
+                continue;
+            }
+
+            let required = attribute_end(index, elements);
+            let size = buffer_size(buffer_id);
+            assert!(
+                required <= size,
+                "Draw call reads {} bytes from the buffer of vertex attribute {}, but it only holds {} bytes",
+                required,
+                index,
+                size
+            );
+        }
+    }
+
+    pub(super) fn check_elements(count: usize, index_type: IndexType, offset: usize) {
+        check_bindings();
+        assert!(
+            get_integer_v(GlGetParameter::ElementArrayBufferBinding) != 0,
+            "Indexed draw call without an element array buffer bound to the VAO"
+        );
+        let required = offset + count * index_type.size();
+        let size = get_buffer_size(BufferType::ElementArrayBuffer);
+        assert!(
+            required <= size,
+            "Draw call reads {} bytes of indices, but the element array buffer only holds {} bytes",
+            required,
+            size
+        );
+    }
+
+    pub(super) fn check_indirect(command_size: usize,
+                                 offset: usize,
+                                 draw_count: usize,
+                                 stride: usize,
+                                 indexed: bool) {
+        check_bindings();
+        if indexed {
+            assert!(
+                get_integer_v(GlGetParameter::ElementArrayBufferBinding) != 0,
+                "Indexed draw call without an element array buffer bound to the VAO"
+            );
+        }
+        assert!(
+            get_integer_v(GlGetParameter::DrawIndirectBufferBinding) != 0,
+            "Indirect draw call without a draw indirect buffer bound"
+        );
+        if draw_count == 0 {
+            return;
+        }
+
+        let stride = if stride == 0 { command_size } else { stride };
+        let required = offset + (draw_count - 1) * stride + command_size;
+        let size = get_buffer_size(BufferType::DrawIndirectBuffer);
+        assert!(
+            required <= size,
+            "Indirect draw call reads {} bytes of commands, but the draw indirect buffer only holds {} bytes",
+            required,
+            size
+        );
+    }
+
+    fn check_bindings() {
+        assert!(
+            get_integer_v(GlGetParameter::VertexArrayBinding) != 0,
+            "Draw call without a bound vertex array object"
+        );
+        assert!(
+            get_integer_v(GlGetParameter::CurrentProgram) != 0,
+            "Draw call without a bound shader program"
+        );
+    }
+
+    /// Returns the number of bytes of the attribute buffer read for `elements` elements.
+    fn attribute_end(index: u32, elements: usize) -> usize {
+        let components = get_vertex_attrib(index, gl::VERTEX_ATTRIB_ARRAY_SIZE).max(0) as usize;
+        let data_type = get_vertex_attrib(index, gl::VERTEX_ATTRIB_ARRAY_TYPE) as GLenum;
+        let element_size = match data_type {
+            gl::BYTE | gl::UNSIGNED_BYTE => components,
+            gl::SHORT | gl::UNSIGNED_SHORT | gl::HALF_FLOAT => components * 2,
+            gl::DOUBLE => components * 8,
+            // Packed formats always occupy 4 bytes.
+            gl::INT_2_10_10_10_REV | gl::UNSIGNED_INT_2_10_10_10_REV
+            | gl::UNSIGNED_INT_10F_11F_11F_REV => 4,
+            _ => components * 4,
+        };
+        let (offset, stride) = if is_dsa_enabled() {
+            binding_offset_and_stride(index)
+        } else {
+            pointer_offset_and_stride(index, element_size)
+        };
+        offset + (elements - 1) * stride + element_size
+    }
+
+    /// The offset and stride of an attribute specified with `glVertexAttribPointer`.
+    fn pointer_offset_and_stride(index: u32, element_size: usize) -> (usize, usize) {
+        let stride = match get_vertex_attrib(index, gl::VERTEX_ATTRIB_ARRAY_STRIDE).max(0) as usize {
+            0 => element_size,
+            stride => stride,
+        };
+
+        let mut offset: *mut std::ffi::c_void = std::ptr::null_mut();
+        unsafe {
+            // The binding declares the output parameter as `*const`, although GL writes to it.
+            gl::GetVertexAttribPointerv(index, gl::VERTEX_ATTRIB_ARRAY_POINTER, std::ptr::addr_of_mut!(offset));
+        }
+        (offset as usize, stride)
+    }
+
+    /// The offset and stride of an attribute read through a vertex buffer binding, as set up
+    /// by `glVertexArrayVertexBuffer` and `glVertexArrayAttribFormat`. With these, the pointer
+    /// and stride of the attribute itself are not updated.
+    fn binding_offset_and_stride(index: u32) -> (usize, usize) {
+        let binding = get_vertex_attrib(index, gl::VERTEX_ATTRIB_BINDING).max(0) as u32;
+        let relative_offset = get_vertex_attrib(index, gl::VERTEX_ATTRIB_RELATIVE_OFFSET).max(0) as usize;
+        let mut binding_offset: GLint64 = 0;
+        let mut binding_stride: GLint = 0;
+        unsafe {
+            gl::GetInteger64i_v(gl::VERTEX_BINDING_OFFSET, binding, &mut binding_offset);
+            gl::GetIntegeri_v(gl::VERTEX_BINDING_STRIDE, binding, &mut binding_stride);
+        }
+        (binding_offset.max(0) as usize + relative_offset, binding_stride.max(0) as usize)
+    }
+
+    fn get_vertex_attrib(index: u32, parameter: GLenum) -> GLint {
+        let mut value: GLint = 0;
+        unsafe {
+            gl::GetVertexAttribiv(index, parameter, &mut value);
+        }
+        value
+    }
+
+    fn buffer_size(buffer_id: u32) -> usize {
+        let previous = get_integer_v(GlGetParameter::ArrayBufferBinding) as u32;
+        crate::gl::buffer::bind_buffer(BufferType::ArrayBuffer, buffer_id);
+        let size = get_buffer_size(BufferType::ArrayBuffer);
+        crate::gl::buffer::bind_buffer(BufferType::ArrayBuffer, previous);
+        size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_indirect_command_layout() {
+        // The layouts are defined by the OpenGL specification.
+        assert_eq!(size_of::<DrawArraysIndirectCommand>(), 16);
+        assert_eq!(size_of::<DrawElementsIndirectCommand>(), 20);
+    }
+}
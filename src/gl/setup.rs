@@ -1,9 +1,11 @@
-use ogl::types::{GLint, GLsizei};
+use ogl::types::{GLboolean, GLint, GLsizei};
 use crate::geometry::dimension::Dimension2D;
 use crate::gl::color::Color;
-use crate::gl::types::{Capability, GlGetParameter};
+use crate::gl::types::{BlendEquation, BlendFactor, Capability, CompareFunction, Face, FrontFace, GlGetParameter, PolygonMode, StencilOp};
 use crate::gl::GLConstant;
 use crate::gl::state::get_integer_v_array;
+use crate::gl::state_cache::{with_state_cache, StateCache, StencilFunc};
+use crate::log_warn;
 
 /// Sets the OpenGL clear color for rendering.
 ///
@@ -25,8 +27,11 @@ use crate::gl::state::get_integer_v_array;
 ///
 /// # Example
 /// ```no_run
-/// let clear_color = Color { r: 0.0, g: 0.0, b: 0.0, a: 1.0 }; // Set color to black
-/// clear_color(clear_color);
+/// use diego::gl::color::Color;
+/// use diego::gl::setup::clear_color;
+///
+/// let color = Color { r: 0.0, g: 0.0, b: 0.0, a: 1.0 }; // Set color to black
+/// clear_color(color);
 /// ```
 pub fn clear_color(color: Color) {
     unsafe {
@@ -58,7 +63,9 @@ pub fn clear_color(color: Color) {
 /// Make sure the context is properly initialized before calling this function.
 ///
 /// # Example
-/// ```
+/// ```no_run
+/// use diego::gl::setup::view_port;
+///
 /// if view_port(0.0, 0.0, 1920.0, 1080.0) {
 ///     println!("Viewport set successfully!");
 /// } else {
@@ -67,11 +74,28 @@ pub fn clear_color(color: Color) {
 /// ```
 ///
 /// This function casts the input values to the appropriate OpenGL types (`GLint` and `GLsizei`),
-/// and checks for errors using `gl::GetError()`.
+/// and checks for errors using `gl::GetError()`, see `viewport`.
 pub fn view_port(x: f32, y: f32, width: f32, height: f32) -> bool {
+    viewport(x as GLint, y as GLint, width as GLsizei, height as GLsizei)
+}
+
+/// Sets the viewport in framebuffer pixels, with the origin in the lower-left corner.
+///
+/// On HiDPI displays the framebuffer is larger than the logical window size, so pass physical
+/// pixels, e.g. from `Window::framebuffer_size`. The viewport follows the window size
+/// automatically, see `gl::viewport` for nested viewports.
+///
+/// # Returns
+/// `false` if OpenGL reported an error, e.g. for a negative width or height. Setting the
+/// current viewport again is skipped, see `StateCache`.
+pub fn viewport(x: i32, y: i32, width: i32, height: i32) -> bool {
+    if !with_state_cache(|cache| cache.viewport([x, y, width, height])) {
+        return true;
+    }
     unsafe {
-        gl::Viewport(x as GLint, y as GLint, width as GLsizei, height as GLsizei);
+        gl::Viewport(x, y, width, height);
         if gl::GetError() != gl::NO_ERROR {
+            with_state_cache(StateCache::invalidate);
             return false;
         }
         true
@@ -97,6 +121,8 @@ pub fn view_port(x: f32, y: f32, width: f32, height: f32) -> bool {
 /// # Example
 ///
 /// ```no_run
+/// use diego::gl::setup::get_max_viewport_dims;
+///
 /// let max_dims = get_max_viewport_dims();
 /// println!("Max viewport dimensions: {}x{}", max_dims.width, max_dims.height);
 /// ```
@@ -107,18 +133,194 @@ pub fn get_max_viewport_dims() -> Dimension2D<f32> {
     Dimension2D::new(max_viewport_dims[0] as f32, max_viewport_dims[1] as f32)
 }
 
+/// Enables an OpenGL capability. Skipped if it is already enabled, see `StateCache`.
 pub fn enable(capability: Capability) {
-    unsafe {
-        gl::Enable(capability.to_gl_constant());
+    if with_state_cache(|cache| cache.set_capability(capability, true)) {
+        unsafe {
+            gl::Enable(capability.to_gl_constant());
+        }
     }
 }
 
+/// Disables an OpenGL capability. Skipped if it is already disabled, see `StateCache`.
 pub fn disable(capability: Capability) {
-    unsafe {
-        gl::Disable(capability.to_gl_constant());
+    if with_state_cache(|cache| cache.set_capability(capability, false)) {
+        unsafe {
+            gl::Disable(capability.to_gl_constant());
+        }
     }
 }
 
+/// Returns whether an OpenGL capability is enabled.
+/// The driver is only queried if the state is not known to the `StateCache`.
 pub fn is_enabled(capability: Capability) -> bool {
-    unsafe { gl::IsEnabled(capability.to_gl_constant()) > 0 }
+    if let Some(enabled) = with_state_cache(|cache| cache.capability(capability)) {
+        return enabled;
+    }
+    let enabled = unsafe { gl::IsEnabled(capability.to_gl_constant()) > 0 };
+    with_state_cache(|cache| cache.remember_capability(capability, enabled));
+    enabled
+}
+
+/// Sets the blend factors of the color and alpha channels (`glBlendFuncSeparate`).
+/// Skipped if they are already set, see `StateCache`.
+pub fn blend_func_separate(src_rgb: BlendFactor, dst_rgb: BlendFactor, src_alpha: BlendFactor, dst_alpha: BlendFactor) {
+    if with_state_cache(|cache| cache.blend_func([src_rgb, dst_rgb, src_alpha, dst_alpha])) {
+        unsafe {
+            gl::BlendFuncSeparate(
+                src_rgb.to_gl_constant(),
+                dst_rgb.to_gl_constant(),
+                src_alpha.to_gl_constant(),
+                dst_alpha.to_gl_constant(),
+            );
+        }
+    }
+}
+
+/// Sets the blend equations of the color and alpha channels (`glBlendEquationSeparate`).
+/// Skipped if they are already set, see `StateCache`.
+pub fn blend_equation_separate(rgb: BlendEquation, alpha: BlendEquation) {
+    if with_state_cache(|cache| cache.blend_equation([rgb, alpha])) {
+        unsafe {
+            gl::BlendEquationSeparate(rgb.to_gl_constant(), alpha.to_gl_constant());
+        }
+    }
+}
+
+/// Sets the constant color used by `BlendFactor::ConstantColor` and `BlendFactor::ConstantAlpha`.
+/// Skipped if it is already set, see `StateCache`.
+pub fn blend_color(color: Color) {
+    if with_state_cache(|cache| cache.blend_color([color.r, color.g, color.b, color.a])) {
+        unsafe {
+            gl::BlendColor(color.r, color.g, color.b, color.a);
+        }
+    }
+}
+
+/// Enables or disables writing of the individual color channels. Skipped if the mask is
+/// already set, see `StateCache`.
+pub fn color_mask(red: bool, green: bool, blue: bool, alpha: bool) {
+    if with_state_cache(|cache| cache.color_mask([red, green, blue, alpha])) {
+        unsafe {
+            gl::ColorMask(red as GLboolean, green as GLboolean, blue as GLboolean, alpha as GLboolean);
+        }
+    }
+}
+
+/// Sets the comparison of the depth test. Skipped if it is already set, see `StateCache`.
+pub fn depth_func(function: CompareFunction) {
+    if with_state_cache(|cache| cache.depth_func(function)) {
+        unsafe {
+            gl::DepthFunc(function.to_gl_constant());
+        }
+    }
+}
+
+/// Enables or disables writing to the depth buffer. Skipped if it is already set,
+/// see `StateCache`.
+pub fn depth_mask(write: bool) {
+    if with_state_cache(|cache| cache.depth_mask(write)) {
+        unsafe {
+            gl::DepthMask(write as GLboolean);
+        }
+    }
+}
+
+/// Sets the function, reference value and mask of the stencil test for both faces.
+/// Skipped if they are already set, see `StateCache`.
+pub fn stencil_func(function: CompareFunction, reference: i32, mask: u32) {
+    let stencil_func = StencilFunc { function, reference, mask };
+    if with_state_cache(|cache| cache.stencil_func(stencil_func)) {
+        unsafe {
+            gl::StencilFunc(function.to_gl_constant(), reference, mask);
+        }
+    }
+}
+
+/// Sets the stencil actions for both faces.
+/// # Arguments
+/// * `stencil_fail` - The action when the stencil test fails.
+/// * `depth_fail` - The action when the stencil test passes but the depth test fails.
+/// * `pass` - The action when both tests pass.
+///
+/// Skipped if they are already set, see `StateCache`.
+pub fn stencil_op(stencil_fail: StencilOp, depth_fail: StencilOp, pass: StencilOp) {
+    if with_state_cache(|cache| cache.stencil_op([stencil_fail, depth_fail, pass])) {
+        unsafe {
+            gl::StencilOp(stencil_fail.to_gl_constant(), depth_fail.to_gl_constant(), pass.to_gl_constant());
+        }
+    }
+}
+
+/// Sets the bits of the stencil buffer that can be written. Skipped if it is already set,
+/// see `StateCache`.
+pub fn stencil_mask(mask: u32) {
+    if with_state_cache(|cache| cache.stencil_mask(mask)) {
+        unsafe {
+            gl::StencilMask(mask);
+        }
+    }
+}
+
+/// Selects the faces that are culled while `Capability::CullFace` is enabled.
+/// Skipped if they are already selected, see `StateCache`.
+pub fn cull_face(face: Face) {
+    if with_state_cache(|cache| cache.cull_face(face)) {
+        unsafe {
+            gl::CullFace(face.to_gl_constant());
+        }
+    }
+}
+
+/// Sets the winding order of front-facing polygons. Skipped if it is already set,
+/// see `StateCache`.
+pub fn front_face(front_face: FrontFace) {
+    if with_state_cache(|cache| cache.front_face(front_face)) {
+        unsafe {
+            gl::FrontFace(front_face.to_gl_constant());
+        }
+    }
+}
+
+/// Sets how front and back facing polygons are rasterized. Skipped if the mode is already set,
+/// see `StateCache`.
+///
+/// OpenGL ES has no `glPolygonMode` and always fills polygons; there the call is skipped, with
+/// a warning for modes other than `PolygonMode::Fill`.
+pub fn polygon_mode(mode: PolygonMode) {
+    if !gl::PolygonMode::is_loaded() {
+        if mode != PolygonMode::Fill {
+            log_warn!("glPolygonMode is not available, ignoring polygon mode {:?}", mode);
+        }
+        return;
+    }
+    if with_state_cache(|cache| cache.polygon_mode(mode)) {
+        unsafe {
+            gl::PolygonMode(gl::FRONT_AND_BACK, mode.to_gl_constant());
+        }
+    }
+}
+
+/// Sets the depth offset of polygons while `Capability::PolygonOffsetFill` is enabled.
+/// # Arguments
+/// * `factor` - Scales the maximum depth slope of the polygon.
+/// * `units` - Scales the smallest resolvable depth difference.
+///
+/// Skipped if the offset is already set, see `StateCache`.
+pub fn polygon_offset(factor: f32, units: f32) {
+    if with_state_cache(|cache| cache.polygon_offset([factor, units])) {
+        unsafe {
+            gl::PolygonOffset(factor, units);
+        }
+    }
+}
+
+/// Sets the scissor box in window coordinates, used while `Capability::ScissorTest` is enabled.
+/// Skipped if it is already set, see `StateCache`.
+pub fn scissor(x: i32, y: i32, width: i32, height: i32) {
+    if with_state_cache(|cache| cache.scissor([x, y, width, height])) {
+        unsafe {
+            gl::Scissor(x, y, width, height);
+        }
+    }
 }
@@ -0,0 +1,235 @@
+use crate::core::runtime_error::RuntimeError;
+use crate::core::runtime_info::RuntimeInfo;
+use crate::create_runtime_info;
+use crate::gl::state::get_integer_v;
+use crate::gl::state_cache::with_state_cache;
+use crate::gl::types::{GlGetParameter, ShaderType};
+use crate::gl::GLConstant;
+use ogl::types::{GLchar, GLenum, GLint, GLsizei, GLuint};
+use std::ffi::CString;
+use std::ptr;
+
+/// Creates a new, empty shader object of the given type.
+/// # Arguments
+/// * `shader_type` - The pipeline stage the shader is written for.
+/// # Returns
+/// The ID of the created shader object.
+pub fn create_shader(shader_type: ShaderType) -> Result<u32, RuntimeError> {
+    let shader_id = unsafe { gl::CreateShader(shader_type.to_gl_constant()) };
+    if shader_id == 0 {
+        return Err(RuntimeError::ObjectCreationError(create_runtime_info!(format!(
+            "Failed to create {} shader",
+            shader_type
+        ))));
+    }
+    Ok(shader_id)
+}
+
+/// Replaces the source code of a shader object.
+/// # Arguments
+/// * `shader_id` - The ID of the shader whose source should be replaced.
+/// * `source` - The GLSL source code. Interior NUL bytes are not allowed.
+pub fn shader_source(shader_id: u32, source: &str) -> Result<(), RuntimeError> {
+    let c_source = CString::new(source).map_err(|_| {
+        RuntimeError::InvalidBufferSize(create_runtime_info!("Shader source contains NUL byte"))
+    })?;
+    unsafe {
+        gl::ShaderSource(shader_id, 1, &c_source.as_ptr(), ptr::null());
+    }
+    Ok(())
+}
+
+/// Compiles the source code that has been stored in the shader object.
+/// Use `get_shader_compile_status` and `get_shader_info_log` to check the result.
+pub fn compile_shader(shader_id: u32) {
+    unsafe {
+        gl::CompileShader(shader_id);
+    }
+}
+
+/// Returns `true` if the last compile operation on the shader was successful.
+pub fn get_shader_compile_status(shader_id: u32) -> bool {
+    let mut status: GLint = 0;
+    unsafe {
+        gl::GetShaderiv(shader_id, gl::COMPILE_STATUS, &mut status);
+    }
+    status == gl::TRUE as GLint
+}
+
+/// Retrieves the information log of a shader object, e.g. the compiler output.
+pub fn get_shader_info_log(shader_id: u32) -> String {
+    let mut length: GLint = 0;
+    unsafe {
+        gl::GetShaderiv(shader_id, gl::INFO_LOG_LENGTH, &mut length);
+    }
+    read_info_log(length, |capacity, written, buffer| unsafe {
+        gl::GetShaderInfoLog(shader_id, capacity, written, buffer);
+    })
+}
+
+/// Deletes a shader object. Shaders that are still attached to a program are flagged for
+/// deletion and released once they are detached.
+pub fn delete_shader(shader_id: u32) {
+    unsafe {
+        gl::DeleteShader(shader_id);
+    }
+}
+
+/// Creates a new, empty program object.
+/// # Returns
+/// The ID of the created program object.
+pub fn create_program() -> Result<u32, RuntimeError> {
+    let program_id = unsafe { gl::CreateProgram() };
+    if program_id == 0 {
+        return Err(RuntimeError::ObjectCreationError(create_runtime_info!(
+            "Failed to create shader program"
+        )));
+    }
+    Ok(program_id)
+}
+
+/// Attaches a compiled shader object to a program object.
+pub fn attach_shader(program_id: u32, shader_id: u32) {
+    unsafe {
+        gl::AttachShader(program_id, shader_id);
+    }
+}
+
+/// Detaches a shader object from a program object.
+pub fn detach_shader(program_id: u32, shader_id: u32) {
+    unsafe {
+        gl::DetachShader(program_id, shader_id);
+    }
+}
+
+/// Links all shaders attached to the program object.
+/// Use `get_program_link_status` and `get_program_info_log` to check the result.
+pub fn link_program(program_id: u32) {
+    unsafe {
+        gl::LinkProgram(program_id);
+    }
+}
+
+/// Returns `true` if the last link operation on the program was successful.
+pub fn get_program_link_status(program_id: u32) -> bool {
+    let mut status: GLint = 0;
+    unsafe {
+        gl::GetProgramiv(program_id, gl::LINK_STATUS, &mut status);
+    }
+    status == gl::TRUE as GLint
+}
+
+/// Retrieves the information log of a program object, e.g. the linker output.
+pub fn get_program_info_log(program_id: u32) -> String {
+    let mut length: GLint = 0;
+    unsafe {
+        gl::GetProgramiv(program_id, gl::INFO_LOG_LENGTH, &mut length);
+    }
+    read_info_log(length, |capacity, written, buffer| unsafe {
+        gl::GetProgramInfoLog(program_id, capacity, written, buffer);
+    })
+}
+
+/// Installs the program object as part of the current rendering state.
+/// # Arguments
+/// * `program_id` - The ID of the program to use. Passing `0` unbinds the current program.
+///
+/// Skipped if the program is already in use, see `StateCache`.
+pub fn use_program(program_id: u32) {
+    if with_state_cache(|cache| cache.use_program(program_id)) {
+        unsafe {
+            gl::UseProgram(program_id);
+        }
+    }
+}
+
+/// Returns the ID of the program in use, or `0` if none is.
+/// The driver is only queried if the program is not known to the `StateCache`.
+pub fn get_current_program() -> u32 {
+    if let Some(program_id) = with_state_cache(|cache| cache.program()) {
+        return program_id;
+    }
+    let program_id = get_integer_v(GlGetParameter::CurrentProgram).max(0) as u32;
+    with_state_cache(|cache| cache.remember_program(program_id));
+    program_id
+}
+
+/// Deletes a program object.
+pub fn delete_program(program_id: u32) {
+    unsafe {
+        gl::DeleteProgram(program_id);
+    }
+    with_state_cache(|cache| cache.program_deleted(program_id));
+}
+
+/// Returns the location of a uniform variable, or `None` if the program has no active
+/// uniform with that name.
+pub fn get_uniform_location(program_id: u32, name: &str) -> Option<i32> {
+    let c_name = CString::new(name).ok()?;
+    let location = unsafe { gl::GetUniformLocation(program_id as GLuint, c_name.as_ptr()) };
+    (location >= 0).then_some(location)
+}
+
+/// Returns `true` if the driver supports retrieving and loading program binaries.
+pub fn is_program_binary_supported() -> bool {
+    gl::GetProgramBinary::is_loaded()
+        && gl::ProgramBinary::is_loaded()
+        && get_integer_v(GlGetParameter::NumProgramBinaryFormats) > 0
+}
+
+/// Tells the driver that the binary of the program will be retrieved after linking.
+/// Must be set before `link_program` is called.
+pub fn set_program_binary_retrievable(program_id: u32, retrievable: bool) {
+    unsafe {
+        gl::ProgramParameteri(program_id, gl::PROGRAM_BINARY_RETRIEVABLE_HINT, retrievable as GLint);
+    }
+}
+
+/// Retrieves the binary representation of a linked program.
+/// # Returns
+/// The driver specific binary format and the binary data, or `None` if the program has no
+/// binary available.
+pub fn get_program_binary(program_id: u32) -> Option<(u32, Vec<u8>)> {
+    let mut length: GLint = 0;
+    unsafe {
+        gl::GetProgramiv(program_id, gl::PROGRAM_BINARY_LENGTH, &mut length);
+    }
+    if length <= 0 {
+        return None;
+    }
+
+    let mut binary: Vec<u8> = vec![0; length as usize];
+    let mut written: GLint = 0;
+    let mut format: GLenum = 0;
+    unsafe {
+        gl::GetProgramBinary(program_id, length, &mut written, &mut format, binary.as_mut_ptr() as *mut _);
+    }
+    if written <= 0 {
+        return None;
+    }
+    binary.truncate(written as usize);
+    Some((format, binary))
+}
+
+/// Loads a program binary previously retrieved with `get_program_binary`.
+/// The driver may reject the binary (e.g. after a driver update); check the result with
+/// `get_program_link_status`.
+pub fn program_binary(program_id: u32, format: u32, binary: &[u8]) {
+    unsafe {
+        gl::ProgramBinary(program_id, format, binary.as_ptr() as *const _, binary.len() as GLsizei);
+    }
+}
+
+fn read_info_log<F>(length: GLint, read: F) -> String
+where
+    F: FnOnce(GLint, *mut GLint, *mut GLchar),
+{
+    if length <= 0 {
+        return String::new();
+    }
+    let mut buffer: Vec<u8> = vec![0; length as usize];
+    let mut written: GLint = 0;
+    read(length, &mut written, buffer.as_mut_ptr() as *mut GLchar);
+    buffer.truncate(written.max(0) as usize);
+    String::from_utf8_lossy(&buffer).trim_end().to_string()
+}
@@ -1,11 +1,13 @@
-use crate::gl::types::GlGetParameter;
+use crate::gl::types::{GlGetParameter, GlStringName};
+use crate::gl::GLConstant;
+use std::ffi::CStr;
 
 /// `get_integer_v` is a utility function that retrieves the value of a specific OpenGL
 /// parameter as an integer.
 /// # Parameters
 /// * `get_parameter: GlGetParameter` - An enum or type representing the OpenGL parameter to
-///    retrieve. This value is converted into a format that the OpenGL function `glGetIntegerv`
-///    expects.
+///   retrieve. This value is converted into a format that the OpenGL function `glGetIntegerv`
+///   expects.
 pub fn get_integer_v(get_parameter: GlGetParameter) -> i32 {
     unsafe {
         let mut value = 0;
@@ -38,7 +40,10 @@ pub fn get_integer_v(get_parameter: GlGetParameter) -> i32 {
 ///
 /// # Example
 /// ```no_run
-/// let viewport_dims = get_integer_v_array(GlGetParameter::MaxViewportDims, 2);
+/// use diego::gl::state::get_integer_v_array;
+/// use diego::gl::types::GlGetParameter;
+///
+/// let viewport_dims = get_integer_v_array(GlGetParameter::MaxViewPortDims, 2);
 /// println!("Max viewport dimensions: {}x{}", viewport_dims[0], viewport_dims[1]);
 /// ```
 ///
@@ -55,3 +60,44 @@ pub fn get_integer_v_array(gl_get_parameter: GlGetParameter, size: usize) -> Vec
     }
     values
 }
+
+/// Retrieves one element of an indexed OpenGL parameter, e.g. the maximum compute work group
+/// count along one axis.
+/// # Parameters
+/// * `get_parameter` - The indexed parameter to retrieve.
+/// * `index` - The index of the element, e.g. `0` to `2` for the x, y and z axis.
+pub fn get_integer_i_v(get_parameter: GlGetParameter, index: u32) -> i32 {
+    unsafe {
+        let mut value = 0;
+        gl::GetIntegeri_v(get_parameter.into(), index, &mut value);
+        value
+    }
+}
+
+/// Returns a driver string like the vendor or renderer name.
+/// # Arguments
+/// * `name` - The string to query.
+/// # Returns
+/// The string reported by `glGetString`, or an empty string if the query failed.
+pub fn get_string(name: GlStringName) -> String {
+    unsafe {
+        let value = gl::GetString(name.to_gl_constant());
+        if value.is_null() {
+            return String::new();
+        }
+        CStr::from_ptr(value as *const _).to_string_lossy().into_owned()
+    }
+}
+
+/// Returns the names of all extensions supported by the current context.
+/// # Returns
+/// The extension names reported by `glGetStringi`, e.g. `GL_ARB_direct_state_access`.
+pub fn get_extensions() -> Vec<String> {
+    let count = get_integer_v(GlGetParameter::NumExtensions).max(0) as u32;
+    (0..count)
+        .filter_map(|index| unsafe {
+            let value = gl::GetStringi(gl::EXTENSIONS, index);
+            (!value.is_null()).then(|| CStr::from_ptr(value as *const _).to_string_lossy().into_owned())
+        })
+        .collect()
+}
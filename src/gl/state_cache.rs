@@ -0,0 +1,466 @@
+use crate::gl::types::{
+    BlendEquation, BlendFactor, BufferType, Capability, CompareFunction, Face, FrontFace, PolygonMode,
+    StencilOp, TextureTarget,
+};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+thread_local! {
+    static STATE_CACHE: RefCell<StateCache> = RefCell::new(StateCache::new());
+}
+
+/// Counts the state changes that were sent to the driver and those skipped as redundant.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct StateCacheStats {
+    pub issued: u64,
+    pub skipped: u64,
+}
+
+/// The stencil test function, reference value and mask, as passed to `glStencilFunc`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StencilFunc {
+    pub function: CompareFunction,
+    pub reference: i32,
+    pub mask: u32,
+}
+
+/// A CPU-side copy of the OpenGL state set through the `gl` functions.
+///
+/// Every state-changing function in `gl` asks the cache first and only calls into the driver if
+/// the value differs from the shadowed one; binding queries like `get_bound_buffer` answer from
+/// the cache without a `glGet*` round trip once the value is known. Values start out unknown,
+/// so the first call always reaches the driver.
+///
+/// The cache only sees changes made through `gl`. Call `invalidate_state_cache` after foreign
+/// code (e.g. a UI library) has touched the OpenGL state.
+///
+/// There is one cache per thread, matching the thread the OpenGL context is current on.
+///
+/// # Example
+/// ```no_run
+/// use diego::gl::setup::enable;
+/// use diego::gl::state_cache::{reset_state_cache_stats, state_cache_stats};
+/// use diego::gl::types::Capability;
+///
+/// enable(Capability::DepthTest);
+/// enable(Capability::DepthTest); // skipped
+/// println!("skipped {} redundant calls", state_cache_stats().skipped);
+/// reset_state_cache_stats();
+/// ```
+#[derive(Debug)]
+pub struct StateCache {
+    enabled: bool,
+    buffers: HashMap<BufferType, u32>,
+    vertex_array: Option<u32>,
+    program: Option<u32>,
+    active_texture_unit: Option<u32>,
+    textures: HashMap<(u32, TextureTarget), u32>,
+    capabilities: HashMap<Capability, bool>,
+    blend_func: Option<[BlendFactor; 4]>,
+    blend_equation: Option<[BlendEquation; 2]>,
+    blend_color: Option<[f32; 4]>,
+    color_mask: Option<[bool; 4]>,
+    depth_func: Option<CompareFunction>,
+    depth_mask: Option<bool>,
+    stencil_func: Option<StencilFunc>,
+    stencil_op: Option<[StencilOp; 3]>,
+    stencil_mask: Option<u32>,
+    cull_face: Option<Face>,
+    front_face: Option<FrontFace>,
+    polygon_mode: Option<PolygonMode>,
+    polygon_offset: Option<[f32; 2]>,
+    viewport: Option<[i32; 4]>,
+    scissor: Option<[i32; 4]>,
+    stats: StateCacheStats,
+}
+
+impl Default for StateCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StateCache {
+    /// Creates an enabled cache with every value unknown.
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            buffers: HashMap::new(),
+            vertex_array: None,
+            program: None,
+            active_texture_unit: None,
+            textures: HashMap::new(),
+            capabilities: HashMap::new(),
+            blend_func: None,
+            blend_equation: None,
+            blend_color: None,
+            color_mask: None,
+            depth_func: None,
+            depth_mask: None,
+            stencil_func: None,
+            stencil_op: None,
+            stencil_mask: None,
+            cull_face: None,
+            front_face: None,
+            polygon_mode: None,
+            polygon_offset: None,
+            viewport: None,
+            scissor: None,
+            stats: StateCacheStats::default(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enables or disables skipping. While disabled every call reaches the driver; the values
+    /// are forgotten, so nothing stale is used after enabling the cache again.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.invalidate();
+    }
+
+    /// Forgets every shadowed value. The statistics are kept.
+    pub fn invalidate(&mut self) {
+        *self = Self {
+            enabled: self.enabled,
+            stats: self.stats,
+            ..Self::new()
+        };
+    }
+
+    pub fn stats(&self) -> StateCacheStats {
+        self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = StateCacheStats::default();
+    }
+
+    // The following functions record a state change and return whether it has to be sent
+    // to the driver.
+
+    pub fn bind_buffer(&mut self, target: BufferType, buffer_id: u32) -> bool {
+        track_entry(&mut self.buffers, target, buffer_id, self.enabled, &mut self.stats)
+    }
+
+    pub fn bind_vertex_array(&mut self, vao: u32) -> bool {
+        let changed = track(&mut self.vertex_array, vao, self.enabled, &mut self.stats);
+        if changed {
+            // The element array buffer binding is part of the VAO state.
+            self.buffers.remove(&BufferType::ElementArrayBuffer);
+        }
+        changed
+    }
+
+    pub fn use_program(&mut self, program_id: u32) -> bool {
+        track(&mut self.program, program_id, self.enabled, &mut self.stats)
+    }
+
+    pub fn active_texture(&mut self, unit: u32) -> bool {
+        track(&mut self.active_texture_unit, unit, self.enabled, &mut self.stats)
+    }
+
+    pub fn bind_texture(&mut self, target: TextureTarget, texture_id: u32) -> bool {
+        match self.active_texture_unit {
+            Some(unit) => track_entry(&mut self.textures, (unit, target), texture_id, self.enabled, &mut self.stats),
+            None => {
+                self.stats.issued += 1;
+                true
+            }
+        }
+    }
+
+    pub fn set_capability(&mut self, capability: Capability, enabled: bool) -> bool {
+        track_entry(&mut self.capabilities, capability, enabled, self.enabled, &mut self.stats)
+    }
+
+    /// Records `glBlendFuncSeparate(src_rgb, dst_rgb, src_alpha, dst_alpha)`.
+    pub fn blend_func(&mut self, factors: [BlendFactor; 4]) -> bool {
+        track(&mut self.blend_func, factors, self.enabled, &mut self.stats)
+    }
+
+    /// Records `glBlendEquationSeparate(rgb, alpha)`.
+    pub fn blend_equation(&mut self, equations: [BlendEquation; 2]) -> bool {
+        track(&mut self.blend_equation, equations, self.enabled, &mut self.stats)
+    }
+
+    pub fn blend_color(&mut self, color: [f32; 4]) -> bool {
+        track(&mut self.blend_color, color, self.enabled, &mut self.stats)
+    }
+
+    /// Records `glColorMask(red, green, blue, alpha)`.
+    pub fn color_mask(&mut self, mask: [bool; 4]) -> bool {
+        track(&mut self.color_mask, mask, self.enabled, &mut self.stats)
+    }
+
+    pub fn depth_func(&mut self, function: CompareFunction) -> bool {
+        track(&mut self.depth_func, function, self.enabled, &mut self.stats)
+    }
+
+    pub fn depth_mask(&mut self, write: bool) -> bool {
+        track(&mut self.depth_mask, write, self.enabled, &mut self.stats)
+    }
+
+    pub fn stencil_func(&mut self, stencil_func: StencilFunc) -> bool {
+        track(&mut self.stencil_func, stencil_func, self.enabled, &mut self.stats)
+    }
+
+    /// Records `glStencilOp(stencil_fail, depth_fail, pass)`.
+    pub fn stencil_op(&mut self, ops: [StencilOp; 3]) -> bool {
+        track(&mut self.stencil_op, ops, self.enabled, &mut self.stats)
+    }
+
+    pub fn stencil_mask(&mut self, mask: u32) -> bool {
+        track(&mut self.stencil_mask, mask, self.enabled, &mut self.stats)
+    }
+
+    pub fn cull_face(&mut self, face: Face) -> bool {
+        track(&mut self.cull_face, face, self.enabled, &mut self.stats)
+    }
+
+    pub fn front_face(&mut self, front_face: FrontFace) -> bool {
+        track(&mut self.front_face, front_face, self.enabled, &mut self.stats)
+    }
+
+    pub fn polygon_mode(&mut self, mode: PolygonMode) -> bool {
+        track(&mut self.polygon_mode, mode, self.enabled, &mut self.stats)
+    }
+
+    /// Records `glPolygonOffset(factor, units)`.
+    pub fn polygon_offset(&mut self, offset: [f32; 2]) -> bool {
+        track(&mut self.polygon_offset, offset, self.enabled, &mut self.stats)
+    }
+
+    /// Records `glViewport(x, y, width, height)`.
+    pub fn viewport(&mut self, rect: [i32; 4]) -> bool {
+        track(&mut self.viewport, rect, self.enabled, &mut self.stats)
+    }
+
+    /// Records `glScissor(x, y, width, height)`.
+    pub fn scissor(&mut self, rect: [i32; 4]) -> bool {
+        track(&mut self.scissor, rect, self.enabled, &mut self.stats)
+    }
+
+    // Notifications about calls that change shadowed state as a side effect.
+
+    /// Deleted buffers are unbound from every target they were bound to.
+    pub fn buffers_deleted(&mut self, buffer_ids: &[u32]) {
+        for binding in self.buffers.values_mut() {
+            if buffer_ids.contains(binding) {
+                *binding = 0;
+            }
+        }
+    }
+
+    /// A deleted VAO that is bound reverts the binding to `0`.
+    pub fn vertex_array_deleted(&mut self, vao: u32) {
+        if self.vertex_array == Some(vao) {
+            self.vertex_array = Some(0);
+            self.buffers.remove(&BufferType::ElementArrayBuffer);
+        }
+    }
+
+    /// A deleted program stays in use until another one is selected, but its ID may be reused
+    /// afterward, so the binding is forgotten.
+    pub fn program_deleted(&mut self, program_id: u32) {
+        if self.program == Some(program_id) {
+            self.program = None;
+        }
+    }
+
+    /// Deleted textures are unbound from every unit they were bound to.
+    pub fn textures_deleted(&mut self, texture_ids: &[u32]) {
+        for binding in self.textures.values_mut() {
+            if texture_ids.contains(binding) {
+                *binding = 0;
+            }
+        }
+    }
+
+    /// `glBindTextureUnit` binds to the target of the texture, which is not known here, so
+    /// all bindings of the unit are forgotten.
+    pub fn texture_unit_bound(&mut self, unit: u32) {
+        self.textures.retain(|(bound_unit, _), _| *bound_unit != unit);
+    }
+
+    /// `glVertexArrayElementBuffer` on the bound VAO changes the element array buffer binding.
+    pub fn vertex_array_element_buffer_set(&mut self, vao: u32, buffer_id: u32) {
+        if self.vertex_array == Some(vao) {
+            self.buffers.insert(BufferType::ElementArrayBuffer, buffer_id);
+        }
+    }
+
+    // Queries; `None` if the value is unknown.
+
+    pub fn buffer(&self, target: BufferType) -> Option<u32> {
+        self.buffers.get(&target).copied()
+    }
+
+    pub fn vertex_array(&self) -> Option<u32> {
+        self.vertex_array
+    }
+
+    pub fn program(&self) -> Option<u32> {
+        self.program
+    }
+
+    /// Returns the texture bound to `target` of the active texture unit.
+    pub fn texture(&self, target: TextureTarget) -> Option<u32> {
+        let unit = self.active_texture_unit?;
+        self.textures.get(&(unit, target)).copied()
+    }
+
+    pub fn capability(&self, capability: Capability) -> Option<bool> {
+        self.capabilities.get(&capability).copied()
+    }
+
+    pub fn viewport_rect(&self) -> Option<[i32; 4]> {
+        self.viewport
+    }
+
+    pub fn scissor_rect(&self) -> Option<[i32; 4]> {
+        self.scissor
+    }
+
+    // Values read back from the driver, so later queries can be answered from the cache.
+
+    pub fn remember_buffer(&mut self, target: BufferType, buffer_id: u32) {
+        self.buffers.insert(target, buffer_id);
+    }
+
+    pub fn remember_vertex_array(&mut self, vao: u32) {
+        self.vertex_array = Some(vao);
+    }
+
+    pub fn remember_program(&mut self, program_id: u32) {
+        self.program = Some(program_id);
+    }
+
+    pub fn remember_texture(&mut self, target: TextureTarget, texture_id: u32) {
+        if let Some(unit) = self.active_texture_unit {
+            self.textures.insert((unit, target), texture_id);
+        }
+    }
+
+    pub fn remember_capability(&mut self, capability: Capability, enabled: bool) {
+        self.capabilities.insert(capability, enabled);
+    }
+}
+
+fn track<T: PartialEq>(slot: &mut Option<T>, value: T, enabled: bool, stats: &mut StateCacheStats) -> bool {
+    if enabled && slot.as_ref() == Some(&value) {
+        stats.skipped += 1;
+        return false;
+    }
+    *slot = enabled.then_some(value);
+    stats.issued += 1;
+    true
+}
+
+fn track_entry<K: Hash + Eq, V: PartialEq>(map: &mut HashMap<K, V>,
+                                           key: K,
+                                           value: V,
+                                           enabled: bool,
+                                           stats: &mut StateCacheStats) -> bool {
+    if enabled && map.get(&key) == Some(&value) {
+        stats.skipped += 1;
+        return false;
+    }
+    if enabled {
+        map.insert(key, value);
+    }
+    stats.issued += 1;
+    true
+}
+
+/// Runs `f` with the state cache of the current thread.
+pub fn with_state_cache<R, F: FnOnce(&mut StateCache) -> R>(f: F) -> R {
+    STATE_CACHE.with(|cache| f(&mut cache.borrow_mut()))
+}
+
+/// Forgets every shadowed value, e.g. after foreign code changed the OpenGL state.
+pub fn invalidate_state_cache() {
+    with_state_cache(StateCache::invalidate);
+}
+
+/// Enables or disables skipping of redundant calls, see `StateCache::set_enabled`.
+pub fn set_state_cache_enabled(enabled: bool) {
+    with_state_cache(|cache| cache.set_enabled(enabled));
+}
+
+/// Returns how many state changes were issued and skipped since the last reset.
+pub fn state_cache_stats() -> StateCacheStats {
+    with_state_cache(|cache| cache.stats())
+}
+
+/// Resets the statistics, e.g. at the start of every frame.
+pub fn reset_state_cache_stats() {
+    with_state_cache(StateCache::reset_stats);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redundant_calls_are_skipped() {
+        let mut cache = StateCache::new();
+        assert!(cache.bind_buffer(BufferType::ArrayBuffer, 3));
+        assert!(!cache.bind_buffer(BufferType::ArrayBuffer, 3));
+        assert!(cache.bind_buffer(BufferType::UniformBuffer, 3));
+        assert!(cache.set_capability(Capability::Blend, true));
+        assert!(!cache.set_capability(Capability::Blend, true));
+        assert!(cache.set_capability(Capability::Blend, false));
+        assert_eq!(cache.stats(), StateCacheStats { issued: 4, skipped: 2 });
+
+        cache.invalidate();
+        assert!(cache.bind_buffer(BufferType::ArrayBuffer, 3));
+        assert_eq!(cache.stats().issued, 5);
+    }
+
+    #[test]
+    fn test_vao_change_forgets_element_buffer() {
+        let mut cache = StateCache::new();
+        cache.bind_vertex_array(1);
+        cache.bind_buffer(BufferType::ElementArrayBuffer, 7);
+        assert!(!cache.bind_vertex_array(1));
+        assert_eq!(cache.buffer(BufferType::ElementArrayBuffer), Some(7));
+
+        cache.bind_vertex_array(2);
+        assert_eq!(cache.buffer(BufferType::ElementArrayBuffer), None);
+        assert!(cache.bind_buffer(BufferType::ElementArrayBuffer, 7));
+
+        cache.vertex_array_deleted(2);
+        assert_eq!(cache.vertex_array(), Some(0));
+    }
+
+    #[test]
+    fn test_textures_are_tracked_per_unit() {
+        let mut cache = StateCache::new();
+        assert!(cache.bind_texture(TextureTarget::Texture2D, 5));
+        assert!(cache.bind_texture(TextureTarget::Texture2D, 5), "active unit is unknown");
+
+        cache.active_texture(0);
+        assert!(cache.bind_texture(TextureTarget::Texture2D, 5));
+        assert!(!cache.bind_texture(TextureTarget::Texture2D, 5));
+        cache.active_texture(1);
+        assert!(cache.bind_texture(TextureTarget::Texture2D, 5));
+
+        cache.textures_deleted(&[5]);
+        assert_eq!(cache.texture(TextureTarget::Texture2D), Some(0));
+        cache.texture_unit_bound(1);
+        assert_eq!(cache.texture(TextureTarget::Texture2D), None);
+    }
+
+    #[test]
+    fn test_disabled_cache_issues_every_call() {
+        let mut cache = StateCache::new();
+        cache.set_enabled(false);
+        assert!(cache.depth_mask(true));
+        assert!(cache.depth_mask(true));
+        assert_eq!(cache.stats().skipped, 0);
+    }
+}
@@ -0,0 +1,287 @@
+use crate::core::runtime_error::RuntimeError;
+use crate::core::runtime_info::RuntimeInfo;
+use crate::create_runtime_info;
+use crate::gl::state_cache::with_state_cache;
+use crate::gl::types::{TextureFilter, TextureFormat, TextureTarget, TextureWrap};
+use crate::gl::GLConstant;
+use ogl::types::{GLint, GLsizei, GLvoid};
+
+/// Generates a specified number of texture IDs and returns them as a vector.
+/// # Arguments
+/// * `count` - The number of texture IDs to generate.
+/// # Returns
+/// A vector containing the generated texture IDs.
+pub fn gen_textures(count: u32) -> Result<Vec<u32>, RuntimeError> {
+    let mut textures: Vec<u32> = vec![0; count as usize];
+    unsafe {
+        gl::GenTextures(count as GLsizei, textures.as_mut_ptr());
+    }
+
+    if textures.contains(&0) {
+        let error_code = unsafe { gl::GetError() };
+        if error_code != gl::NO_ERROR {
+            let err_msg = format!("OpenGL error code: {}", error_code);
+            return Err(RuntimeError::OpenGLError(create_runtime_info!(&err_msg), Some(error_code)));
+        }
+        return Err(RuntimeError::ObjectCreationError(create_runtime_info!(
+            "Failed to generate texture objects"
+        )));
+    }
+
+    Ok(textures)
+}
+
+/// Binds a texture to the given target of the active texture unit.
+/// # Arguments
+/// * `target` - The texture target (e.g., `TEXTURE_2D`).
+/// * `texture_id` - The ID of the texture to bind. Passing `0` unbinds the current texture.
+///
+/// Skipped if the texture is already bound to the active unit, see `StateCache`.
+pub fn bind_texture(target: TextureTarget, texture_id: u32) {
+    if with_state_cache(|cache| cache.bind_texture(target, texture_id)) {
+        unsafe {
+            gl::BindTexture(target.to_gl_constant(), texture_id);
+        }
+    }
+}
+
+/// Returns the ID of the texture bound to `target` of the active texture unit, or `0` if none
+/// is bound. The driver is only queried if the binding is not known to the `StateCache`.
+pub fn get_bound_texture(target: TextureTarget) -> u32 {
+    if let Some(texture_id) = with_state_cache(|cache| cache.texture(target)) {
+        return texture_id;
+    }
+    let binding = match target {
+        TextureTarget::Texture1D => gl::TEXTURE_BINDING_1D,
+        TextureTarget::Texture2D => gl::TEXTURE_BINDING_2D,
+        TextureTarget::Texture3D => gl::TEXTURE_BINDING_3D,
+        TextureTarget::Texture2DArray => gl::TEXTURE_BINDING_2D_ARRAY,
+        TextureTarget::TextureCubeMap => gl::TEXTURE_BINDING_CUBE_MAP,
+    };
+    let mut texture_id: GLint = 0;
+    unsafe {
+        gl::GetIntegerv(binding, &mut texture_id);
+    }
+    let texture_id = texture_id.max(0) as u32;
+    with_state_cache(|cache| cache.remember_texture(target, texture_id));
+    texture_id
+}
+
+/// Selects the active texture unit; subsequent `bind_texture` calls affect this unit.
+/// # Arguments
+/// * `unit` - The zero-based index of the texture unit.
+pub fn active_texture(unit: u32) {
+    if with_state_cache(|cache| cache.active_texture(unit)) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + unit);
+        }
+    }
+}
+
+/// Uploads 8-bit pixel data to the texture currently bound to `target`.
+/// Ensure that the texture is properly bound before calling this function.
+/// # Arguments
+/// * `target` - The texture target (e.g., `TEXTURE_2D`).
+/// * `format` - The channel layout of `pixels` and of the texture storage.
+/// * `width` - The width of the image in pixels.
+/// * `height` - The height of the image in pixels.
+/// * `pixels` - The tightly packed pixel rows, starting with the bottom row.
+pub fn tex_image_2d(
+    target: TextureTarget,
+    format: TextureFormat,
+    width: u32,
+    height: u32,
+    pixels: &[u8],
+) {
+    with_tight_unpack_alignment(|| unsafe {
+        gl::TexImage2D(
+            target.to_gl_constant(),
+            0,
+            format.internal_format() as GLint,
+            width as GLsizei,
+            height as GLsizei,
+            0,
+            format.to_gl_constant(),
+            gl::UNSIGNED_BYTE,
+            pixels.as_ptr() as *const GLvoid,
+        );
+    });
+}
+
+/// Runs `upload` with `GL_UNPACK_ALIGNMENT` set to 1 for tightly packed rows and restores the
+/// previous alignment afterwards, so later uploads with padded rows are not affected.
+fn with_tight_unpack_alignment<F: FnOnce()>(upload: F) {
+    let mut alignment: GLint = 4;
+    unsafe {
+        gl::GetIntegerv(gl::UNPACK_ALIGNMENT, &mut alignment);
+        gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+    }
+    upload();
+    if alignment != 1 {
+        unsafe {
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, alignment);
+        }
+    }
+}
+
+/// Sets the minification and magnification filter of the texture bound to `target`.
+pub fn set_texture_filter(target: TextureTarget, min: TextureFilter, mag: TextureFilter) {
+    let target = target.to_gl_constant();
+    unsafe {
+        gl::TexParameteri(target, gl::TEXTURE_MIN_FILTER, min.to_gl_constant() as GLint);
+        gl::TexParameteri(target, gl::TEXTURE_MAG_FILTER, mag.to_gl_constant() as GLint);
+    }
+}
+
+/// Sets the wrapping mode along the S and T axis of the texture bound to `target`.
+pub fn set_texture_wrap(target: TextureTarget, wrap_s: TextureWrap, wrap_t: TextureWrap) {
+    let target = target.to_gl_constant();
+    unsafe {
+        gl::TexParameteri(target, gl::TEXTURE_WRAP_S, wrap_s.to_gl_constant() as GLint);
+        gl::TexParameteri(target, gl::TEXTURE_WRAP_T, wrap_t.to_gl_constant() as GLint);
+    }
+}
+
+/// Generates the complete mipmap chain for the texture bound to `target`.
+pub fn generate_mipmap(target: TextureTarget) {
+    unsafe {
+        gl::GenerateMipmap(target.to_gl_constant());
+    }
+}
+
+/// Deletes the specified textures from OpenGL.
+/// # Arguments
+/// * `textures` - A slice or vector of texture IDs to delete.
+pub fn delete_textures<T: AsRef<[u32]>>(textures: T) {
+    let slice = textures.as_ref();
+    unsafe {
+        gl::DeleteTextures(slice.len() as GLsizei, slice.as_ptr());
+    }
+    with_state_cache(|cache| cache.textures_deleted(slice));
+}
+
+/// Creates a specified number of texture objects with Direct State Access (OpenGL 4.5).
+///
+/// Unlike `gen_textures`, the textures get their target right away, so they can be used with
+/// the `texture_*` functions without being bound first.
+/// # Arguments
+/// * `target` - The texture target (e.g., `TEXTURE_2D`).
+/// * `count` - The number of textures to create.
+/// # Returns
+/// A vector containing the IDs of the created textures.
+pub fn create_textures(target: TextureTarget, count: u32) -> Result<Vec<u32>, RuntimeError> {
+    let mut textures: Vec<u32> = vec![0; count as usize];
+    unsafe {
+        gl::CreateTextures(target.to_gl_constant(), count as GLsizei, textures.as_mut_ptr());
+    }
+
+    if textures.contains(&0) {
+        let error_code = unsafe { gl::GetError() };
+        if error_code != gl::NO_ERROR {
+            let err_msg = format!("OpenGL error code: {}", error_code);
+            return Err(RuntimeError::OpenGLError(create_runtime_info!(&err_msg), Some(error_code)));
+        }
+        return Err(RuntimeError::ObjectCreationError(create_runtime_info!(
+            "Failed to create texture objects"
+        )));
+    }
+
+    Ok(textures)
+}
+
+/// Allocates immutable storage for all mipmap levels of a two-dimensional texture.
+///
+/// The size and format of the storage cannot be changed afterward; the contents can be
+/// replaced with `texture_sub_image_2d`.
+/// # Arguments
+/// * `texture` - The ID of a texture created with `create_textures`.
+/// * `levels` - The number of mipmap levels, see `mipmap_levels`.
+/// * `format` - The format whose sized internal format is used for the storage.
+/// * `width` - The width of the base level in pixels.
+/// * `height` - The height of the base level in pixels.
+pub fn texture_storage_2d(texture: u32, levels: u32, format: TextureFormat, width: u32, height: u32) {
+    unsafe {
+        gl::TextureStorage2D(
+            texture,
+            levels as GLsizei,
+            format.internal_format(),
+            width as GLsizei,
+            height as GLsizei,
+        );
+    }
+}
+
+/// Uploads 8-bit pixel data to the base level of a texture, without binding it.
+/// # Arguments
+/// * `texture` - The ID of a texture with storage of at least `width` x `height` pixels.
+/// * `format` - The channel layout of `pixels`.
+/// * `width` - The width of the image in pixels.
+/// * `height` - The height of the image in pixels.
+/// * `pixels` - The tightly packed pixel rows, starting with the bottom row.
+pub fn texture_sub_image_2d(texture: u32, format: TextureFormat, width: u32, height: u32, pixels: &[u8]) {
+    with_tight_unpack_alignment(|| unsafe {
+        gl::TextureSubImage2D(
+            texture,
+            0,
+            0,
+            0,
+            width as GLsizei,
+            height as GLsizei,
+            format.to_gl_constant(),
+            gl::UNSIGNED_BYTE,
+            pixels.as_ptr() as *const GLvoid,
+        );
+    });
+}
+
+/// Sets the minification and magnification filter of a texture, without binding it.
+pub fn texture_filter(texture: u32, min: TextureFilter, mag: TextureFilter) {
+    unsafe {
+        gl::TextureParameteri(texture, gl::TEXTURE_MIN_FILTER, min.to_gl_constant() as GLint);
+        gl::TextureParameteri(texture, gl::TEXTURE_MAG_FILTER, mag.to_gl_constant() as GLint);
+    }
+}
+
+/// Sets the wrapping mode along the S and T axis of a texture, without binding it.
+pub fn texture_wrap(texture: u32, wrap_s: TextureWrap, wrap_t: TextureWrap) {
+    unsafe {
+        gl::TextureParameteri(texture, gl::TEXTURE_WRAP_S, wrap_s.to_gl_constant() as GLint);
+        gl::TextureParameteri(texture, gl::TEXTURE_WRAP_T, wrap_t.to_gl_constant() as GLint);
+    }
+}
+
+/// Generates the complete mipmap chain of a texture, without binding it.
+pub fn generate_texture_mipmap(texture: u32) {
+    unsafe {
+        gl::GenerateTextureMipmap(texture);
+    }
+}
+
+/// Binds a texture to a texture unit without changing the active texture unit.
+/// # Arguments
+/// * `unit` - The zero-based index of the texture unit.
+/// * `texture` - The ID of the texture. Passing `0` unbinds the texture of the unit.
+pub fn bind_texture_unit(unit: u32, texture: u32) {
+    unsafe {
+        gl::BindTextureUnit(unit, texture);
+    }
+    with_state_cache(|cache| cache.texture_unit_bound(unit));
+}
+
+/// Returns the number of mipmap levels of a complete mipmap chain for the given size.
+pub fn mipmap_levels(width: u32, height: u32) -> u32 {
+    u32::BITS - width.max(height).max(1).leading_zeros()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mipmap_levels() {
+        assert_eq!(mipmap_levels(1, 1), 1);
+        assert_eq!(mipmap_levels(256, 256), 9);
+        assert_eq!(mipmap_levels(300, 17), 9);
+        assert_eq!(mipmap_levels(0, 0), 1);
+    }
+}
@@ -1,4 +1,5 @@
 /// Represents OpenGL capabilities that can be enabled or disabled.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Capability {
     /// Capability to blend pixels.
     Blend,
@@ -17,6 +18,8 @@ pub enum Capability {
     PolygonSmooth,
     /// Capability to update stencil buffer.
     StencilTest,
+    /// Capability to offset the depth values of filled polygons, see `polygon_offset`.
+    PolygonOffsetFill,
 }
 
 impl GLConstant for Capability {
@@ -31,6 +34,7 @@ impl GLConstant for Capability {
             Capability::LineSmooth => gl::LINE_SMOOTH,
             Capability::PolygonSmooth => gl::POLYGON_SMOOTH,
             Capability::StencilTest => gl::STENCIL_TEST,
+            Capability::PolygonOffsetFill => gl::POLYGON_OFFSET_FILL,
         }
     }
 }
@@ -47,9 +51,12 @@ mod clear_buffer_mask {
 
 use ogl::types::{GLenum, GLuint};
 pub use clear_buffer_mask::ClearBufferMask;
+use crate::core::ConversionError;
 use crate::gl::GLConstant;
+use std::fmt::{Display, Formatter};
 
 #[repr(u32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum GlGetParameter {
     MaxTextureSize = gl::MAX_TEXTURE_SIZE,
     MaxVertexAttribs = gl::MAX_VERTEX_ATTRIBS,
@@ -65,30 +72,78 @@ pub enum GlGetParameter {
     MaxElementsIndices = gl::MAX_ELEMENTS_INDICES,
     MaxElementsVertices = gl::MAX_ELEMENTS_VERTICES,
     Max3DTextureSize = gl::MAX_3D_TEXTURE_SIZE,
-    VertexArrayBinding = gl::VERTEX_ATTRIB_BINDING,
+    MaxCubeMapTextureSize = gl::MAX_CUBE_MAP_TEXTURE_SIZE,
+    MaxArrayTextureLayers = gl::MAX_ARRAY_TEXTURE_LAYERS,
+    MaxRenderbufferSize = gl::MAX_RENDERBUFFER_SIZE,
+    MaxSamples = gl::MAX_SAMPLES,
+    MaxColorAttachments = gl::MAX_COLOR_ATTACHMENTS,
+    MaxUniformBlockSize = gl::MAX_UNIFORM_BLOCK_SIZE,
+    MaxUniformBufferBindings = gl::MAX_UNIFORM_BUFFER_BINDINGS,
+    MaxShaderStorageBlockSize = gl::MAX_SHADER_STORAGE_BLOCK_SIZE,
+    MaxShaderStorageBufferBindings = gl::MAX_SHADER_STORAGE_BUFFER_BINDINGS,
+    MaxComputeWorkGroupInvocations = gl::MAX_COMPUTE_WORK_GROUP_INVOCATIONS,
+    MaxComputeWorkGroupCount = gl::MAX_COMPUTE_WORK_GROUP_COUNT,
+    MaxComputeWorkGroupSize = gl::MAX_COMPUTE_WORK_GROUP_SIZE,
+    VertexArrayBinding = gl::VERTEX_ARRAY_BINDING,
     ArrayBufferBinding = gl::ARRAY_BUFFER_BINDING,
+    ElementArrayBufferBinding = gl::ELEMENT_ARRAY_BUFFER_BINDING,
+    DrawIndirectBufferBinding = gl::DRAW_INDIRECT_BUFFER_BINDING,
+    CurrentProgram = gl::CURRENT_PROGRAM,
+    TextureBinding2D = gl::TEXTURE_BINDING_2D,
+    NumProgramBinaryFormats = gl::NUM_PROGRAM_BINARY_FORMATS,
+    ProgramBinaryFormats = gl::PROGRAM_BINARY_FORMATS,
+    MajorVersion = gl::MAJOR_VERSION,
+    ContextProfileMask = gl::CONTEXT_PROFILE_MASK,
+    ContextFlags = gl::CONTEXT_FLAGS,
+    MinorVersion = gl::MINOR_VERSION,
+    NumExtensions = gl::NUM_EXTENSIONS,
 }
 
-impl From<GLenum> for GlGetParameter {
-    fn from(value: GLenum) -> Self {
+impl TryFrom<GLenum> for GlGetParameter {
+    type Error = ConversionError<GLenum>;
+
+    fn try_from(value: GLenum) -> Result<Self, Self::Error> {
         match value {
-            gl::MAX_TEXTURE_SIZE => GlGetParameter::MaxTextureSize,
-            gl::MAX_VERTEX_ATTRIBS => GlGetParameter::MaxVertexAttribs,
-            gl::VIEWPORT => GlGetParameter::Viewport,
-            gl::MAX_VIEWPORT_DIMS => GlGetParameter::MaxViewPortDims,
-            gl::MAX_COMBINED_TEXTURE_IMAGE_UNITS => GlGetParameter::MaxCombinedTextureImageUnits,
-            gl::MAX_TEXTURE_IMAGE_UNITS => GlGetParameter::MaxTextureImageUnits,
-            gl::MAX_VERTEX_TEXTURE_IMAGE_UNITS => GlGetParameter::MaxVertexTextureImageUnits,
-            gl::MAX_FRAGMENT_UNIFORM_COMPONENTS => GlGetParameter::MaxFragmentUniformComponents,
-            gl::MAX_VERTEX_UNIFORM_COMPONENTS => GlGetParameter::MaxVertexUniformComponents,
-            gl::MAX_VARYING_COMPONENTS => GlGetParameter::MaxVaryingComponents,
-            gl::MAX_DRAW_BUFFERS => GlGetParameter::MaxDrawBuffers,
-            gl::MAX_ELEMENTS_INDICES => GlGetParameter::MaxElementsIndices,
-            gl::MAX_ELEMENTS_VERTICES => GlGetParameter::MaxElementsVertices,
-            gl::MAX_3D_TEXTURE_SIZE => GlGetParameter::Max3DTextureSize,
-            gl::VERTEX_ARRAY_BINDING => GlGetParameter::VertexArrayBinding,
-            gl::ARRAY_BUFFER_BINDING => GlGetParameter::ArrayBufferBinding,
-            _ => panic!("Unknown GLenum: {}", value),
+            gl::MAX_TEXTURE_SIZE => Ok(GlGetParameter::MaxTextureSize),
+            gl::MAX_VERTEX_ATTRIBS => Ok(GlGetParameter::MaxVertexAttribs),
+            gl::VIEWPORT => Ok(GlGetParameter::Viewport),
+            gl::MAX_VIEWPORT_DIMS => Ok(GlGetParameter::MaxViewPortDims),
+            gl::MAX_COMBINED_TEXTURE_IMAGE_UNITS => Ok(GlGetParameter::MaxCombinedTextureImageUnits),
+            gl::MAX_TEXTURE_IMAGE_UNITS => Ok(GlGetParameter::MaxTextureImageUnits),
+            gl::MAX_VERTEX_TEXTURE_IMAGE_UNITS => Ok(GlGetParameter::MaxVertexTextureImageUnits),
+            gl::MAX_FRAGMENT_UNIFORM_COMPONENTS => Ok(GlGetParameter::MaxFragmentUniformComponents),
+            gl::MAX_VERTEX_UNIFORM_COMPONENTS => Ok(GlGetParameter::MaxVertexUniformComponents),
+            gl::MAX_VARYING_COMPONENTS => Ok(GlGetParameter::MaxVaryingComponents),
+            gl::MAX_DRAW_BUFFERS => Ok(GlGetParameter::MaxDrawBuffers),
+            gl::MAX_ELEMENTS_INDICES => Ok(GlGetParameter::MaxElementsIndices),
+            gl::MAX_ELEMENTS_VERTICES => Ok(GlGetParameter::MaxElementsVertices),
+            gl::MAX_3D_TEXTURE_SIZE => Ok(GlGetParameter::Max3DTextureSize),
+            gl::MAX_CUBE_MAP_TEXTURE_SIZE => Ok(GlGetParameter::MaxCubeMapTextureSize),
+            gl::MAX_ARRAY_TEXTURE_LAYERS => Ok(GlGetParameter::MaxArrayTextureLayers),
+            gl::MAX_RENDERBUFFER_SIZE => Ok(GlGetParameter::MaxRenderbufferSize),
+            gl::MAX_SAMPLES => Ok(GlGetParameter::MaxSamples),
+            gl::MAX_COLOR_ATTACHMENTS => Ok(GlGetParameter::MaxColorAttachments),
+            gl::MAX_UNIFORM_BLOCK_SIZE => Ok(GlGetParameter::MaxUniformBlockSize),
+            gl::MAX_UNIFORM_BUFFER_BINDINGS => Ok(GlGetParameter::MaxUniformBufferBindings),
+            gl::MAX_SHADER_STORAGE_BLOCK_SIZE => Ok(GlGetParameter::MaxShaderStorageBlockSize),
+            gl::MAX_SHADER_STORAGE_BUFFER_BINDINGS => Ok(GlGetParameter::MaxShaderStorageBufferBindings),
+            gl::MAX_COMPUTE_WORK_GROUP_INVOCATIONS => Ok(GlGetParameter::MaxComputeWorkGroupInvocations),
+            gl::MAX_COMPUTE_WORK_GROUP_COUNT => Ok(GlGetParameter::MaxComputeWorkGroupCount),
+            gl::MAX_COMPUTE_WORK_GROUP_SIZE => Ok(GlGetParameter::MaxComputeWorkGroupSize),
+            gl::VERTEX_ARRAY_BINDING => Ok(GlGetParameter::VertexArrayBinding),
+            gl::ARRAY_BUFFER_BINDING => Ok(GlGetParameter::ArrayBufferBinding),
+            gl::ELEMENT_ARRAY_BUFFER_BINDING => Ok(GlGetParameter::ElementArrayBufferBinding),
+            gl::DRAW_INDIRECT_BUFFER_BINDING => Ok(GlGetParameter::DrawIndirectBufferBinding),
+            gl::CURRENT_PROGRAM => Ok(GlGetParameter::CurrentProgram),
+            gl::TEXTURE_BINDING_2D => Ok(GlGetParameter::TextureBinding2D),
+            gl::NUM_PROGRAM_BINARY_FORMATS => Ok(GlGetParameter::NumProgramBinaryFormats),
+            gl::PROGRAM_BINARY_FORMATS => Ok(GlGetParameter::ProgramBinaryFormats),
+            gl::MAJOR_VERSION => Ok(GlGetParameter::MajorVersion),
+            gl::CONTEXT_PROFILE_MASK => Ok(GlGetParameter::ContextProfileMask),
+            gl::CONTEXT_FLAGS => Ok(GlGetParameter::ContextFlags),
+            gl::MINOR_VERSION => Ok(GlGetParameter::MinorVersion),
+            gl::NUM_EXTENSIONS => Ok(GlGetParameter::NumExtensions),
+            _ => Err(ConversionError::InvalidValue(value)),
         }
     }
 }
@@ -110,12 +165,56 @@ impl From<GlGetParameter> for GLenum {
             GlGetParameter::MaxElementsIndices => gl::MAX_ELEMENTS_INDICES,
             GlGetParameter::MaxElementsVertices => gl::MAX_ELEMENTS_VERTICES,
             GlGetParameter::Max3DTextureSize => gl::MAX_3D_TEXTURE_SIZE,
+            GlGetParameter::MaxCubeMapTextureSize => gl::MAX_CUBE_MAP_TEXTURE_SIZE,
+            GlGetParameter::MaxArrayTextureLayers => gl::MAX_ARRAY_TEXTURE_LAYERS,
+            GlGetParameter::MaxRenderbufferSize => gl::MAX_RENDERBUFFER_SIZE,
+            GlGetParameter::MaxSamples => gl::MAX_SAMPLES,
+            GlGetParameter::MaxColorAttachments => gl::MAX_COLOR_ATTACHMENTS,
+            GlGetParameter::MaxUniformBlockSize => gl::MAX_UNIFORM_BLOCK_SIZE,
+            GlGetParameter::MaxUniformBufferBindings => gl::MAX_UNIFORM_BUFFER_BINDINGS,
+            GlGetParameter::MaxShaderStorageBlockSize => gl::MAX_SHADER_STORAGE_BLOCK_SIZE,
+            GlGetParameter::MaxShaderStorageBufferBindings => gl::MAX_SHADER_STORAGE_BUFFER_BINDINGS,
+            GlGetParameter::MaxComputeWorkGroupInvocations => gl::MAX_COMPUTE_WORK_GROUP_INVOCATIONS,
+            GlGetParameter::MaxComputeWorkGroupCount => gl::MAX_COMPUTE_WORK_GROUP_COUNT,
+            GlGetParameter::MaxComputeWorkGroupSize => gl::MAX_COMPUTE_WORK_GROUP_SIZE,
             GlGetParameter::VertexArrayBinding => gl::VERTEX_ARRAY_BINDING,
             GlGetParameter::ArrayBufferBinding => gl::ARRAY_BUFFER_BINDING,
+            GlGetParameter::ElementArrayBufferBinding => gl::ELEMENT_ARRAY_BUFFER_BINDING,
+            GlGetParameter::DrawIndirectBufferBinding => gl::DRAW_INDIRECT_BUFFER_BINDING,
+            GlGetParameter::CurrentProgram => gl::CURRENT_PROGRAM,
+            GlGetParameter::TextureBinding2D => gl::TEXTURE_BINDING_2D,
+            GlGetParameter::NumProgramBinaryFormats => gl::NUM_PROGRAM_BINARY_FORMATS,
+            GlGetParameter::ProgramBinaryFormats => gl::PROGRAM_BINARY_FORMATS,
+            GlGetParameter::MajorVersion => gl::MAJOR_VERSION,
+            GlGetParameter::ContextProfileMask => gl::CONTEXT_PROFILE_MASK,
+            GlGetParameter::ContextFlags => gl::CONTEXT_FLAGS,
+            GlGetParameter::MinorVersion => gl::MINOR_VERSION,
+            GlGetParameter::NumExtensions => gl::NUM_EXTENSIONS,
+        }
+    }
+}
+
+/// Names of the driver strings that can be queried with `glGetString`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GlStringName {
+    Vendor,
+    Renderer,
+    Version,
+    ShadingLanguageVersion,
+}
+
+impl GLConstant for GlStringName {
+    fn to_gl_constant(self) -> GLuint {
+        match self {
+            GlStringName::Vendor => gl::VENDOR,
+            GlStringName::Renderer => gl::RENDERER,
+            GlStringName::Version => gl::VERSION,
+            GlStringName::ShadingLanguageVersion => gl::SHADING_LANGUAGE_VERSION,
         }
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum BufferType {
     /// Stores vertex attributes like vertex coordinates, normals, texture coordinates, etc.
     ArrayBuffer,
@@ -177,25 +276,27 @@ impl GLConstant for BufferType {
     }
 }
 
-impl From<GLenum> for BufferType {
-    fn from(value: GLenum) -> Self {
+impl TryFrom<GLenum> for BufferType {
+    type Error = ConversionError<GLenum>;
+
+    fn try_from(value: GLenum) -> Result<Self, Self::Error> {
         match value {
-            gl::ARRAY_BUFFER => BufferType::ArrayBuffer,
-            gl::ELEMENT_ARRAY_BUFFER => BufferType::ElementArrayBuffer,
-            gl::UNIFORM_BUFFER => BufferType::UniformBuffer,
-            gl::TEXTURE_BUFFER => BufferType::TextureBuffer,
-            gl::FRAMEBUFFER => BufferType::Framebuffer,
-            gl::RENDERBUFFER => BufferType::Renderbuffer,
-            gl::COPY_READ_BUFFER => BufferType::CopyReadBuffer,
-            gl::COPY_WRITE_BUFFER => BufferType::CopyWriteBuffer,
-            gl::PIXEL_PACK_BUFFER => BufferType::PixelPackBuffer,
-            gl::PIXEL_UNPACK_BUFFER => BufferType::PixelUnpackBuffer,
-            gl::TRANSFORM_FEEDBACK_BUFFER => BufferType::TransformFeedbackBuffer,
-            gl::ATOMIC_COUNTER_BUFFER => BufferType::AtomicCounterBuffer,
-            gl::DRAW_INDIRECT_BUFFER => BufferType::DrawIndirectBuffer,
-            gl::DISPATCH_INDIRECT_BUFFER => BufferType::DispatchIndirectBuffer,
-            gl::SHADER_STORAGE_BUFFER => BufferType::ShaderStorageBuffer,
-            _ => panic!("Unknown GLenum: {}", value),
+            gl::ARRAY_BUFFER => Ok(BufferType::ArrayBuffer),
+            gl::ELEMENT_ARRAY_BUFFER => Ok(BufferType::ElementArrayBuffer),
+            gl::UNIFORM_BUFFER => Ok(BufferType::UniformBuffer),
+            gl::TEXTURE_BUFFER => Ok(BufferType::TextureBuffer),
+            gl::FRAMEBUFFER => Ok(BufferType::Framebuffer),
+            gl::RENDERBUFFER => Ok(BufferType::Renderbuffer),
+            gl::COPY_READ_BUFFER => Ok(BufferType::CopyReadBuffer),
+            gl::COPY_WRITE_BUFFER => Ok(BufferType::CopyWriteBuffer),
+            gl::PIXEL_PACK_BUFFER => Ok(BufferType::PixelPackBuffer),
+            gl::PIXEL_UNPACK_BUFFER => Ok(BufferType::PixelUnpackBuffer),
+            gl::TRANSFORM_FEEDBACK_BUFFER => Ok(BufferType::TransformFeedbackBuffer),
+            gl::ATOMIC_COUNTER_BUFFER => Ok(BufferType::AtomicCounterBuffer),
+            gl::DRAW_INDIRECT_BUFFER => Ok(BufferType::DrawIndirectBuffer),
+            gl::DISPATCH_INDIRECT_BUFFER => Ok(BufferType::DispatchIndirectBuffer),
+            gl::SHADER_STORAGE_BUFFER => Ok(BufferType::ShaderStorageBuffer),
+            _ => Err(ConversionError::InvalidValue(value)),
         }
     }
 }
@@ -242,3 +343,410 @@ impl GLConstant for BufferUsage {
     }
 }
 
+
+/// Represents the programmable pipeline stage a shader object is compiled for.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ShaderType {
+    Vertex,
+    Fragment,
+    Geometry,
+    TessControl,
+    TessEvaluation,
+    Compute,
+}
+
+impl ShaderType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ShaderType::Vertex => "vertex",
+            ShaderType::Fragment => "fragment",
+            ShaderType::Geometry => "geometry",
+            ShaderType::TessControl => "tessellation control",
+            ShaderType::TessEvaluation => "tessellation evaluation",
+            ShaderType::Compute => "compute",
+        }
+    }
+}
+
+impl Display for ShaderType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl GLConstant for ShaderType {
+    fn to_gl_constant(self) -> GLuint {
+        match self {
+            ShaderType::Vertex => gl::VERTEX_SHADER,
+            ShaderType::Fragment => gl::FRAGMENT_SHADER,
+            ShaderType::Geometry => gl::GEOMETRY_SHADER,
+            ShaderType::TessControl => gl::TESS_CONTROL_SHADER,
+            ShaderType::TessEvaluation => gl::TESS_EVALUATION_SHADER,
+            ShaderType::Compute => gl::COMPUTE_SHADER,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TextureTarget {
+    Texture1D,
+    Texture2D,
+    Texture3D,
+    Texture2DArray,
+    TextureCubeMap,
+}
+
+impl GLConstant for TextureTarget {
+    fn to_gl_constant(self) -> GLuint {
+        match self {
+            TextureTarget::Texture1D => gl::TEXTURE_1D,
+            TextureTarget::Texture2D => gl::TEXTURE_2D,
+            TextureTarget::Texture3D => gl::TEXTURE_3D,
+            TextureTarget::Texture2DArray => gl::TEXTURE_2D_ARRAY,
+            TextureTarget::TextureCubeMap => gl::TEXTURE_CUBE_MAP,
+        }
+    }
+}
+
+/// The channel layout of 8-bit texture data.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TextureFormat {
+    /// One channel (`GL_RED` / `GL_R8`).
+    Red,
+    /// Two channels (`GL_RG` / `GL_RG8`).
+    Rg,
+    /// Three channels (`GL_RGB` / `GL_RGB8`).
+    Rgb,
+    /// Four channels (`GL_RGBA` / `GL_RGBA8`).
+    Rgba,
+}
+
+impl TextureFormat {
+    /// Returns the sized internal format used for the texture storage.
+    pub fn internal_format(&self) -> GLenum {
+        match self {
+            TextureFormat::Red => gl::R8,
+            TextureFormat::Rg => gl::RG8,
+            TextureFormat::Rgb => gl::RGB8,
+            TextureFormat::Rgba => gl::RGBA8,
+        }
+    }
+
+    /// Returns the number of 8-bit channels per pixel.
+    pub fn channels(&self) -> usize {
+        match self {
+            TextureFormat::Red => 1,
+            TextureFormat::Rg => 2,
+            TextureFormat::Rgb => 3,
+            TextureFormat::Rgba => 4,
+        }
+    }
+}
+
+impl GLConstant for TextureFormat {
+    fn to_gl_constant(self) -> GLuint {
+        match self {
+            TextureFormat::Red => gl::RED,
+            TextureFormat::Rg => gl::RG,
+            TextureFormat::Rgb => gl::RGB,
+            TextureFormat::Rgba => gl::RGBA,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TextureFilter {
+    Nearest,
+    Linear,
+    NearestMipmapNearest,
+    LinearMipmapNearest,
+    NearestMipmapLinear,
+    LinearMipmapLinear,
+}
+
+impl GLConstant for TextureFilter {
+    fn to_gl_constant(self) -> GLuint {
+        match self {
+            TextureFilter::Nearest => gl::NEAREST,
+            TextureFilter::Linear => gl::LINEAR,
+            TextureFilter::NearestMipmapNearest => gl::NEAREST_MIPMAP_NEAREST,
+            TextureFilter::LinearMipmapNearest => gl::LINEAR_MIPMAP_NEAREST,
+            TextureFilter::NearestMipmapLinear => gl::NEAREST_MIPMAP_LINEAR,
+            TextureFilter::LinearMipmapLinear => gl::LINEAR_MIPMAP_LINEAR,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TextureWrap {
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge,
+    ClampToBorder,
+}
+
+impl GLConstant for TextureWrap {
+    fn to_gl_constant(self) -> GLuint {
+        match self {
+            TextureWrap::Repeat => gl::REPEAT,
+            TextureWrap::MirroredRepeat => gl::MIRRORED_REPEAT,
+            TextureWrap::ClampToEdge => gl::CLAMP_TO_EDGE,
+            TextureWrap::ClampToBorder => gl::CLAMP_TO_BORDER,
+        }
+    }
+}
+
+/// The kind of primitives assembled from the vertices of a draw call.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PrimitiveTopology {
+    Points,
+    Lines,
+    LineStrip,
+    LineLoop,
+    #[default]
+    Triangles,
+    TriangleStrip,
+    TriangleFan,
+}
+
+impl GLConstant for PrimitiveTopology {
+    fn to_gl_constant(self) -> GLuint {
+        match self {
+            PrimitiveTopology::Points => gl::POINTS,
+            PrimitiveTopology::Lines => gl::LINES,
+            PrimitiveTopology::LineStrip => gl::LINE_STRIP,
+            PrimitiveTopology::LineLoop => gl::LINE_LOOP,
+            PrimitiveTopology::Triangles => gl::TRIANGLES,
+            PrimitiveTopology::TriangleStrip => gl::TRIANGLE_STRIP,
+            PrimitiveTopology::TriangleFan => gl::TRIANGLE_FAN,
+        }
+    }
+}
+
+/// The data type of the indices stored in an element array buffer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum IndexType {
+    UnsignedByte,
+    UnsignedShort,
+    UnsignedInt,
+}
+
+impl IndexType {
+    /// Returns the smallest index type that can address `vertex_count` vertices.
+    pub fn for_vertex_count(vertex_count: usize) -> Self {
+        if vertex_count <= u8::MAX as usize + 1 {
+            IndexType::UnsignedByte
+        } else if vertex_count <= u16::MAX as usize + 1 {
+            IndexType::UnsignedShort
+        } else {
+            IndexType::UnsignedInt
+        }
+    }
+
+    /// Returns the size of a single index in bytes.
+    pub fn size(&self) -> usize {
+        match self {
+            IndexType::UnsignedByte => 1,
+            IndexType::UnsignedShort => 2,
+            IndexType::UnsignedInt => 4,
+        }
+    }
+}
+
+impl GLConstant for IndexType {
+    fn to_gl_constant(self) -> GLuint {
+        match self {
+            IndexType::UnsignedByte => gl::UNSIGNED_BYTE,
+            IndexType::UnsignedShort => gl::UNSIGNED_SHORT,
+            IndexType::UnsignedInt => gl::UNSIGNED_INT,
+        }
+    }
+}
+
+/// Factors the source and destination colors are multiplied with before blending.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BlendFactor {
+    Zero,
+    One,
+    SrcColor,
+    OneMinusSrcColor,
+    DstColor,
+    OneMinusDstColor,
+    SrcAlpha,
+    OneMinusSrcAlpha,
+    DstAlpha,
+    OneMinusDstAlpha,
+    ConstantColor,
+    OneMinusConstantColor,
+    ConstantAlpha,
+    OneMinusConstantAlpha,
+    SrcAlphaSaturate,
+}
+
+impl GLConstant for BlendFactor {
+    fn to_gl_constant(self) -> GLuint {
+        match self {
+            BlendFactor::Zero => gl::ZERO,
+            BlendFactor::One => gl::ONE,
+            BlendFactor::SrcColor => gl::SRC_COLOR,
+            BlendFactor::OneMinusSrcColor => gl::ONE_MINUS_SRC_COLOR,
+            BlendFactor::DstColor => gl::DST_COLOR,
+            BlendFactor::OneMinusDstColor => gl::ONE_MINUS_DST_COLOR,
+            BlendFactor::SrcAlpha => gl::SRC_ALPHA,
+            BlendFactor::OneMinusSrcAlpha => gl::ONE_MINUS_SRC_ALPHA,
+            BlendFactor::DstAlpha => gl::DST_ALPHA,
+            BlendFactor::OneMinusDstAlpha => gl::ONE_MINUS_DST_ALPHA,
+            BlendFactor::ConstantColor => gl::CONSTANT_COLOR,
+            BlendFactor::OneMinusConstantColor => gl::ONE_MINUS_CONSTANT_COLOR,
+            BlendFactor::ConstantAlpha => gl::CONSTANT_ALPHA,
+            BlendFactor::OneMinusConstantAlpha => gl::ONE_MINUS_CONSTANT_ALPHA,
+            BlendFactor::SrcAlphaSaturate => gl::SRC_ALPHA_SATURATE,
+        }
+    }
+}
+
+/// How the weighted source and destination colors are combined when blending.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BlendEquation {
+    Add,
+    Subtract,
+    ReverseSubtract,
+    Min,
+    Max,
+}
+
+impl GLConstant for BlendEquation {
+    fn to_gl_constant(self) -> GLuint {
+        match self {
+            BlendEquation::Add => gl::FUNC_ADD,
+            BlendEquation::Subtract => gl::FUNC_SUBTRACT,
+            BlendEquation::ReverseSubtract => gl::FUNC_REVERSE_SUBTRACT,
+            BlendEquation::Min => gl::MIN,
+            BlendEquation::Max => gl::MAX,
+        }
+    }
+}
+
+/// Comparison used by the depth and stencil tests.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CompareFunction {
+    Never,
+    Less,
+    Equal,
+    LessOrEqual,
+    Greater,
+    NotEqual,
+    GreaterOrEqual,
+    Always,
+}
+
+impl GLConstant for CompareFunction {
+    fn to_gl_constant(self) -> GLuint {
+        match self {
+            CompareFunction::Never => gl::NEVER,
+            CompareFunction::Less => gl::LESS,
+            CompareFunction::Equal => gl::EQUAL,
+            CompareFunction::LessOrEqual => gl::LEQUAL,
+            CompareFunction::Greater => gl::GREATER,
+            CompareFunction::NotEqual => gl::NOTEQUAL,
+            CompareFunction::GreaterOrEqual => gl::GEQUAL,
+            CompareFunction::Always => gl::ALWAYS,
+        }
+    }
+}
+
+/// Action applied to the stencil buffer value depending on the test results.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum StencilOp {
+    Keep,
+    Zero,
+    Replace,
+    Increment,
+    IncrementWrap,
+    Decrement,
+    DecrementWrap,
+    Invert,
+}
+
+impl GLConstant for StencilOp {
+    fn to_gl_constant(self) -> GLuint {
+        match self {
+            StencilOp::Keep => gl::KEEP,
+            StencilOp::Zero => gl::ZERO,
+            StencilOp::Replace => gl::REPLACE,
+            StencilOp::Increment => gl::INCR,
+            StencilOp::IncrementWrap => gl::INCR_WRAP,
+            StencilOp::Decrement => gl::DECR,
+            StencilOp::DecrementWrap => gl::DECR_WRAP,
+            StencilOp::Invert => gl::INVERT,
+        }
+    }
+}
+
+/// Selects front-facing, back-facing or both kinds of polygons, e.g. for face culling.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Face {
+    Front,
+    Back,
+    FrontAndBack,
+}
+
+impl GLConstant for Face {
+    fn to_gl_constant(self) -> GLuint {
+        match self {
+            Face::Front => gl::FRONT,
+            Face::Back => gl::BACK,
+            Face::FrontAndBack => gl::FRONT_AND_BACK,
+        }
+    }
+}
+
+/// The winding order of front-facing polygons.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FrontFace {
+    CounterClockwise,
+    Clockwise,
+}
+
+impl GLConstant for FrontFace {
+    fn to_gl_constant(self) -> GLuint {
+        match self {
+            FrontFace::CounterClockwise => gl::CCW,
+            FrontFace::Clockwise => gl::CW,
+        }
+    }
+}
+
+/// How polygons are rasterized.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PolygonMode {
+    /// The interior of the polygon is filled.
+    Fill,
+    /// Only the edges are drawn, e.g. for wireframe rendering.
+    Line,
+    /// Only the vertices are drawn.
+    Point,
+}
+
+impl GLConstant for PolygonMode {
+    fn to_gl_constant(self) -> GLuint {
+        match self {
+            PolygonMode::Fill => gl::FILL,
+            PolygonMode::Line => gl::LINE,
+            PolygonMode::Point => gl::POINT,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_enums_are_rejected() {
+        assert_eq!(GlGetParameter::try_from(gl::MAX_SAMPLES).ok(), Some(GlGetParameter::MaxSamples));
+        assert!(GlGetParameter::try_from(gl::FLOAT).is_err());
+        assert!(matches!(BufferType::try_from(gl::UNIFORM_BUFFER), Ok(BufferType::UniformBuffer)));
+        assert!(BufferType::try_from(0).is_err());
+    }
+}
@@ -1,4 +1,7 @@
-use crate::glx::vertex_attribute_type::VertexAttributeType;
+use crate::glx::vertex_data_type::VertexDataType;
+use crate::gl::state::get_integer_v;
+use crate::gl::state_cache::with_state_cache;
+use crate::gl::types::GlGetParameter;
 use ogl::types::GLboolean;
 use std::ffi::c_void;
 use std::ptr;
@@ -21,17 +24,33 @@ pub fn delete_vertex_array(vao: u32) {
     unsafe {
         gl::DeleteVertexArrays(1, &vao);
     }
+    with_state_cache(|cache| cache.vertex_array_deleted(vao));
 }
 
 /// Binds the specified OpenGL Vertex Array Object (VAO) for subsequent rendering operations.
 /// # Parameters
 /// * `vao` - The ID of the VAO to bind. Passing `0` will unbind the currently bound VAO.
+///
+/// Skipped if the VAO is already bound, see `StateCache`.
 pub fn bind_vertex_array(vao: u32) {
-    unsafe {
-        gl::BindVertexArray(vao);
+    if with_state_cache(|cache| cache.bind_vertex_array(vao)) {
+        unsafe {
+            gl::BindVertexArray(vao);
+        }
     }
 }
 
+/// Returns the ID of the bound VAO, or `0` if none is bound.
+/// The driver is only queried if the binding is not known to the `StateCache`.
+pub fn get_bound_vertex_array() -> u32 {
+    if let Some(vao) = with_state_cache(|cache| cache.vertex_array()) {
+        return vao;
+    }
+    let vao = get_integer_v(GlGetParameter::VertexArrayBinding).max(0) as u32;
+    with_state_cache(|cache| cache.remember_vertex_array(vao));
+    vao
+}
+
 /// Specifies the format of the vertex attribute data for the currently bound vertex buffer.
 ///
 /// This function sets the pointer to the attribute array, specifying how data is stored in the
@@ -44,18 +63,18 @@ pub fn bind_vertex_array(vao: u32) {
 ///
 /// * `index` - The index of the generic vertex attribute to modify.
 /// * `size` - The number of components per attribute
-///    (e.g., 1 for a float, 2 for a vec2, 3 for a vec3).
-/// * `attribute_type` - The data type of each component in the attribute
-///    (e.g., `GL_FLOAT`, `GL_INT`).
+///   (e.g., 1 for a float, 2 for a vec2, 3 for a vec3).
+/// * `data_type` - The data type of each component in the attribute
+///   (e.g., `GL_FLOAT`, `GL_INT`).
 /// * `normalized` - Whether fixed-point data values should be normalized (`true`) or not (`false`)
-///    when accessed.
+///   when accessed.
 /// * `stride` - The byte offset between consecutive attributes. If set to 0, the attributes are
-///    tightly packed.
+///   tightly packed.
 /// * `ptr` - A pointer to the first component of the first attribute in the array. If `None`,
-///    it defaults to a null pointer.
+///   it defaults to a null pointer.
 pub fn vertex_attrib_pointer(index: u32,
                              size: i32,
-                             attribute_type: VertexAttributeType,
+                             data_type: VertexDataType,
                              normalized: bool,
                              stride: i32,
                              ptr: Option<*const c_void>) {
@@ -63,10 +82,10 @@ pub fn vertex_attrib_pointer(index: u32,
         gl::VertexAttribPointer(
             index,
             size,
-            attribute_type.into(),
+            data_type.into(),
             normalized as GLboolean,
             stride,
-            ptr.unwrap_or_else(|| ptr::null())
+            ptr.unwrap_or(ptr::null())
         );
     }
 }
@@ -86,26 +105,26 @@ pub fn vertex_attrib_pointer(index: u32,
 ///
 /// * `index` - The index of the generic vertex attribute to modify.
 /// * `size` - The number of components per attribute
-///    (e.g., 1 for a single integer, 2 for a pair, 3 for a triplet).
-/// * `attribute_type` - The data type of each component in the attribute
-///    (e.g., `GL_INT`, `GL_UNSIGNED_INT`).
+///   (e.g., 1 for a single integer, 2 for a pair, 3 for a triplet).
+/// * `data_type` - The data type of each component in the attribute
+///   (e.g., `GL_INT`, `GL_UNSIGNED_INT`).
 /// * `stride` - The byte offset between consecutive attributes. If set to 0, the attributes are
-///    tightly packed.
+///   tightly packed.
 /// * `ptr` - A pointer to the first component of the first attribute in the array. If `None`,
-///    it defaults to a null pointer.
+///   it defaults to a null pointer.
 ///
 pub fn vertex_attrib_pointer_i(index: u32,
                                size: i32,
-                               attribute_type: VertexAttributeType,
+                               data_type: VertexDataType,
                                stride: i32,
                                ptr: Option<*const c_void>) {
     unsafe {
         gl::VertexAttribIPointer(
             index,
             size,
-            attribute_type.into(),
+            data_type.into(),
             stride,
-            ptr.unwrap_or_else(|| ptr::null())
+            ptr.unwrap_or(ptr::null())
         );
     }
 }
@@ -125,29 +144,39 @@ pub fn vertex_attrib_pointer_i(index: u32,
 ///
 /// * `index` - The index of the generic vertex attribute to modify.
 /// * `size` - The number of components per attribute
-///    (e.g., 1 for a single double, 2 for a vec2, 3 for a vec3).
-/// * `attribute_type` - The data type of each component in the attribute
-///    (should typically be `GL_DOUBLE`).
+///   (e.g., 1 for a single double, 2 for a vec2, 3 for a vec3).
+/// * `data_type` - The data type of each component in the attribute
+///   (should typically be `GL_DOUBLE`).
 /// * `stride` - The byte offset between consecutive attributes. If set to 0, the attributes are
-///    tightly packed.
+///   tightly packed.
 /// * `ptr` - A pointer to the first component of the first attribute in the array.
-///    If `None`, it defaults to a null pointer.
+///   If `None`, it defaults to a null pointer.
 pub fn vertex_attrib_pointer_l(index: u32,
                                size: i32,
-                               attribut_type: VertexAttributeType,
+                               data_type: VertexDataType,
                                stride: i32,
                                ptr: Option<*const c_void>) {
     unsafe {
         gl::VertexAttribLPointer(
             index,
             size,
-            attribut_type.into(),
+            data_type.into(),
             stride,
-            ptr.unwrap_or_else(|| ptr::null())
+            ptr.unwrap_or(ptr::null())
         );
     }
 }
 
+/// Sets the rate at which a generic vertex attribute advances during instanced rendering.
+/// # Parameters
+/// * `index` - The index of the generic vertex attribute.
+/// * `divisor` - `0` advances the attribute once per vertex, `n` once every `n` instances.
+pub fn vertex_attrib_divisor(index: u32, divisor: u32) {
+    unsafe {
+        gl::VertexAttribDivisor(index, divisor);
+    }
+}
+
 /// Enables a generic vertex attribute array at the specified index.
 /// This function activates a vertex attribute array, allowing OpenGL to use the attribute data
 /// during rendering. The index corresponds to the generic vertex attribute that
@@ -206,3 +235,123 @@ pub fn disable_vertex_array_attrib(vao: u32, index: u32) {
         gl::DisableVertexArrayAttrib(vao, index);
     }
 }
+
+/// Creates a new Vertex Array Object (VAO) with Direct State Access (OpenGL 4.5).
+///
+/// Unlike `gen_vertex_array`, the VAO is initialized right away, so it can be configured with
+/// the `vertex_array_*` functions without being bound first.
+/// # Returns
+/// * `u32` - The ID of the newly created VAO.
+pub fn create_vertex_array() -> u32 {
+    unsafe {
+        let mut vao_id = 0;
+        gl::CreateVertexArrays(1, &mut vao_id);
+        vao_id
+    }
+}
+
+/// Attaches a buffer to a vertex buffer binding point of a VAO.
+/// # Parameters
+/// * `vao` - The ID of the VAO.
+/// * `binding_index` - The vertex buffer binding point; attributes refer to it with
+///   `vertex_array_attrib_binding`.
+/// * `buffer` - The ID of the buffer to attach.
+/// * `offset` - The byte offset of the first element in the buffer.
+/// * `stride` - The byte distance between consecutive elements. Unlike the attribute pointer
+///   functions, `0` is not replaced by the tightly packed size.
+pub fn vertex_array_vertex_buffer(vao: u32, binding_index: u32, buffer: u32, offset: usize, stride: i32) {
+    unsafe {
+        gl::VertexArrayVertexBuffer(vao, binding_index, buffer, offset as isize, stride);
+    }
+}
+
+/// Attaches an element array buffer to a VAO.
+/// # Parameters
+/// * `vao` - The ID of the VAO.
+/// * `buffer` - The ID of the buffer holding the indices. Passing `0` detaches the current one.
+pub fn vertex_array_element_buffer(vao: u32, buffer: u32) {
+    unsafe {
+        gl::VertexArrayElementBuffer(vao, buffer);
+    }
+    with_state_cache(|cache| cache.vertex_array_element_buffer_set(vao, buffer));
+}
+
+/// Specifies the format of a floating-point vertex attribute of a VAO.
+///
+/// This is the DSA counterpart of `vertex_attrib_pointer`; the buffer and stride are set per
+/// binding point with `vertex_array_vertex_buffer`.
+/// # Parameters
+/// * `vao` - The ID of the VAO.
+/// * `index` - The index of the generic vertex attribute.
+/// * `size` - The number of components per attribute.
+/// * `data_type` - The data type of each component.
+/// * `normalized` - Whether integer values are normalized when accessed.
+/// * `relative_offset` - The byte offset of the attribute within an element.
+pub fn vertex_array_attrib_format(vao: u32,
+                                  index: u32,
+                                  size: i32,
+                                  data_type: VertexDataType,
+                                  normalized: bool,
+                                  relative_offset: u32) {
+    unsafe {
+        gl::VertexArrayAttribFormat(vao, index, size, data_type.into(), normalized as GLboolean, relative_offset);
+    }
+}
+
+/// Specifies the format of an integer vertex attribute of a VAO; the DSA counterpart of
+/// `vertex_attrib_pointer_i`.
+/// # Parameters
+/// * `vao` - The ID of the VAO.
+/// * `index` - The index of the generic vertex attribute.
+/// * `size` - The number of components per attribute.
+/// * `data_type` - The integer data type of each component.
+/// * `relative_offset` - The byte offset of the attribute within an element.
+pub fn vertex_array_attrib_format_i(vao: u32,
+                                    index: u32,
+                                    size: i32,
+                                    data_type: VertexDataType,
+                                    relative_offset: u32) {
+    unsafe {
+        gl::VertexArrayAttribIFormat(vao, index, size, data_type.into(), relative_offset);
+    }
+}
+
+/// Specifies the format of a double-precision vertex attribute of a VAO; the DSA counterpart
+/// of `vertex_attrib_pointer_l`.
+/// # Parameters
+/// * `vao` - The ID of the VAO.
+/// * `index` - The index of the generic vertex attribute.
+/// * `size` - The number of components per attribute.
+/// * `data_type` - The data type of each component (should be `GL_DOUBLE`).
+/// * `relative_offset` - The byte offset of the attribute within an element.
+pub fn vertex_array_attrib_format_l(vao: u32,
+                                    index: u32,
+                                    size: i32,
+                                    data_type: VertexDataType,
+                                    relative_offset: u32) {
+    unsafe {
+        gl::VertexArrayAttribLFormat(vao, index, size, data_type.into(), relative_offset);
+    }
+}
+
+/// Selects the vertex buffer binding point a vertex attribute of a VAO reads from.
+/// # Parameters
+/// * `vao` - The ID of the VAO.
+/// * `index` - The index of the generic vertex attribute.
+/// * `binding_index` - The vertex buffer binding point.
+pub fn vertex_array_attrib_binding(vao: u32, index: u32, binding_index: u32) {
+    unsafe {
+        gl::VertexArrayAttribBinding(vao, index, binding_index);
+    }
+}
+
+/// Sets the rate at which all attributes of a vertex buffer binding point advance.
+/// # Parameters
+/// * `vao` - The ID of the VAO.
+/// * `binding_index` - The vertex buffer binding point.
+/// * `divisor` - `0` advances once per vertex, `n` once every `n` instances.
+pub fn vertex_array_binding_divisor(vao: u32, binding_index: u32, divisor: u32) {
+    unsafe {
+        gl::VertexArrayBindingDivisor(vao, binding_index, divisor);
+    }
+}
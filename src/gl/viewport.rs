@@ -0,0 +1,252 @@
+use crate::display::types::Size;
+use crate::gl::setup::{disable, enable, scissor, viewport};
+use crate::gl::types::Capability;
+use crate::log_warn;
+use std::cell::RefCell;
+
+thread_local! {
+    static VIEWPORT_STACK: RefCell<ViewportStack> = RefCell::new(ViewportStack::default());
+}
+
+/// A rectangle in framebuffer pixels, with the origin in the lower-left corner like all
+/// OpenGL window coordinates.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Rect {
+    pub const fn new(x: i32, y: i32, width: i32, height: i32) -> Self {
+        Self { x, y, width, height }
+    }
+
+    /// Returns the rectangle covering a framebuffer of the given size.
+    pub fn from_size(size: Size) -> Self {
+        Self::new(0, 0, size.width as i32, size.height as i32)
+    }
+
+    /// Converts a rectangle in logical pixels with the origin in the upper-left corner, as
+    /// used by window events and UI layouts, to framebuffer pixels.
+    ///
+    /// The edges are rounded individually, so adjacent logical rectangles stay adjacent on
+    /// HiDPI displays with fractional scale factors.
+    /// # Arguments
+    /// * `scale_factor` - The ratio of framebuffer to logical pixels, see `Window::scale_factor`.
+    /// * `framebuffer_height` - The height of the framebuffer in pixels, to flip the y axis.
+    pub fn from_logical(x: f64, y: f64, width: f64, height: f64, scale_factor: f64, framebuffer_height: i32) -> Self {
+        let left = (x * scale_factor).round() as i32;
+        let top = (y * scale_factor).round() as i32;
+        let right = ((x + width) * scale_factor).round() as i32;
+        let bottom = ((y + height) * scale_factor).round() as i32;
+        Self::new(left, framebuffer_height - bottom, right - left, bottom - top)
+    }
+
+    /// Returns the overlapping part of both rectangles; an empty rectangle if they do not
+    /// overlap.
+    pub fn intersection(&self, other: &Rect) -> Rect {
+        let left = self.x.max(other.x);
+        let bottom = self.y.max(other.y);
+        let right = (self.x + self.width).min(other.x + other.width);
+        let top = (self.y + self.height).min(other.y + other.height);
+        Rect::new(left, bottom, (right - left).max(0), (top - bottom).max(0))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width <= 0 || self.height <= 0
+    }
+}
+
+/// Nested viewports and scissor boxes on top of the default framebuffer.
+///
+/// Without pushed viewports, the viewport covers the whole framebuffer. Scissor boxes are
+/// clipped to the enclosing one, so nested UI panels cannot draw outside their parents.
+///
+/// This is the pure bookkeeping; the free functions of this module apply the changes to the
+/// OpenGL state of the current thread.
+#[derive(Debug, Default)]
+pub struct ViewportStack {
+    framebuffer: Rect,
+    viewports: Vec<Rect>,
+    scissors: Vec<Rect>,
+}
+
+impl ViewportStack {
+    pub fn set_framebuffer_size(&mut self, size: Size) {
+        self.framebuffer = Rect::from_size(size);
+    }
+
+    pub fn framebuffer(&self) -> Rect {
+        self.framebuffer
+    }
+
+    /// Returns the innermost viewport, or the whole framebuffer if none was pushed.
+    pub fn viewport(&self) -> Rect {
+        self.viewports.last().copied().unwrap_or(self.framebuffer)
+    }
+
+    /// Returns the innermost scissor box, or `None` if the scissor test is off.
+    pub fn scissor(&self) -> Option<Rect> {
+        self.scissors.last().copied()
+    }
+
+    pub fn push_viewport(&mut self, rect: Rect) {
+        self.viewports.push(rect);
+    }
+
+    /// Removes the innermost viewport; `None` if no viewport was pushed.
+    pub fn pop_viewport(&mut self) -> Option<Rect> {
+        self.viewports.pop()
+    }
+
+    /// Pushes a scissor box, clipped to the enclosing one.
+    ///
+    /// # Returns
+    /// The clipped box.
+    pub fn push_scissor(&mut self, rect: Rect) -> Rect {
+        let clipped = match self.scissors.last() {
+            Some(enclosing) => rect.intersection(enclosing),
+            None => rect,
+        };
+        self.scissors.push(clipped);
+        clipped
+    }
+
+    /// Removes the innermost scissor box; `None` if no box was pushed.
+    pub fn pop_scissor(&mut self) -> Option<Rect> {
+        self.scissors.pop()
+    }
+}
+
+fn apply_viewport(rect: Rect) {
+    viewport(rect.x, rect.y, rect.width, rect.height);
+}
+
+fn apply_scissor(rect: Option<Rect>) {
+    match rect {
+        Some(rect) => {
+            enable(Capability::ScissorTest);
+            scissor(rect.x, rect.y, rect.width, rect.height);
+        }
+        None => disable(Capability::ScissorTest),
+    }
+}
+
+fn with_viewport_stack<R, F: FnOnce(&mut ViewportStack) -> R>(f: F) -> R {
+    VIEWPORT_STACK.with(|stack| f(&mut stack.borrow_mut()))
+}
+
+/// Sets the size of the default framebuffer in pixels and updates the viewport if no
+/// viewport is pushed. Called by the application whenever the window is resized.
+pub fn set_framebuffer_size(size: Size) {
+    apply_viewport(with_viewport_stack(|stack| {
+        stack.set_framebuffer_size(size);
+        stack.viewport()
+    }));
+}
+
+/// Returns the innermost viewport, or the whole framebuffer if none was pushed.
+pub fn current_viewport() -> Rect {
+    with_viewport_stack(|stack| stack.viewport())
+}
+
+/// Returns the innermost scissor box, or `None` if the scissor test is off.
+pub fn current_scissor() -> Option<Rect> {
+    with_viewport_stack(|stack| stack.scissor())
+}
+
+/// Sets the viewport until the matching `pop_viewport`, e.g. for one half of a split screen.
+///
+/// # Example
+/// ```no_run
+/// use diego::gl::viewport::{current_viewport, pop_viewport, push_viewport, Rect};
+///
+/// let screen = current_viewport();
+/// let half = screen.width / 2;
+/// push_viewport(Rect::new(0, 0, half, screen.height));
+/// // draw the first player's view
+/// pop_viewport();
+/// push_viewport(Rect::new(half, 0, screen.width - half, screen.height));
+/// // draw the second player's view
+/// pop_viewport();
+/// ```
+pub fn push_viewport(rect: Rect) {
+    with_viewport_stack(|stack| stack.push_viewport(rect));
+    apply_viewport(rect);
+}
+
+/// Restores the viewport that was active before the matching `push_viewport`.
+pub fn pop_viewport() {
+    let (popped, viewport) = with_viewport_stack(|stack| (stack.pop_viewport(), stack.viewport()));
+    if popped.is_none() {
+        log_warn!("pop_viewport called without a pushed viewport");
+    }
+    apply_viewport(viewport);
+}
+
+/// Enables the scissor test with the box clipped to the enclosing scissor box, until the
+/// matching `pop_scissor`.
+///
+/// # Returns
+/// The clipped box; it is empty if the box lies outside the enclosing one.
+pub fn push_scissor(rect: Rect) -> Rect {
+    let clipped = with_viewport_stack(|stack| stack.push_scissor(rect));
+    apply_scissor(Some(clipped));
+    clipped
+}
+
+/// Restores the enclosing scissor box, or disables the scissor test after the outermost box.
+pub fn pop_scissor() {
+    let (popped, scissor) = with_viewport_stack(|stack| (stack.pop_scissor(), stack.scissor()));
+    if popped.is_none() {
+        log_warn!("pop_scissor called without a pushed scissor box");
+    }
+    apply_scissor(scissor);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_logical() {
+        // A 100x50 logical panel in the upper-left corner of a 800x600 window at 150%.
+        let rect = Rect::from_logical(0.0, 0.0, 100.0, 50.0, 1.5, 900);
+        assert_eq!(rect, Rect::new(0, 825, 150, 75));
+
+        let left = Rect::from_logical(0.0, 0.0, 33.3, 10.0, 1.25, 100);
+        let right = Rect::from_logical(33.3, 0.0, 33.3, 10.0, 1.25, 100);
+        assert_eq!(left.x + left.width, right.x);
+    }
+
+    #[test]
+    fn test_nested_scissors_are_clipped() {
+        let mut stack = ViewportStack::default();
+        stack.set_framebuffer_size(Size::new(800, 600));
+        assert_eq!(stack.viewport(), Rect::new(0, 0, 800, 600));
+        assert_eq!(stack.scissor(), None);
+
+        stack.push_scissor(Rect::new(100, 100, 200, 200));
+        assert_eq!(stack.push_scissor(Rect::new(250, 0, 100, 150)), Rect::new(250, 100, 50, 50));
+        assert!(stack.push_scissor(Rect::new(0, 0, 10, 10)).is_empty());
+
+        stack.pop_scissor();
+        stack.pop_scissor();
+        assert_eq!(stack.scissor(), Some(Rect::new(100, 100, 200, 200)));
+        stack.pop_scissor();
+        assert_eq!(stack.pop_scissor(), None);
+    }
+
+    #[test]
+    fn test_viewport_falls_back_to_framebuffer() {
+        let mut stack = ViewportStack::default();
+        stack.set_framebuffer_size(Size::new(800, 600));
+        stack.push_viewport(Rect::new(0, 0, 400, 600));
+        stack.set_framebuffer_size(Size::new(1600, 1200));
+        assert_eq!(stack.viewport(), Rect::new(0, 0, 400, 600));
+        stack.pop_viewport();
+        assert_eq!(stack.viewport(), Rect::new(0, 0, 1600, 1200));
+    }
+}
@@ -0,0 +1,248 @@
+use crate::core::runtime_error::RuntimeError;
+use crate::core::runtime_info::RuntimeInfo;
+use crate::create_runtime_info;
+use crate::gl::buffer::{
+    bind_buffer, buffer_data, buffer_storage_uninitialized, buffer_sub_data, create_buffers,
+    delete_buffers, gen_buffers, get_bound_buffer, get_buffer_sub_data, get_named_buffer_sub_data,
+    named_buffer_data, named_buffer_storage_uninitialized, named_buffer_sub_data,
+};
+use crate::gl::dsa::is_dsa_enabled;
+use crate::gl::types::{BufferType, BufferUsage};
+use crate::glx::{Bindable, BindableState};
+use bytemuck::Pod;
+use std::marker::PhantomData;
+use std::ops::Range;
+
+/// A typed OpenGL buffer object holding elements of type `T`.
+///
+/// `T` must be `Pod` (plain old data, see the `bytemuck` crate), so the elements can be copied
+/// to and from the GPU byte by byte. Vertex types usually derive it together with `Zeroable`:
+///
+/// ```no_run
+/// use bytemuck::{Pod, Zeroable};
+/// use diego::gl::types::{BufferType, BufferUsage};
+/// use diego::glx::buffer::Buffer;
+///
+/// #[repr(C)]
+/// #[derive(Clone, Copy, Pod, Zeroable)]
+/// struct ColoredVertex {
+///     position: [f32; 2],
+///     color: [f32; 3],
+/// }
+///
+/// let vertices = [ColoredVertex { position: [0.0, 0.5], color: [1.0, 0.0, 0.0] }];
+/// let mut buffer =
+///     Buffer::new_with_data(BufferType::ArrayBuffer, &vertices, BufferUsage::DynamicDraw).unwrap();
+/// buffer.sub_data(0, &vertices).unwrap();
+/// assert_eq!(buffer.read().len(), 1);
+/// ```
+///
+/// With Direct State Access (see `gl::dsa`) the buffer is edited without binding it. Otherwise
+/// every operation binds the buffer to its target and restores the previous binding afterward,
+/// so editing a buffer never changes the element buffer of the currently bound VAO. The buffer
+/// is deleted when it is dropped.
+#[derive(Debug)]
+pub struct Buffer<T: Pod> {
+    id: u32,
+    target: BufferType,
+    usage: BufferUsage,
+    len: usize,
+    _element: PhantomData<T>,
+}
+
+impl<T: Pod> Buffer<T> {
+    /// Creates a buffer without storage.
+    pub fn new(target: BufferType, usage: BufferUsage) -> Result<Self, RuntimeError> {
+        let id = if is_dsa_enabled() { create_buffers(1)?[0] } else { gen_buffers(1)?[0] };
+        Ok(Self {
+            id,
+            target,
+            usage,
+            len: 0,
+            _element: PhantomData,
+        })
+    }
+
+    /// Creates a buffer and uploads `data` to it.
+    pub fn new_with_data(target: BufferType,
+                         data: &[T],
+                         usage: BufferUsage) -> Result<Self, RuntimeError> {
+        let mut buffer = Self::new(target, usage)?;
+        buffer.set_data(data);
+        Ok(buffer)
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn target(&self) -> BufferType {
+        self.target
+    }
+
+    pub fn usage(&self) -> BufferUsage {
+        self.usage
+    }
+
+    /// Returns the number of elements the buffer can hold.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the size of the buffer storage in bytes.
+    pub fn byte_size(&self) -> usize {
+        self.len * size_of::<T>()
+    }
+
+    /// Replaces the storage of the buffer with a new one holding `data`.
+    pub fn set_data(&mut self, data: &[T]) {
+        if is_dsa_enabled() {
+            named_buffer_data(self.id, data, self.usage);
+        } else {
+            self.with_bound(|target| buffer_data(target, data, self.usage));
+        }
+        self.len = data.len();
+    }
+
+    /// Replaces the elements starting at element index `offset` with `data`.
+    ///
+    /// # Returns
+    /// An error if the range does not fit into the buffer. Use `resize` or `set_data` to
+    /// grow the buffer first.
+    pub fn sub_data(&mut self, offset: usize, data: &[T]) -> Result<(), RuntimeError> {
+        let end = offset.checked_add(data.len()).ok_or_else(|| range_overflow(offset, data.len()))?;
+        self.check_range(offset..end)?;
+        if data.is_empty() {
+            return Ok(());
+        }
+        if is_dsa_enabled() {
+            named_buffer_sub_data(self.id, offset * size_of::<T>(), data);
+        } else {
+            self.with_bound(|target| buffer_sub_data(target, offset * size_of::<T>(), data));
+        }
+        Ok(())
+    }
+
+    /// Allocates new, uninitialized storage for `len` elements.
+    ///
+    /// The old storage is orphaned: draw calls still in flight keep using it, while the driver
+    /// hands out fresh memory for the new data without waiting for them. The previous contents
+    /// are not preserved.
+    pub fn resize(&mut self, len: usize) {
+        let size = len * size_of::<T>();
+        if is_dsa_enabled() {
+            named_buffer_storage_uninitialized(self.id, size, self.usage);
+        } else {
+            self.with_bound(|target| buffer_storage_uninitialized(target, size, self.usage));
+        }
+        self.len = len;
+    }
+
+    /// Orphans the current storage and keeps the size, e.g. before rewriting a streaming
+    /// buffer every frame.
+    pub fn orphan(&mut self) {
+        self.resize(self.len);
+    }
+
+    /// Reads the whole buffer back from the GPU.
+    pub fn read(&self) -> Vec<T> {
+        self.read_range(0..self.len).unwrap_or_default()
+    }
+
+    /// Reads the elements in `range` back from the GPU.
+    pub fn read_range(&self, range: Range<usize>) -> Result<Vec<T>, RuntimeError> {
+        self.check_range(range.clone())?;
+        let offset = range.start.checked_mul(size_of::<T>())
+            .ok_or_else(|| range_overflow(range.start, range.len()))?;
+        let mut data = vec![T::zeroed(); range.len()];
+        if data.is_empty() {
+            return Ok(data);
+        }
+        if is_dsa_enabled() {
+            get_named_buffer_sub_data(self.id, offset, &mut data);
+        } else {
+            self.with_bound(|target| get_buffer_sub_data(target, offset, &mut data));
+        }
+        Ok(data)
+    }
+
+    fn check_range(&self, range: Range<usize>) -> Result<(), RuntimeError> {
+        if range.start > range.end || range.end > self.len {
+            return Err(RuntimeError::InvalidBufferSize(create_runtime_info!(format!(
+                "Range {:?} exceeds buffer of {} elements",
+                range,
+                self.len
+            ))));
+        }
+        Ok(())
+    }
+
+    fn with_bound<R, F: FnOnce(BufferType) -> R>(&self, operation: F) -> R {
+        let previous = get_bound_buffer(self.target);
+        bind_buffer(self.target, self.id);
+        let result = operation(self.target);
+        if previous != self.id {
+            bind_buffer(self.target, previous);
+        }
+        result
+    }
+}
+
+fn range_overflow(offset: usize, len: usize) -> RuntimeError {
+    RuntimeError::InvalidBufferSize(create_runtime_info!(format!(
+        "Range of {} elements at offset {} overflows",
+        len,
+        offset
+    )))
+}
+
+impl<T: Pod> Drop for Buffer<T> {
+    fn drop(&mut self) {
+        if self.id != 0 {
+            delete_buffers([self.id]);
+        }
+    }
+}
+
+impl<T: Pod> Bindable for Buffer<T> {
+    fn bind(&self) -> anyhow::Result<()> {
+        bind_buffer(self.target, self.id);
+        Ok(())
+    }
+
+    fn unbind(&self) -> anyhow::Result<()> {
+        bind_buffer(self.target, 0);
+        Ok(())
+    }
+}
+
+impl<T: Pod> BindableState for Buffer<T> {
+    fn is_bound(&self) -> anyhow::Result<bool> {
+        Ok(self.id > 0 && get_bound_buffer(self.target) == self.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_out_of_range_accesses_are_rejected() {
+        // Id 0 is never passed to GL, since every range check fails first.
+        let mut buffer = Buffer::<u32> {
+            id: 0,
+            target: BufferType::ArrayBuffer,
+            usage: BufferUsage::StaticDraw,
+            len: 4,
+            _element: PhantomData,
+        };
+        assert!(buffer.sub_data(usize::MAX, &[1, 2]).is_err());
+        assert!(buffer.sub_data(3, &[1, 2]).is_err());
+        assert!(buffer.read_range(2..5).is_err());
+        assert!(buffer.read_range(usize::MAX - 1..usize::MAX).is_err());
+    }
+}
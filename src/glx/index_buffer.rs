@@ -0,0 +1,153 @@
+use crate::core::runtime_error::RuntimeError;
+use crate::core::runtime_info::RuntimeInfo;
+use crate::create_runtime_info;
+use crate::gl::buffer::{
+    bind_buffer, buffer_data, create_buffers, delete_buffers, gen_buffers, get_bound_buffer,
+    named_buffer_data,
+};
+use crate::gl::dsa::is_dsa_enabled;
+use crate::gl::types::{BufferType, BufferUsage, IndexType};
+use crate::gl::vao::{bind_vertex_array, get_bound_vertex_array, vertex_array_element_buffer};
+use crate::glx::vertex_array_object::VertexArrayObject;
+use crate::glx::{Bindable, BindableState};
+
+/// An element array buffer holding the indices of a mesh.
+///
+/// The indices are stored with the smallest `IndexType` able to address all vertices, so a
+/// mesh with up to 256 vertices uses one byte per index and a mesh with up to 65536 vertices
+/// two bytes.
+#[derive(Debug)]
+pub struct IndexBuffer {
+    id: u32,
+    index_type: IndexType,
+    count: usize,
+}
+
+impl IndexBuffer {
+    /// Creates an index buffer and uploads the given indices.
+    ///
+    /// # Arguments
+    /// * `indices` - The vertex indices, e.g. as returned by `dedupe_vertices`.
+    /// * `vertex_count` - The number of vertices the indices refer to; selects the index type.
+    ///
+    /// Fails with `RuntimeError::InvalidBufferSize` if an index is not below `vertex_count`,
+    /// since it could not be stored in the selected index type.
+    ///
+    /// The buffer is not attached to any vertex array object; use `attach_to` for that.
+    /// Without Direct State Access the previous `ELEMENT_ARRAY_BUFFER` binding is restored
+    /// after uploading, so the element buffer of the currently bound VAO stays unchanged.
+    pub fn new(indices: &[u32], vertex_count: usize) -> Result<Self, RuntimeError> {
+        if let Some(&index) = indices.iter().find(|&&index| index as usize >= vertex_count) {
+            return Err(RuntimeError::InvalidBufferSize(create_runtime_info!(format!(
+                "Index {} is out of range for {} vertices",
+                index, vertex_count
+            ))));
+        }
+        let id = if is_dsa_enabled() { create_buffers(1)?[0] } else { gen_buffers(1)?[0] };
+        let index_buffer = Self {
+            id,
+            index_type: IndexType::for_vertex_count(vertex_count),
+            count: indices.len(),
+        };
+        index_buffer.upload(indices, BufferUsage::StaticDraw);
+        Ok(index_buffer)
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn index_type(&self) -> IndexType {
+        self.index_type
+    }
+
+    /// Returns the number of indices in the buffer.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Makes this buffer the element array buffer of `vao`.
+    ///
+    /// Without Direct State Access the VAO is bound for this, and the previously bound VAO is
+    /// bound again afterward.
+    pub fn attach_to(&self, vao: &VertexArrayObject) {
+        if is_dsa_enabled() {
+            vertex_array_element_buffer(vao.id(), self.id);
+        } else {
+            let previous_vao = get_bound_vertex_array();
+            bind_vertex_array(vao.id());
+            bind_buffer(BufferType::ElementArrayBuffer, self.id);
+            bind_vertex_array(previous_vao);
+        }
+    }
+
+    fn upload(&self, indices: &[u32], usage: BufferUsage) {
+        match self.index_type {
+            IndexType::UnsignedByte => {
+                let data: Vec<u8> = indices.iter().map(|&i| i as u8).collect();
+                self.upload_data(&data, usage);
+            }
+            IndexType::UnsignedShort => {
+                let data: Vec<u16> = indices.iter().map(|&i| i as u16).collect();
+                self.upload_data(&data, usage);
+            }
+            IndexType::UnsignedInt => self.upload_data(indices, usage),
+        }
+    }
+
+    fn upload_data<T>(&self, data: &[T], usage: BufferUsage) {
+        if is_dsa_enabled() {
+            named_buffer_data(self.id, data, usage);
+        } else {
+            let previous = get_bound_buffer(BufferType::ElementArrayBuffer);
+            bind_buffer(BufferType::ElementArrayBuffer, self.id);
+            buffer_data(BufferType::ElementArrayBuffer, data, usage);
+            bind_buffer(BufferType::ElementArrayBuffer, previous);
+        }
+    }
+}
+
+impl Drop for IndexBuffer {
+    fn drop(&mut self) {
+        if self.id != 0 {
+            delete_buffers([self.id]);
+        }
+    }
+}
+
+impl Bindable for IndexBuffer {
+    fn bind(&self) -> anyhow::Result<()> {
+        bind_buffer(BufferType::ElementArrayBuffer, self.id);
+        Ok(())
+    }
+
+    fn unbind(&self) -> anyhow::Result<()> {
+        bind_buffer(BufferType::ElementArrayBuffer, 0);
+        Ok(())
+    }
+}
+
+impl BindableState for IndexBuffer {
+    fn is_bound(&self) -> anyhow::Result<bool> {
+        Ok(self.id > 0 && get_bound_buffer(BufferType::ElementArrayBuffer) == self.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_type_for_vertex_count() {
+        assert_eq!(IndexType::for_vertex_count(3), IndexType::UnsignedByte);
+        assert_eq!(IndexType::for_vertex_count(256), IndexType::UnsignedByte);
+        assert_eq!(IndexType::for_vertex_count(257), IndexType::UnsignedShort);
+        assert_eq!(IndexType::for_vertex_count(65536), IndexType::UnsignedShort);
+        assert_eq!(IndexType::for_vertex_count(65537), IndexType::UnsignedInt);
+    }
+
+    #[test]
+    fn test_out_of_range_indices_are_rejected() {
+        assert!(matches!(IndexBuffer::new(&[0, 1, 256], 256), Err(RuntimeError::InvalidBufferSize(_))));
+    }
+}
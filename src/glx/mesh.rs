@@ -0,0 +1,233 @@
+use crate::core::runtime_error::RuntimeError;
+use crate::core::runtime_info::RuntimeInfo;
+use crate::create_runtime_info;
+use crate::gl::rendering::{
+    draw_arrays, draw_arrays_instanced, draw_elements, draw_elements_instanced,
+};
+use crate::gl::types::{BufferType, BufferUsage, PrimitiveTopology};
+use crate::glx::buffer::Buffer;
+use crate::glx::index_buffer::IndexBuffer;
+use crate::glx::vertex_array_object::VertexArrayObject;
+use crate::glx::vertex_layout::{DynamicVertexLayout, StepRate, VertexLayout};
+use crate::glx::vertex_utils::dedupe_vertices;
+use crate::glx::Bindable;
+use crate::vertex::VertexEq;
+use bytemuck::Pod;
+use std::ops::Range;
+
+/// A renderable mesh owning its vertex array object, vertex buffer and optional index buffer.
+///
+/// The vertex attributes are configured from `V::attributes()`, so `V` has to be a
+/// `#[repr(C)]` type whose fields match the layout.
+///
+/// The vertex data stays accessible through `vertex_buffer`, e.g. to update animated vertices
+/// with `Buffer::sub_data`.
+///
+/// # Example
+/// ```no_run
+/// use diego::gl::types::PrimitiveTopology;
+/// use diego::glx::mesh::Mesh;
+/// use diego::vertex::textured_vertex::TexturedVertex;
+///
+/// let quad = [
+///     TexturedVertex::new_xyz_uv(-0.5, -0.5, 0.0, 0.0, 0.0),
+///     TexturedVertex::new_xyz_uv(0.5, -0.5, 0.0, 1.0, 0.0),
+///     TexturedVertex::new_xyz_uv(0.5, 0.5, 0.0, 1.0, 1.0),
+///     TexturedVertex::new_xyz_uv(0.5, 0.5, 0.0, 1.0, 1.0),
+///     TexturedVertex::new_xyz_uv(-0.5, 0.5, 0.0, 0.0, 1.0),
+///     TexturedVertex::new_xyz_uv(-0.5, -0.5, 0.0, 0.0, 0.0),
+/// ];
+/// let mesh = Mesh::deduped(&quad, 0.0001).unwrap();
+/// mesh.draw(PrimitiveTopology::Triangles).unwrap();
+/// ```
+pub struct Mesh<V: VertexLayout + Pod> {
+    vao: VertexArrayObject,
+    vertex_buffer: Buffer<V>,
+    index_buffer: Option<IndexBuffer>,
+}
+
+impl<V: VertexLayout + Pod> Mesh<V> {
+    /// Creates an indexed mesh. Fails if an index is out of range for the vertices.
+    pub fn new(vertices: &[V], indices: &[u32]) -> Result<Self, RuntimeError> {
+        Self::create(vertices, Some(indices))
+    }
+
+    /// Creates a mesh without index buffer; vertices are drawn in order.
+    pub fn from_vertices(vertices: &[V]) -> Result<Self, RuntimeError> {
+        Self::create(vertices, None)
+    }
+
+    /// Removes duplicate vertices with `dedupe_vertices` and creates an indexed mesh from
+    /// the result.
+    pub fn deduped(vertices: &[V], tolerance: f32) -> Result<Self, RuntimeError>
+    where
+        V: VertexEq<V> + Clone,
+    {
+        let (unique_vertices, indices) = dedupe_vertices(vertices, tolerance);
+        Self::create(&unique_vertices, Some(&indices))
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.vertex_buffer.len()
+    }
+
+    pub fn vertex_buffer(&self) -> &Buffer<V> {
+        &self.vertex_buffer
+    }
+
+    pub fn vertex_buffer_mut(&mut self) -> &mut Buffer<V> {
+        &mut self.vertex_buffer
+    }
+
+    pub fn index_buffer(&self) -> Option<&IndexBuffer> {
+        self.index_buffer.as_ref()
+    }
+
+    /// Returns the number of elements drawn by `draw`: the index count for indexed meshes,
+    /// the vertex count otherwise.
+    pub fn element_count(&self) -> usize {
+        self.index_buffer.as_ref().map_or(self.vertex_count(), IndexBuffer::count)
+    }
+
+    /// Draws the whole mesh.
+    pub fn draw(&self, topology: PrimitiveTopology) -> anyhow::Result<()> {
+        self.draw_range(topology, 0..self.element_count())
+    }
+
+    /// Draws a range of the mesh. For indexed meshes the range refers to indices, otherwise
+    /// to vertices.
+    pub fn draw_range(&self, topology: PrimitiveTopology, range: Range<usize>) -> anyhow::Result<()> {
+        self.draw_range_instanced(topology, range, None)
+    }
+
+    /// Draws `instance_count` instances of the whole mesh. Per-instance attributes have to be
+    /// attached to the VAO, e.g. with an `InstancedMesh`.
+    pub fn draw_instanced(&self, topology: PrimitiveTopology, instance_count: usize) -> anyhow::Result<()> {
+        self.draw_range_instanced(topology, 0..self.element_count(), Some(instance_count))
+    }
+
+    fn draw_range_instanced(&self,
+                            topology: PrimitiveTopology,
+                            range: Range<usize>,
+                            instance_count: Option<usize>) -> anyhow::Result<()> {
+        if range.start > range.end || range.end > self.element_count() {
+            return Err(RuntimeError::InvalidBufferSize(create_runtime_info!(format!(
+                "Draw range {:?} exceeds the {} elements of the mesh",
+                range,
+                self.element_count()
+            )))
+            .into());
+        }
+
+        self.vao.bind()?;
+        match (&self.index_buffer, instance_count) {
+            (Some(index_buffer), None) => {
+                let index_type = index_buffer.index_type();
+                draw_elements(topology, range.len(), index_type, range.start * index_type.size());
+            }
+            (Some(index_buffer), Some(instance_count)) => {
+                let index_type = index_buffer.index_type();
+                let offset = range.start * index_type.size();
+                draw_elements_instanced(topology, range.len(), index_type, offset, instance_count);
+            }
+            (None, None) => draw_arrays(topology, range.start, range.len()),
+            (None, Some(instance_count)) => {
+                draw_arrays_instanced(topology, range.start, range.len(), instance_count)
+            }
+        }
+        Ok(())
+    }
+
+    fn create(vertices: &[V], indices: Option<&[u32]>) -> Result<Self, RuntimeError> {
+        let vao = VertexArrayObject::default();
+        let vertex_buffer =
+            Buffer::new_with_data(BufferType::ArrayBuffer, vertices, BufferUsage::StaticDraw)?;
+
+        let mut layout = DynamicVertexLayout::from_attributes(V::attributes());
+        layout.finalize_attributes();
+        layout.attach(&vao, 0, vertex_buffer.id(), 0);
+
+        let index_buffer = indices
+            .map(|indices| IndexBuffer::new(indices, vertices.len()))
+            .transpose()?;
+        if let Some(index_buffer) = &index_buffer {
+            index_buffer.attach_to(&vao);
+        }
+
+        Ok(Self {
+            vao,
+            vertex_buffer,
+            index_buffer,
+        })
+    }
+}
+
+/// A mesh drawn many times in a single draw call, with per-instance data of type `I` read from
+/// a second vertex buffer.
+///
+/// The attributes of `I` advance once per instance and are placed behind the attributes of
+/// `V`; a `[[f32; 4]; 4]` field becomes a `mat4` occupying four locations.
+///
+/// # Example
+/// ```no_run
+/// use bytemuck::{Pod, Zeroable};
+/// use diego::gl::types::PrimitiveTopology;
+/// use diego::glx::mesh::{InstancedMesh, Mesh};
+/// use diego::glx::vertex_layout::VertexLayout;
+/// use diego::vertex::textured_vertex::TexturedVertex;
+///
+/// #[repr(C)]
+/// #[derive(Clone, Copy, Pod, Zeroable, VertexLayout)]
+/// struct BoxInstance {
+///     model: [[f32; 4]; 4],
+/// }
+///
+/// # let cube_vertices: Vec<TexturedVertex> = Vec::new();
+/// # let transforms: Vec<BoxInstance> = Vec::new();
+/// let cube = Mesh::deduped(&cube_vertices, 0.0001).unwrap();
+/// let mut boxes = InstancedMesh::new(cube, &transforms).unwrap();
+/// boxes.draw(PrimitiveTopology::Triangles).unwrap();
+/// ```
+pub struct InstancedMesh<V: VertexLayout + Pod, I: VertexLayout + Pod> {
+    mesh: Mesh<V>,
+    instance_buffer: Buffer<I>,
+}
+
+impl<V: VertexLayout + Pod, I: VertexLayout + Pod> InstancedMesh<V, I> {
+    pub fn new(mesh: Mesh<V>, instances: &[I]) -> Result<Self, RuntimeError> {
+        let mut vertex_layout = DynamicVertexLayout::of::<V>();
+        vertex_layout.finalize_attributes();
+        let mut instance_layout = DynamicVertexLayout::of::<I>().with_step_rate(StepRate::PerInstance(1));
+        instance_layout.finalize_attributes();
+
+        let instance_buffer =
+            Buffer::new_with_data(BufferType::ArrayBuffer, instances, BufferUsage::DynamicDraw)?;
+        let first_location = vertex_layout.next_free_location_from(0);
+        instance_layout.attach(&mesh.vao, 1, instance_buffer.id(), first_location);
+
+        Ok(Self { mesh, instance_buffer })
+    }
+
+    pub fn mesh(&self) -> &Mesh<V> {
+        &self.mesh
+    }
+
+    pub fn instance_count(&self) -> usize {
+        self.instance_buffer.len()
+    }
+
+    pub fn instance_buffer_mut(&mut self) -> &mut Buffer<I> {
+        &mut self.instance_buffer
+    }
+
+    /// Replaces the per-instance data. The buffer storage is reallocated, the VAO keeps
+    /// referencing the same buffer object.
+    pub fn set_instances(&mut self, instances: &[I]) {
+        self.instance_buffer.set_data(instances);
+    }
+
+    /// Draws all instances of the mesh.
+    pub fn draw(&self, topology: PrimitiveTopology) -> anyhow::Result<()> {
+        self.mesh.draw_instanced(topology, self.instance_count())
+    }
+}
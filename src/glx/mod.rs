@@ -7,7 +7,15 @@ pub mod vertex_attribute_type;
 pub mod vertex_utils;
 pub mod vertex_layout_manager;
 pub mod vertex_array_object;
-mod vertex_buffer_objects;
+pub mod buffer;
+pub mod index_buffer;
+pub mod mesh;
+pub mod pipeline_state;
+pub mod program_cache;
+pub mod shader_preprocessor;
+pub mod shader_program;
+pub mod shader_variants;
+pub mod texture;
 
 /// A trait for objects that can be bound and unbound in the context of OpenGL 
 /// or similar graphics APIs.
@@ -17,9 +25,9 @@ mod vertex_buffer_objects;
 ///
 /// # Methods
 /// - `bind`: Binds the object to the current context. 
-///    Returns a `Result` indicating success or failure.
+///   Returns a `Result` indicating success or failure.
 /// - `unbind`: Unbinds the object from the current context. 
-///    Returns a `Result` indicating success or failure.
+///   Returns a `Result` indicating success or failure.
 pub trait Bindable {
     fn bind(&self) -> Result<()>;
     fn unbind(&self) -> Result<()>;
@@ -33,7 +41,7 @@ pub trait Bindable {
 ///
 /// # Methods
 /// - `is_bound`: Returns a `Result<bool>` indicating whether the object is currently bound (`true`) 
-///               or not (`false`).
+///   or not (`false`).
 pub trait BindableState {
     fn is_bound(&self) -> Result<bool>;
 }
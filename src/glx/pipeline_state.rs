@@ -0,0 +1,517 @@
+use crate::assets::AssetHandle;
+use crate::gl::color::Color;
+use crate::gl::setup::{
+    blend_color, blend_equation_separate, blend_func_separate, color_mask, cull_face, depth_func,
+    depth_mask, disable, enable, front_face, polygon_mode, polygon_offset, stencil_func, stencil_mask,
+    stencil_op,
+};
+use crate::gl::types::{
+    BlendEquation, BlendFactor, Capability, CompareFunction, Face, FrontFace, PolygonMode, StencilOp,
+};
+use crate::glx::shader_program::ShaderProgram;
+use crate::glx::vertex_array_object::VertexArrayObject;
+use crate::glx::vertex_layout::DynamicVertexLayout;
+use crate::glx::vertex_layout_manager::VertexLayoutManager;
+use crate::glx::Bindable;
+
+/// Describes how fragment colors are blended into the framebuffer.
+///
+/// The state is immutable; the `with_*` functions return a modified copy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlendState {
+    enabled: bool,
+    src_rgb: BlendFactor,
+    dst_rgb: BlendFactor,
+    src_alpha: BlendFactor,
+    dst_alpha: BlendFactor,
+    equation_rgb: BlendEquation,
+    equation_alpha: BlendEquation,
+    constant: Color,
+    color_mask: [bool; 4],
+}
+
+impl Default for BlendState {
+    fn default() -> Self {
+        Self::opaque()
+    }
+}
+
+impl BlendState {
+    /// Blending disabled; fragments overwrite the framebuffer.
+    pub const fn opaque() -> Self {
+        Self {
+            enabled: false,
+            src_rgb: BlendFactor::One,
+            dst_rgb: BlendFactor::Zero,
+            src_alpha: BlendFactor::One,
+            dst_alpha: BlendFactor::Zero,
+            equation_rgb: BlendEquation::Add,
+            equation_alpha: BlendEquation::Add,
+            constant: Color { r: 0.0, g: 0.0, b: 0.0, a: 0.0 },
+            color_mask: [true; 4],
+        }
+    }
+
+    /// Classic transparency for straight (non-premultiplied) alpha:
+    /// `src * src.a + dst * (1 - src.a)`.
+    pub const fn alpha_blend() -> Self {
+        Self::blended(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha, BlendFactor::One, BlendFactor::OneMinusSrcAlpha)
+    }
+
+    /// Transparency for colors already multiplied by their alpha: `src + dst * (1 - src.a)`.
+    pub const fn premultiplied_alpha() -> Self {
+        Self::blended(BlendFactor::One, BlendFactor::OneMinusSrcAlpha, BlendFactor::One, BlendFactor::OneMinusSrcAlpha)
+    }
+
+    /// Adds the alpha weighted source to the framebuffer, e.g. for particles and light glows.
+    pub const fn additive() -> Self {
+        Self::blended(BlendFactor::SrcAlpha, BlendFactor::One, BlendFactor::One, BlendFactor::One)
+    }
+
+    /// Blending enabled with the given factors and `BlendEquation::Add`.
+    pub const fn blended(src_rgb: BlendFactor, dst_rgb: BlendFactor, src_alpha: BlendFactor, dst_alpha: BlendFactor) -> Self {
+        Self {
+            enabled: true,
+            src_rgb,
+            dst_rgb,
+            src_alpha,
+            dst_alpha,
+            ..Self::opaque()
+        }
+    }
+
+    pub const fn with_equations(self, equation_rgb: BlendEquation, equation_alpha: BlendEquation) -> Self {
+        Self { equation_rgb, equation_alpha, ..self }
+    }
+
+    /// Sets the color used by the `Constant*` blend factors.
+    pub const fn with_constant(self, constant: Color) -> Self {
+        Self { constant, ..self }
+    }
+
+    /// Selects the color channels that are written.
+    pub const fn with_color_mask(self, red: bool, green: bool, blue: bool, alpha: bool) -> Self {
+        Self { color_mask: [red, green, blue, alpha], ..self }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Returns the factors as `[src_rgb, dst_rgb, src_alpha, dst_alpha]`.
+    pub fn factors(&self) -> [BlendFactor; 4] {
+        [self.src_rgb, self.dst_rgb, self.src_alpha, self.dst_alpha]
+    }
+
+    /// Returns the equations as `[rgb, alpha]`.
+    pub fn equations(&self) -> [BlendEquation; 2] {
+        [self.equation_rgb, self.equation_alpha]
+    }
+
+    pub fn constant(&self) -> Color {
+        self.constant
+    }
+
+    pub fn color_mask(&self) -> [bool; 4] {
+        self.color_mask
+    }
+
+    /// Sets the OpenGL blend state. The factors and equations are only set while blending is
+    /// enabled.
+    pub fn apply(&self) {
+        let [red, green, blue, alpha] = self.color_mask;
+        color_mask(red, green, blue, alpha);
+        if !self.enabled {
+            disable(Capability::Blend);
+            return;
+        }
+        enable(Capability::Blend);
+        blend_func_separate(self.src_rgb, self.dst_rgb, self.src_alpha, self.dst_alpha);
+        blend_equation_separate(self.equation_rgb, self.equation_alpha);
+        if self.factors().iter().any(uses_constant) {
+            blend_color(self.constant);
+        }
+    }
+}
+
+fn uses_constant(factor: &BlendFactor) -> bool {
+    matches!(
+        factor,
+        BlendFactor::ConstantColor
+            | BlendFactor::OneMinusConstantColor
+            | BlendFactor::ConstantAlpha
+            | BlendFactor::OneMinusConstantAlpha
+    )
+}
+
+/// Describes the stencil test and what happens to the stencil buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StencilState {
+    pub function: CompareFunction,
+    pub reference: i32,
+    /// The bits compared by the stencil test.
+    pub read_mask: u32,
+    /// The bits that can be written.
+    pub write_mask: u32,
+    /// The action when the stencil test fails.
+    pub stencil_fail: StencilOp,
+    /// The action when the stencil test passes but the depth test fails.
+    pub depth_fail: StencilOp,
+    /// The action when both tests pass.
+    pub pass: StencilOp,
+}
+
+impl Default for StencilState {
+    fn default() -> Self {
+        Self {
+            function: CompareFunction::Always,
+            reference: 0,
+            read_mask: u32::MAX,
+            write_mask: u32::MAX,
+            stencil_fail: StencilOp::Keep,
+            depth_fail: StencilOp::Keep,
+            pass: StencilOp::Keep,
+        }
+    }
+}
+
+/// Describes the depth and stencil tests.
+///
+/// The state is immutable; the `with_*` functions return a modified copy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DepthStencilState {
+    depth_test: bool,
+    depth_write: bool,
+    depth_func: CompareFunction,
+    stencil: Option<StencilState>,
+}
+
+impl Default for DepthStencilState {
+    fn default() -> Self {
+        Self::depth_test()
+    }
+}
+
+impl DepthStencilState {
+    /// Depth test with `CompareFunction::Less` and depth writes, no stencil test.
+    pub const fn depth_test() -> Self {
+        Self {
+            depth_test: true,
+            depth_write: true,
+            depth_func: CompareFunction::Less,
+            stencil: None,
+        }
+    }
+
+    /// Depth test without depth writes, e.g. for transparent geometry drawn after the opaque one.
+    pub const fn depth_read_only() -> Self {
+        Self { depth_write: false, ..Self::depth_test() }
+    }
+
+    /// No depth and stencil test, e.g. for UI and full screen passes.
+    pub const fn disabled() -> Self {
+        Self {
+            depth_test: false,
+            depth_write: false,
+            depth_func: CompareFunction::Always,
+            stencil: None,
+        }
+    }
+
+    pub const fn with_depth_func(self, depth_func: CompareFunction) -> Self {
+        Self { depth_func, ..self }
+    }
+
+    pub const fn with_depth_write(self, depth_write: bool) -> Self {
+        Self { depth_write, ..self }
+    }
+
+    /// Enables the stencil test; `None` disables it.
+    pub const fn with_stencil(self, stencil: Option<StencilState>) -> Self {
+        Self { stencil, ..self }
+    }
+
+    pub fn is_depth_test_enabled(&self) -> bool {
+        self.depth_test
+    }
+
+    pub fn is_depth_write_enabled(&self) -> bool {
+        self.depth_write
+    }
+
+    pub fn depth_func(&self) -> CompareFunction {
+        self.depth_func
+    }
+
+    pub fn stencil(&self) -> Option<&StencilState> {
+        self.stencil.as_ref()
+    }
+
+    /// Sets the OpenGL depth and stencil state. The depth function and stencil operations are
+    /// only set while the respective test is enabled.
+    pub fn apply(&self) {
+        // The masks also apply to clears, so they are set regardless of the tests.
+        depth_mask(self.depth_write);
+        if self.depth_test {
+            enable(Capability::DepthTest);
+            depth_func(self.depth_func);
+        } else {
+            disable(Capability::DepthTest);
+        }
+
+        match &self.stencil {
+            Some(stencil) => {
+                enable(Capability::StencilTest);
+                stencil_func(stencil.function, stencil.reference, stencil.read_mask);
+                stencil_op(stencil.stencil_fail, stencil.depth_fail, stencil.pass);
+                stencil_mask(stencil.write_mask);
+            }
+            None => {
+                disable(Capability::StencilTest);
+                stencil_mask(u32::MAX);
+            }
+        }
+    }
+}
+
+/// Describes how primitives are rasterized.
+///
+/// The state is immutable; the `with_*` functions return a modified copy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RasterizerState {
+    cull_face: Option<Face>,
+    front_face: FrontFace,
+    polygon_mode: PolygonMode,
+    /// `None` leaves the scissor test as it is, e.g. as set by `gl::viewport::push_scissor`.
+    scissor_test: Option<bool>,
+    depth_bias: Option<(f32, f32)>,
+}
+
+impl Default for RasterizerState {
+    fn default() -> Self {
+        Self::cull_back()
+    }
+}
+
+impl RasterizerState {
+    /// Filled polygons with counter-clockwise front faces and back faces culled.
+    pub const fn cull_back() -> Self {
+        Self {
+            cull_face: Some(Face::Back),
+            front_face: FrontFace::CounterClockwise,
+            polygon_mode: PolygonMode::Fill,
+            scissor_test: None,
+            depth_bias: None,
+        }
+    }
+
+    /// Filled polygons without culling, e.g. for double-sided geometry.
+    pub const fn no_cull() -> Self {
+        Self { cull_face: None, ..Self::cull_back() }
+    }
+
+    /// Polygon edges only, without culling. Not available on OpenGL ES.
+    pub const fn wireframe() -> Self {
+        Self { polygon_mode: PolygonMode::Line, ..Self::no_cull() }
+    }
+
+    /// Selects the culled faces; `None` disables culling.
+    pub const fn with_cull_face(self, cull_face: Option<Face>) -> Self {
+        Self { cull_face, ..self }
+    }
+
+    pub const fn with_front_face(self, front_face: FrontFace) -> Self {
+        Self { front_face, ..self }
+    }
+
+    /// Sets the polygon mode. Only `PolygonMode::Fill` is available on OpenGL ES.
+    pub const fn with_polygon_mode(self, polygon_mode: PolygonMode) -> Self {
+        Self { polygon_mode, ..self }
+    }
+
+    /// Enables or disables the scissor test; `None`, the default, leaves it as it is, so a box
+    /// pushed with `gl::viewport::push_scissor` stays in effect.
+    pub const fn with_scissor_test(self, scissor_test: Option<bool>) -> Self {
+        Self { scissor_test, ..self }
+    }
+
+    /// Offsets the depth of filled polygons by `factor * slope + units * r`, e.g. to avoid
+    /// shadow acne; `None` disables the offset.
+    pub const fn with_depth_bias(self, depth_bias: Option<(f32, f32)>) -> Self {
+        Self { depth_bias, ..self }
+    }
+
+    pub fn cull_face(&self) -> Option<Face> {
+        self.cull_face
+    }
+
+    pub fn front_face(&self) -> FrontFace {
+        self.front_face
+    }
+
+    pub fn polygon_mode(&self) -> PolygonMode {
+        self.polygon_mode
+    }
+
+    pub fn scissor_test(&self) -> Option<bool> {
+        self.scissor_test
+    }
+
+    pub fn depth_bias(&self) -> Option<(f32, f32)> {
+        self.depth_bias
+    }
+
+    /// Sets the OpenGL rasterizer state. On OpenGL ES the polygon mode is left out, see
+    /// `gl::setup::polygon_mode`.
+    pub fn apply(&self) {
+        match self.cull_face {
+            Some(face) => {
+                enable(Capability::CullFace);
+                cull_face(face);
+            }
+            None => disable(Capability::CullFace),
+        }
+        front_face(self.front_face);
+        polygon_mode(self.polygon_mode);
+        match self.scissor_test {
+            Some(true) => enable(Capability::ScissorTest),
+            Some(false) => disable(Capability::ScissorTest),
+            None => {}
+        }
+        match self.depth_bias {
+            Some((factor, units)) => {
+                enable(Capability::PolygonOffsetFill);
+                polygon_offset(factor, units);
+            }
+            None => disable(Capability::PolygonOffsetFill),
+        }
+    }
+}
+
+/// Bundles a shader program, its vertex layout and the fixed-function state of a draw call.
+///
+/// Applying a pipeline only changes what differs from the current OpenGL state: every state
+/// change goes through the `StateCache`, so switching between pipelines that share e.g. the
+/// depth state leaves it untouched.
+///
+/// # Example
+/// ```no_run
+/// use diego::glx::pipeline_state::{BlendState, DepthStencilState, PipelineState};
+/// use diego::glx::shader_program::ShaderProgram;
+/// use std::cell::RefCell;
+/// use std::rc::Rc;
+///
+/// let program = ShaderProgram::from_files("sprite.vert", "sprite.frag").unwrap();
+/// let transparent = PipelineState::new(Rc::new(RefCell::new(program)))
+///     .with_blend_state(BlendState::alpha_blend())
+///     .with_depth_stencil_state(DepthStencilState::depth_read_only());
+/// transparent.apply().unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct PipelineState {
+    program: AssetHandle<ShaderProgram>,
+    vertex_layout: Option<DynamicVertexLayout>,
+    blend: BlendState,
+    depth_stencil: DepthStencilState,
+    rasterizer: RasterizerState,
+}
+
+impl PipelineState {
+    /// Creates a pipeline with opaque blending, depth test and back face culling.
+    pub fn new(program: AssetHandle<ShaderProgram>) -> Self {
+        Self {
+            program,
+            vertex_layout: None,
+            blend: BlendState::default(),
+            depth_stencil: DepthStencilState::default(),
+            rasterizer: RasterizerState::default(),
+        }
+    }
+
+    /// Sets the layout of the vertices the program consumes, see `apply_to`.
+    pub fn with_vertex_layout(mut self, mut vertex_layout: DynamicVertexLayout) -> Self {
+        vertex_layout.finalize_attributes();
+        self.vertex_layout = Some(vertex_layout);
+        self
+    }
+
+    pub fn with_blend_state(mut self, blend: BlendState) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    pub fn with_depth_stencil_state(mut self, depth_stencil: DepthStencilState) -> Self {
+        self.depth_stencil = depth_stencil;
+        self
+    }
+
+    pub fn with_rasterizer_state(mut self, rasterizer: RasterizerState) -> Self {
+        self.rasterizer = rasterizer;
+        self
+    }
+
+    pub fn program(&self) -> &AssetHandle<ShaderProgram> {
+        &self.program
+    }
+
+    pub fn vertex_layout(&self) -> Option<&DynamicVertexLayout> {
+        self.vertex_layout.as_ref()
+    }
+
+    pub fn blend_state(&self) -> &BlendState {
+        &self.blend
+    }
+
+    pub fn depth_stencil_state(&self) -> &DepthStencilState {
+        &self.depth_stencil
+    }
+
+    pub fn rasterizer_state(&self) -> &RasterizerState {
+        &self.rasterizer
+    }
+
+    /// Uses the program and sets the blend, depth-stencil and rasterizer state.
+    pub fn apply(&self) -> anyhow::Result<()> {
+        self.program.borrow().bind()?;
+        self.blend.apply();
+        self.depth_stencil.apply();
+        self.rasterizer.apply();
+        Ok(())
+    }
+
+    /// Applies the pipeline and binds `vao`. If the pipeline has a vertex layout, it is
+    /// activated on the VAO through `layouts`, which only specifies the attributes again if
+    /// the VAO had a different layout; bind the vertex buffer before in that case.
+    pub fn apply_to(&self, vao: &VertexArrayObject, layouts: &mut VertexLayoutManager) -> anyhow::Result<()> {
+        self.apply()?;
+        match &self.vertex_layout {
+            Some(layout) => {
+                layouts.activate_layout(vao, layout)?;
+            }
+            None => vao.bind()?,
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets() {
+        assert!(!BlendState::opaque().is_enabled());
+        assert_eq!(
+            BlendState::premultiplied_alpha().factors(),
+            [BlendFactor::One, BlendFactor::OneMinusSrcAlpha, BlendFactor::One, BlendFactor::OneMinusSrcAlpha]
+        );
+        assert_eq!(BlendState::additive().equations(), [BlendEquation::Add, BlendEquation::Add]);
+        assert_ne!(BlendState::alpha_blend(), BlendState::alpha_blend().with_color_mask(true, true, true, false));
+
+        assert!(!DepthStencilState::depth_read_only().is_depth_write_enabled());
+        assert!(DepthStencilState::depth_read_only().is_depth_test_enabled());
+        assert_eq!(RasterizerState::wireframe().cull_face(), None);
+        assert_eq!(RasterizerState::default().cull_face(), Some(Face::Back));
+        assert_eq!(RasterizerState::default().scissor_test(), None);
+    }
+}
@@ -0,0 +1,219 @@
+use crate::gl::shader::{
+    create_program, delete_program, get_program_binary, get_program_link_status,
+    is_program_binary_supported, program_binary,
+};
+use crate::gl::state::{get_integer_v, get_integer_v_array, get_string};
+use crate::gl::types::{GlGetParameter, GlStringName};
+use crate::glx::shader_program::ShaderStage;
+use crate::{log_debug, log_warn};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+const CACHE_MAGIC: &[u8; 4] = b"DGPB";
+const CACHE_VERSION: u32 = 1;
+const HEADER_SIZE: usize = 12;
+
+pub type SharedProgramCache = Rc<ProgramCache>;
+
+/// Stores linked shader programs on disk, so later runs can skip compiling and linking.
+///
+/// Entries are keyed by a hash of the preprocessed stage sources, the driver vendor, renderer
+/// and version strings and the program binary formats supported by the driver. Loading an
+/// entry the driver rejects (e.g. after a driver update) removes it from the cache; the caller
+/// then compiles the program from source as usual.
+///
+/// # Example
+/// ```no_run
+/// use std::rc::Rc;
+/// use diego::glx::program_cache::ProgramCache;
+/// use diego::glx::shader_program::{ShaderProgram, ShaderStage};
+/// use diego::gl::types::ShaderType;
+///
+/// let cache = Rc::new(ProgramCache::new("cache/shaders"));
+/// let program = ShaderProgram::from_stages_cached(vec![
+///     ShaderStage::from_file(ShaderType::Vertex, "assets/shaders/light/light_cube.vert").unwrap(),
+///     ShaderStage::from_file(ShaderType::Fragment, "assets/shaders/light/light_cube.frag").unwrap(),
+/// ], Some(&cache)).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct ProgramCache {
+    directory: PathBuf,
+    driver_id: Option<String>,
+}
+
+impl ProgramCache {
+    /// Creates a cache storing its entries in `directory`. Requires a current OpenGL context.
+    ///
+    /// If the driver does not support program binaries, the cache is disabled and every
+    /// lookup misses.
+    pub fn new<P: AsRef<Path>>(directory: P) -> Self {
+        let driver_id = is_program_binary_supported().then(|| {
+            let format_count = get_integer_v(GlGetParameter::NumProgramBinaryFormats).max(0);
+            let formats =
+                get_integer_v_array(GlGetParameter::ProgramBinaryFormats, format_count as usize);
+            format!(
+                "{}\n{}\n{}\n{:?}",
+                get_string(GlStringName::Vendor),
+                get_string(GlStringName::Renderer),
+                get_string(GlStringName::Version),
+                formats
+            )
+        });
+        Self {
+            directory: directory.as_ref().to_path_buf(),
+            driver_id,
+        }
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.driver_id.is_some()
+    }
+
+    /// Creates a program from a cached binary.
+    ///
+    /// # Returns
+    /// The ID of the linked program, or `None` if there is no usable entry for the stages.
+    pub fn load(&self, stages: &[ShaderStage]) -> Option<u32> {
+        let path = self.entry_path(stages)?;
+        let data = fs::read(&path).ok()?;
+        let Some((format, binary)) = decode_entry(&data) else {
+            log_warn!("Discarding corrupt program cache entry {}", path.display());
+            let _ = fs::remove_file(&path);
+            return None;
+        };
+
+        let program_id = create_program().ok()?;
+        program_binary(program_id, format, binary);
+        if !get_program_link_status(program_id) {
+            log_debug!("Driver rejected cached program {}, recompiling", path.display());
+            delete_program(program_id);
+            let _ = fs::remove_file(&path);
+            return None;
+        }
+        Some(program_id)
+    }
+
+    /// Stores the binary of a linked program. Failures are logged and otherwise ignored,
+    /// since the cache is only an optimization.
+    pub fn store(&self, program_id: u32, stages: &[ShaderStage]) {
+        let Some(path) = self.entry_path(stages) else {
+            return;
+        };
+        let Some((format, binary)) = get_program_binary(program_id) else {
+            return;
+        };
+        if let Err(e) = self.write_entry(&path, format, &binary) {
+            log_warn!("Failed to write program cache entry {}: {}", path.display(), e);
+        }
+    }
+
+    fn write_entry(&self, path: &Path, format: u32, binary: &[u8]) -> io::Result<()> {
+        fs::create_dir_all(&self.directory)?;
+        // Write to a temporary file first so a crash never leaves a truncated entry behind.
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, encode_entry(format, binary))?;
+        fs::rename(&temp_path, path)
+    }
+
+    fn entry_path(&self, stages: &[ShaderStage]) -> Option<PathBuf> {
+        let driver_id = self.driver_id.as_ref()?;
+        Some(self.directory.join(format!("{}.bin", cache_key(driver_id, stages))))
+    }
+}
+
+/// Hashes the driver identification and all stage sources into a file name safe key.
+///
+/// A stable FNV-1a hash is used instead of `DefaultHasher`, whose output may change between
+/// Rust releases and would invalidate the cache on every toolchain update.
+fn cache_key(driver_id: &str, stages: &[ShaderStage]) -> String {
+    let mut hash = Fnv1a::new();
+    hash.write(driver_id.as_bytes());
+    for stage in stages {
+        hash.write(&[0]);
+        hash.write(stage.shader_type.as_str().as_bytes());
+        hash.write(&[0]);
+        hash.write(stage.source.as_bytes());
+    }
+    format!("{:016x}", hash.finish())
+}
+
+fn encode_entry(format: u32, binary: &[u8]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(HEADER_SIZE + binary.len());
+    data.extend_from_slice(CACHE_MAGIC);
+    data.extend_from_slice(&CACHE_VERSION.to_le_bytes());
+    data.extend_from_slice(&format.to_le_bytes());
+    data.extend_from_slice(binary);
+    data
+}
+
+fn decode_entry(data: &[u8]) -> Option<(u32, &[u8])> {
+    if data.len() <= HEADER_SIZE || &data[0..4] != CACHE_MAGIC {
+        return None;
+    }
+    let version = u32::from_le_bytes(data[4..8].try_into().ok()?);
+    if version != CACHE_VERSION {
+        return None;
+    }
+    let format = u32::from_le_bytes(data[8..12].try_into().ok()?);
+    Some((format, &data[HEADER_SIZE..]))
+}
+
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gl::types::ShaderType;
+
+    #[test]
+    fn test_entry_roundtrip() {
+        let data = encode_entry(0x8e21, &[1, 2, 3, 4]);
+        assert_eq!(decode_entry(&data), Some((0x8e21, &[1u8, 2, 3, 4][..])));
+
+        let mut corrupt = data.clone();
+        corrupt[0] = b'X';
+        assert_eq!(decode_entry(&corrupt), None);
+        assert_eq!(decode_entry(&data[..HEADER_SIZE]), None);
+    }
+
+    #[test]
+    fn test_cache_key_depends_on_sources_and_driver() {
+        let stages = vec![
+            ShaderStage::from_source(ShaderType::Vertex, "void main() {}"),
+            ShaderStage::from_source(ShaderType::Fragment, "void main() {}"),
+        ];
+        let key = cache_key("vendor\nrenderer", &stages);
+        assert_eq!(key.len(), 16);
+        assert_eq!(key, cache_key("vendor\nrenderer", &stages));
+        assert_ne!(key, cache_key("vendor\nother renderer", &stages));
+
+        let changed = vec![
+            ShaderStage::from_source(ShaderType::Vertex, "void main() {}"),
+            ShaderStage::from_source(ShaderType::Fragment, "void main() { }"),
+        ];
+        assert_ne!(key, cache_key("vendor\nrenderer", &changed));
+    }
+}
@@ -0,0 +1,385 @@
+use crate::glx::shader_program::ShaderError;
+use regex::{Captures, Regex};
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// The result of preprocessing a shader: the expanded source and the files it was built from.
+///
+/// `#line` directives in the source refer to files by their index in `files`, which is what
+/// GLSL calls the source string number.
+#[derive(Clone, Debug, Default)]
+pub struct PreprocessedShader {
+    pub source: String,
+    pub files: Vec<PathBuf>,
+}
+
+impl PreprocessedShader {
+    /// Rewrites source string numbers in a compiler log into file names.
+    ///
+    /// Drivers report locations as `0(12)` (NVIDIA) or `0:12` (Mesa, AMD); both forms are
+    /// replaced by `path/to/file.glsl:12`.
+    pub fn resolve_log(&self, log: &str) -> String {
+        resolve_source_locations(&self.files, log)
+    }
+}
+
+/// Replaces `index(line)` and `index:line` locations in `log` by `file:line`, where `file`
+/// is the entry at `index` in `files`. Unknown indices are left unchanged.
+pub(crate) fn resolve_source_locations(files: &[PathBuf], log: &str) -> String {
+    static LOCATION: OnceLock<Regex> = OnceLock::new();
+    let location = LOCATION.get_or_init(|| Regex::new(r"\b(\d+)(?:\((\d+)\)|:(\d+))").unwrap());
+
+    location
+        .replace_all(log, |caps: &Captures| {
+            let file = caps[1].parse::<usize>().ok().and_then(|index| files.get(index));
+            let line = caps.get(2).or_else(|| caps.get(3)).map(|m| m.as_str());
+            match (file, line) {
+                (Some(file), Some(line)) => format!("{}:{}", file.display(), line),
+                _ => caps[0].to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// A set of feature defines that selects one variant of a shader, e.g. `{NORMAL_MAP}`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PermutationKey(BTreeSet<String>);
+
+impl PermutationKey {
+    pub fn new<I, S>(features: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self(features.into_iter().map(Into::into).collect())
+    }
+
+    pub fn contains(&self, feature: &str) -> bool {
+        self.0.contains(feature)
+    }
+
+    pub fn features(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+
+    /// Returns every combination of the given features, including the empty one.
+    pub fn all_combinations(features: &[&str]) -> Vec<PermutationKey> {
+        (0..1usize << features.len())
+            .map(|mask| {
+                PermutationKey::new(
+                    features.iter().enumerate().filter(|(bit, _)| mask & (1 << bit) != 0).map(|(_, f)| *f),
+                )
+            })
+            .collect()
+    }
+}
+
+impl<const N: usize> From<[&str; N]> for PermutationKey {
+    fn from(value: [&str; N]) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<&[&str]> for PermutationKey {
+    fn from(value: &[&str]) -> Self {
+        Self::new(value.iter().copied())
+    }
+}
+
+impl Display for PermutationKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}]", self.0.iter().cloned().collect::<Vec<_>>().join(", "))
+    }
+}
+
+/// Expands `#include` directives, injects defines and a default `#version` into GLSL sources.
+///
+/// - `#include "file"` (or `<file>`) is resolved relative to the asset root. Every file is
+///   included at most once; recursive includes are reported as an error.
+/// - `#version` is kept at the very top. Sources without one get the default version if set.
+///   `#version` lines in included files are dropped.
+/// - Defines are inserted directly after the `#version` line.
+/// - `#line` directives are emitted around every include, so compiler messages can be mapped
+///   back to the original file with `PreprocessedShader::resolve_log`.
+#[derive(Clone, Debug)]
+pub struct ShaderPreprocessor {
+    asset_root: PathBuf,
+    default_version: Option<String>,
+    defines: Vec<(String, Option<String>)>,
+}
+
+impl Default for ShaderPreprocessor {
+    fn default() -> Self {
+        Self::new("assets/shaders")
+    }
+}
+
+impl ShaderPreprocessor {
+    pub fn new<P: AsRef<Path>>(asset_root: P) -> Self {
+        Self {
+            asset_root: asset_root.as_ref().to_path_buf(),
+            default_version: None,
+            defines: Vec::new(),
+        }
+    }
+
+    /// Sets the version that is injected into sources without a `#version` line,
+    /// e.g. `"330 core"`.
+    pub fn with_default_version<T: Into<String>>(mut self, version: T) -> Self {
+        self.default_version = Some(version.into());
+        self
+    }
+
+    /// Adds `#define name value`. An existing define with the same name is replaced.
+    pub fn with_define<N: Into<String>, V: ToString>(mut self, name: N, value: V) -> Self {
+        self.set_define(name.into(), Some(value.to_string()));
+        self
+    }
+
+    /// Adds `#define name` without a value.
+    pub fn with_flag<N: Into<String>>(mut self, name: N) -> Self {
+        self.set_define(name.into(), None);
+        self
+    }
+
+    /// Returns a copy of the preprocessor with `#define FEATURE 1` for every feature of the key.
+    pub fn for_permutation(&self, key: &PermutationKey) -> Self {
+        key.features().fold(self.clone(), |preprocessor, feature| preprocessor.with_define(feature, 1))
+    }
+
+    pub fn asset_root(&self) -> &Path {
+        &self.asset_root
+    }
+
+    pub fn defines(&self) -> &[(String, Option<String>)] {
+        &self.defines
+    }
+
+    fn set_define(&mut self, name: String, value: Option<String>) {
+        match self.defines.iter_mut().find(|(existing, _)| *existing == name) {
+            Some(define) => define.1 = value,
+            None => self.defines.push((name, value)),
+        }
+    }
+
+    /// Resolves `path` relative to the asset root (absolute paths are kept).
+    pub fn resolve_path<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        let path = path.as_ref();
+        if path.is_absolute() || path.exists() {
+            path.to_path_buf()
+        } else {
+            self.asset_root.join(path)
+        }
+    }
+
+    /// Reads and preprocesses a shader file.
+    pub fn process_file<P: AsRef<Path>>(&self, path: P) -> Result<PreprocessedShader, ShaderError> {
+        let path = self.resolve_path(path);
+        let source = read_file(&path)?;
+        self.process(&source, path)
+    }
+
+    /// Preprocesses a shader source. `name` is used as the file name of the root source.
+    pub fn process_source<N: AsRef<Path>>(&self, source: &str, name: N)
+        -> Result<PreprocessedShader, ShaderError>
+    {
+        self.process(source, name.as_ref().to_path_buf())
+    }
+
+    fn process(&self, source: &str, root: PathBuf) -> Result<PreprocessedShader, ShaderError> {
+        let mut output = PreprocessedShader { source: String::new(), files: vec![root.clone()] };
+        let lines: Vec<&str> = source.lines().collect();
+
+        // `#version` must precede everything except comments, so everything up to it is dropped.
+        let version_index = lines.iter().position(|line| line.trim_start().starts_with("#version"));
+        let body_start = match (version_index, &self.default_version) {
+            (Some(index), _) => {
+                output.source.push_str(lines[index].trim());
+                output.source.push('\n');
+                index + 1
+            }
+            (None, Some(version)) => {
+                output.source.push_str(&format!("#version {}\n", version));
+                0
+            }
+            (None, None) => 0,
+        };
+
+        for (name, value) in &self.defines {
+            match value {
+                Some(value) => output.source.push_str(&format!("#define {} {}\n", name, value)),
+                None => output.source.push_str(&format!("#define {}\n", name)),
+            }
+        }
+
+        let mut include_stack = vec![normalize(&root)];
+        output.source.push_str(&format!("#line {} 0\n", body_start + 1));
+        self.expand(&lines[body_start..], body_start, 0, &mut include_stack, &mut output)?;
+        Ok(output)
+    }
+
+    fn expand(&self,
+              lines: &[&str],
+              first_line: usize,
+              file_index: usize,
+              include_stack: &mut Vec<PathBuf>,
+              output: &mut PreprocessedShader) -> Result<(), ShaderError> {
+        for (offset, line) in lines.iter().enumerate() {
+            let line_number = first_line + offset + 1;
+            let trimmed = line.trim_start();
+
+            if trimmed.starts_with("#version") {
+                // Only the root source may declare the version.
+                output.source.push('\n');
+                continue;
+            }
+
+            let Some(directive) = trimmed.strip_prefix("#include") else {
+                output.source.push_str(line);
+                output.source.push('\n');
+                continue;
+            };
+
+            let include = parse_include(directive).ok_or_else(|| ShaderError::PreprocessError {
+                file: output.files[file_index].display().to_string(),
+                line: line_number,
+                message: format!("Malformed include directive `{}`", trimmed.trim_end()),
+            })?;
+            let include_path = self.asset_root.join(include);
+            let normalized = normalize(&include_path);
+
+            if include_stack.contains(&normalized) {
+                return Err(ShaderError::PreprocessError {
+                    file: output.files[file_index].display().to_string(),
+                    line: line_number,
+                    message: format!("Recursive include of `{}`", include),
+                });
+            }
+
+            if output.files.iter().any(|file| normalize(file) == normalized) {
+                // Already included once; keep the line numbering intact.
+                output.source.push('\n');
+                continue;
+            }
+
+            let source = read_file(&include_path)?;
+            let include_index = output.files.len();
+            output.files.push(include_path);
+            include_stack.push(normalized);
+
+            let include_lines: Vec<&str> = source.lines().collect();
+            output.source.push_str(&format!("#line 1 {}\n", include_index));
+            self.expand(&include_lines, 0, include_index, include_stack, output)?;
+            output.source.push_str(&format!("#line {} {}\n", line_number + 1, file_index));
+
+            include_stack.pop();
+        }
+        Ok(())
+    }
+}
+
+fn parse_include(directive: &str) -> Option<&str> {
+    let directive = directive.trim();
+    let (open, close) = match directive.chars().next()? {
+        '"' => ('"', '"'),
+        '<' => ('<', '>'),
+        _ => return None,
+    };
+    let rest = directive.strip_prefix(open)?;
+    let end = rest.find(close)?;
+    let name = &rest[..end];
+    (!name.is_empty()).then_some(name)
+}
+
+fn read_file(path: &Path) -> Result<String, ShaderError> {
+    fs::read_to_string(path).map_err(|source| ShaderError::SourceReadError { path: path.to_path_buf(), source })
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asset_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("diego_glsl_{}_{}", name, std::process::id()));
+        fs::create_dir_all(dir.join("common")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_include_and_line_directives() {
+        let dir = asset_dir("include");
+        fs::write(dir.join("common/light.glsl"), "uniform vec3 lightColor;\nvec3 light() { return lightColor; }\n").unwrap();
+        fs::write(dir.join("cube.frag"), "#version 330 core\n#include \"common/light.glsl\"\nout vec4 FragColor;\n").unwrap();
+
+        let result = ShaderPreprocessor::new(&dir).process_file("cube.frag").unwrap();
+        let expected = "#version 330 core\n\
+                        #line 2 0\n\
+                        #line 1 1\n\
+                        uniform vec3 lightColor;\n\
+                        vec3 light() { return lightColor; }\n\
+                        #line 3 0\n\
+                        out vec4 FragColor;\n";
+        assert_eq!(result.source, expected);
+        assert_eq!(result.files.len(), 2);
+        assert!(result.files[1].ends_with("common/light.glsl"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_default_version_and_defines() {
+        let result = ShaderPreprocessor::new("unused")
+            .with_default_version("330 core")
+            .with_define("MAX_LIGHTS", 4)
+            .with_flag("NORMAL_MAP")
+            .process_source("void main() {}\n", "inline.frag")
+            .unwrap();
+        assert_eq!(
+            result.source,
+            "#version 330 core\n#define MAX_LIGHTS 4\n#define NORMAL_MAP\n#line 1 0\nvoid main() {}\n"
+        );
+    }
+
+    #[test]
+    fn test_recursive_include_is_rejected() {
+        let dir = asset_dir("recursive");
+        fs::write(dir.join("a.glsl"), "#include \"b.glsl\"\n").unwrap();
+        fs::write(dir.join("b.glsl"), "#include \"a.glsl\"\n").unwrap();
+
+        let result = ShaderPreprocessor::new(&dir).process_file("a.glsl");
+        assert!(matches!(result, Err(ShaderError::PreprocessError { line: 1, .. })));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_log() {
+        let shader = PreprocessedShader {
+            source: String::new(),
+            files: vec![PathBuf::from("cube.frag"), PathBuf::from("common/light.glsl")],
+        };
+        assert_eq!(
+            shader.resolve_log("0(12) : error C1008: undefined variable\nERROR: 1:3: 'x' : syntax error"),
+            "cube.frag:12 : error C1008: undefined variable\nERROR: common/light.glsl:3: 'x' : syntax error"
+        );
+        assert_eq!(shader.resolve_log("5:1: unknown"), "5:1: unknown");
+    }
+
+    #[test]
+    fn test_permutation_keys() {
+        let keys = PermutationKey::all_combinations(&["NORMAL_MAP", "SHADOWS"]);
+        assert_eq!(keys.len(), 4);
+        assert!(keys.contains(&PermutationKey::default()));
+        assert!(keys.contains(&PermutationKey::from(["SHADOWS", "NORMAL_MAP"])));
+
+        let preprocessor = ShaderPreprocessor::new("unused").for_permutation(&["NORMAL_MAP"].into());
+        assert_eq!(preprocessor.defines(), &[("NORMAL_MAP".to_string(), Some("1".to_string()))]);
+    }
+}
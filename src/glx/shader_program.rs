@@ -0,0 +1,341 @@
+use crate::assets::Reloadable;
+use crate::core::runtime_error::RuntimeError;
+use crate::gl::shader::{
+    attach_shader, compile_shader, create_program, create_shader, delete_program, delete_shader,
+    detach_shader, get_current_program, get_program_info_log, get_program_link_status, get_shader_compile_status,
+    get_shader_info_log, get_uniform_location, link_program, set_program_binary_retrievable,
+    shader_source, use_program,
+};
+use crate::gl::types::ShaderType;
+use crate::glx::program_cache::{ProgramCache, SharedProgramCache};
+use crate::glx::shader_preprocessor::{resolve_source_locations, ShaderPreprocessor};
+use crate::glx::{Bindable, BindableState};
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ShaderError {
+    #[error("Failed to read shader source `{path}`: {source}")]
+    SourceReadError { path: PathBuf, source: std::io::Error },
+    #[error("Failed to compile {shader_type} shader `{name}`:\n{log}")]
+    CompileError { shader_type: ShaderType, name: String, log: String },
+    #[error("Failed to link shader program `{name}`:\n{log}")]
+    LinkError { name: String, log: String },
+    #[error("Failed to preprocess `{file}` (line {line}): {message}")]
+    PreprocessError { file: String, line: usize, message: String },
+    #[error(transparent)]
+    RuntimeError(#[from] RuntimeError),
+}
+
+/// The source code of a single shader stage, optionally backed by a file on disk.
+///
+/// File backed stages can be run through a `ShaderPreprocessor`; in that case `source` holds
+/// the expanded source and `source_files` lists every included file.
+#[derive(Clone, Debug)]
+pub struct ShaderStage {
+    pub shader_type: ShaderType,
+    pub path: Option<PathBuf>,
+    pub source: String,
+    preprocessor: Option<ShaderPreprocessor>,
+    source_map: Vec<PathBuf>,
+}
+
+impl ShaderStage {
+    pub fn from_source<T: Into<String>>(shader_type: ShaderType, source: T) -> Self {
+        Self {
+            shader_type,
+            path: None,
+            source: source.into(),
+            preprocessor: None,
+            source_map: Vec::new(),
+        }
+    }
+
+    pub fn from_file<P: AsRef<Path>>(shader_type: ShaderType, path: P) -> Result<Self, ShaderError> {
+        let mut stage = Self {
+            shader_type,
+            path: Some(path.as_ref().to_path_buf()),
+            source: String::new(),
+            preprocessor: None,
+            source_map: Vec::new(),
+        };
+        stage.read_source()?;
+        Ok(stage)
+    }
+
+    /// Loads a stage from a file below the preprocessor's asset root and expands it.
+    pub fn preprocessed<P: AsRef<Path>>(preprocessor: &ShaderPreprocessor,
+                                        shader_type: ShaderType,
+                                        path: P) -> Result<Self, ShaderError> {
+        let mut stage = Self {
+            shader_type,
+            path: Some(preprocessor.resolve_path(path)),
+            source: String::new(),
+            preprocessor: Some(preprocessor.clone()),
+            source_map: Vec::new(),
+        };
+        stage.read_source()?;
+        Ok(stage)
+    }
+
+    /// Returns all files the stage source was built from, including resolved includes.
+    pub fn source_files(&self) -> Vec<PathBuf> {
+        if self.source_map.is_empty() {
+            self.path.iter().cloned().collect()
+        } else {
+            self.source_map.clone()
+        }
+    }
+
+    /// Re-reads the source from disk. Stages without a file are left untouched.
+    fn read_source(&mut self) -> Result<(), ShaderError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        match &self.preprocessor {
+            Some(preprocessor) => {
+                let preprocessed = preprocessor.process_file(path)?;
+                self.source = preprocessed.source;
+                self.source_map = preprocessed.files;
+            }
+            None => {
+                self.source = fs::read_to_string(path).map_err(|source| {
+                    ShaderError::SourceReadError { path: path.clone(), source }
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    fn display_name(&self) -> String {
+        match &self.path {
+            Some(path) => path.display().to_string(),
+            None => format!("<{} source>", self.shader_type),
+        }
+    }
+}
+
+/// A linked OpenGL shader program built from one or more `ShaderStage`s.
+///
+/// The program keeps its stage sources so it can be rebuilt in place. A failed rebuild leaves
+/// the previously linked program active, which makes it safe to use for hot reloading.
+///
+/// Programs created with a `ProgramCache` are loaded from and stored to the cache, both
+/// initially and on every rebuild.
+#[derive(Debug)]
+pub struct ShaderProgram {
+    id: u32,
+    name: Option<String>,
+    stages: Vec<ShaderStage>,
+    cache: Option<SharedProgramCache>,
+}
+
+impl ShaderProgram {
+    pub fn from_stages<T: Into<Vec<ShaderStage>>>(stages: T) -> Result<Self, ShaderError> {
+        Self::from_stages_cached(stages, None)
+    }
+
+    /// Creates a program from the given stages, using the program binary cache if one is given.
+    pub fn from_stages_cached<T: Into<Vec<ShaderStage>>>(stages: T,
+                                                         cache: Option<&SharedProgramCache>)
+        -> Result<Self, ShaderError>
+    {
+        let mut program = Self {
+            id: 0,
+            name: None,
+            stages: stages.into(),
+            cache: cache.cloned(),
+        };
+        program.id = build_program(&program.stages, &program.to_string(), program.cache.as_deref())?;
+        Ok(program)
+    }
+
+    pub fn from_files<P: AsRef<Path>>(vertex_file: P, fragment_file: P) -> Result<Self, ShaderError> {
+        Self::from_stages(vec![
+            ShaderStage::from_file(ShaderType::Vertex, vertex_file)?,
+            ShaderStage::from_file(ShaderType::Fragment, fragment_file)?,
+        ])
+    }
+
+    /// Loads a vertex and fragment shader through the given preprocessor.
+    pub fn from_files_with<P: AsRef<Path>>(preprocessor: &ShaderPreprocessor,
+                                           vertex_file: P,
+                                           fragment_file: P) -> Result<Self, ShaderError> {
+        Self::from_files_with_cached(preprocessor, vertex_file, fragment_file, None)
+    }
+
+    /// Same as `from_files_with`, using the program binary cache if one is given.
+    pub fn from_files_with_cached<P: AsRef<Path>>(preprocessor: &ShaderPreprocessor,
+                                                  vertex_file: P,
+                                                  fragment_file: P,
+                                                  cache: Option<&SharedProgramCache>)
+        -> Result<Self, ShaderError>
+    {
+        Self::from_stages_cached(vec![
+            ShaderStage::preprocessed(preprocessor, ShaderType::Vertex, vertex_file)?,
+            ShaderStage::preprocessed(preprocessor, ShaderType::Fragment, fragment_file)?,
+        ], cache)
+    }
+
+    pub fn from_sources<T: Into<String>>(vertex_source: T, fragment_source: T) -> Result<Self, ShaderError> {
+        Self::from_stages(vec![
+            ShaderStage::from_source(ShaderType::Vertex, vertex_source),
+            ShaderStage::from_source(ShaderType::Fragment, fragment_source),
+        ])
+    }
+
+    pub fn with_name<T: Into<String>>(mut self, name: T) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn stages(&self) -> &[ShaderStage] {
+        &self.stages
+    }
+
+    pub fn uniform_location(&self, name: &str) -> Option<i32> {
+        get_uniform_location(self.id, name)
+    }
+
+    /// Re-reads all file backed stages and links a new program from them.
+    ///
+    /// The program ID is only replaced when compiling and linking succeeded; otherwise the
+    /// previous program and sources are kept and the error is returned.
+    pub fn rebuild(&mut self) -> Result<(), ShaderError> {
+        let mut stages = self.stages.clone();
+        for stage in &mut stages {
+            stage.read_source()?;
+        }
+
+        let program_id = build_program(&stages, &self.to_string(), self.cache.as_deref())?;
+        let was_bound = self.is_bound().unwrap_or(false);
+        delete_program(self.id);
+        self.id = program_id;
+        self.stages = stages;
+        if was_bound {
+            use_program(self.id);
+        }
+        Ok(())
+    }
+}
+
+impl Display for ShaderProgram {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (&self.name, self.stages.first()) {
+            (Some(name), _) => write!(f, "{}", name),
+            (None, Some(stage)) => write!(f, "{}", stage.display_name()),
+            (None, None) => write!(f, "<empty program>"),
+        }
+    }
+}
+
+impl Drop for ShaderProgram {
+    fn drop(&mut self) {
+        if self.id != 0 {
+            delete_program(self.id);
+        }
+    }
+}
+
+impl Bindable for ShaderProgram {
+    fn bind(&self) -> anyhow::Result<()> {
+        use_program(self.id);
+        Ok(())
+    }
+
+    fn unbind(&self) -> anyhow::Result<()> {
+        use_program(0);
+        Ok(())
+    }
+}
+
+impl BindableState for ShaderProgram {
+    fn is_bound(&self) -> anyhow::Result<bool> {
+        Ok(self.id > 0 && self.id == get_current_program())
+    }
+}
+
+impl Reloadable for ShaderProgram {
+    fn source_files(&self) -> Vec<PathBuf> {
+        self.stages.iter().flat_map(ShaderStage::source_files).collect()
+    }
+
+    fn reload(&mut self) -> anyhow::Result<()> {
+        Ok(self.rebuild()?)
+    }
+
+    fn asset_name(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Compiles every stage and links them into a new program object, unless the cache already
+/// holds a usable binary for the stages. All intermediate objects are released again when any
+/// step fails.
+fn build_program(stages: &[ShaderStage],
+                 program_name: &str,
+                 cache: Option<&ProgramCache>) -> Result<u32, ShaderError> {
+    let cache = cache.filter(|cache| cache.is_enabled());
+    if let Some(program_id) = cache.and_then(|cache| cache.load(stages)) {
+        return Ok(program_id);
+    }
+
+    let mut shader_ids = Vec::with_capacity(stages.len());
+    let result = compile_stages(stages, &mut shader_ids)
+        .and_then(|_| link_shaders(&shader_ids, program_name, cache.is_some()));
+    for shader_id in shader_ids {
+        delete_shader(shader_id);
+    }
+
+    if let (Ok(program_id), Some(cache)) = (&result, cache) {
+        cache.store(*program_id, stages);
+    }
+    result
+}
+
+fn compile_stages(stages: &[ShaderStage], shader_ids: &mut Vec<u32>) -> Result<(), ShaderError> {
+    for stage in stages {
+        let shader_id = create_shader(stage.shader_type)?;
+        shader_ids.push(shader_id);
+        shader_source(shader_id, &stage.source)?;
+        compile_shader(shader_id);
+        if !get_shader_compile_status(shader_id) {
+            return Err(ShaderError::CompileError {
+                shader_type: stage.shader_type,
+                name: stage.display_name(),
+                log: resolve_source_locations(&stage.source_map, &get_shader_info_log(shader_id)),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn link_shaders(shader_ids: &[u32], program_name: &str, retrievable: bool)
+    -> Result<u32, ShaderError>
+{
+    let program_id = create_program()?;
+    if retrievable {
+        set_program_binary_retrievable(program_id, true);
+    }
+    for &shader_id in shader_ids {
+        attach_shader(program_id, shader_id);
+    }
+    link_program(program_id);
+    for &shader_id in shader_ids {
+        detach_shader(program_id, shader_id);
+    }
+
+    if !get_program_link_status(program_id) {
+        let log = get_program_info_log(program_id);
+        delete_program(program_id);
+        return Err(ShaderError::LinkError { name: program_name.to_string(), log });
+    }
+    Ok(program_id)
+}
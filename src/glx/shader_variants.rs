@@ -0,0 +1,111 @@
+use crate::assets::Reloadable;
+use crate::glx::program_cache::SharedProgramCache;
+use crate::glx::shader_preprocessor::{PermutationKey, ShaderPreprocessor};
+use crate::glx::shader_program::{ShaderError, ShaderProgram};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// A set of shader programs built from the same sources with different feature defines.
+///
+/// Every `PermutationKey` selects one program; for a key `{NORMAL_MAP}` the sources are compiled
+/// with `#define NORMAL_MAP 1`.
+///
+/// # Example
+/// ```no_run
+/// use diego::glx::shader_preprocessor::{PermutationKey, ShaderPreprocessor};
+/// use diego::glx::shader_variants::ShaderVariants;
+///
+/// let preprocessor = ShaderPreprocessor::default().with_default_version("330 core");
+/// let variants = ShaderVariants::from_files(
+///     &preprocessor,
+///     "light/lighting.vert",
+///     "light/lighting.frag",
+///     &PermutationKey::all_combinations(&["NORMAL_MAP"]),
+/// ).unwrap();
+///
+/// let program = variants.get(&["NORMAL_MAP"].into()).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct ShaderVariants {
+    programs: HashMap<PermutationKey, ShaderProgram>,
+}
+
+impl ShaderVariants {
+    /// Builds one program per key from a vertex and a fragment shader file.
+    pub fn from_files<P: AsRef<Path>>(preprocessor: &ShaderPreprocessor,
+                                      vertex_file: P,
+                                      fragment_file: P,
+                                      keys: &[PermutationKey]) -> Result<Self, ShaderError> {
+        Self::from_files_cached(preprocessor, vertex_file, fragment_file, keys, None)
+    }
+
+    /// Same as `from_files`, but loads and stores the programs through a program binary cache.
+    pub fn from_files_cached<P: AsRef<Path>>(preprocessor: &ShaderPreprocessor,
+                                             vertex_file: P,
+                                             fragment_file: P,
+                                             keys: &[PermutationKey],
+                                             cache: Option<&SharedProgramCache>)
+        -> Result<Self, ShaderError>
+    {
+        let mut programs = HashMap::with_capacity(keys.len());
+        for key in keys {
+            let program = ShaderProgram::from_files_with_cached(
+                &preprocessor.for_permutation(key),
+                vertex_file.as_ref(),
+                fragment_file.as_ref(),
+                cache,
+            )?;
+            let name = format!("{} {}", program, key);
+            programs.insert(key.clone(), program.with_name(name));
+        }
+        Ok(Self { programs })
+    }
+
+    pub fn get(&self, key: &PermutationKey) -> Option<&ShaderProgram> {
+        self.programs.get(key)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &PermutationKey> {
+        self.programs.keys()
+    }
+
+    pub fn len(&self) -> usize {
+        self.programs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.programs.is_empty()
+    }
+}
+
+impl Reloadable for ShaderVariants {
+    fn source_files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> =
+            self.programs.values().flat_map(|program| program.source_files()).collect();
+        files.sort();
+        files.dedup();
+        files
+    }
+
+    /// Rebuilds every variant. Variants that fail keep their previous program; the first
+    /// error is returned.
+    fn reload(&mut self) -> anyhow::Result<()> {
+        let mut first_error = None;
+        for program in self.programs.values_mut() {
+            if let Err(e) = program.rebuild() {
+                first_error.get_or_insert(e);
+            }
+        }
+        match first_error {
+            Some(e) => Err(e.into()),
+            None => Ok(()),
+        }
+    }
+
+    fn asset_name(&self) -> String {
+        match self.programs.values().next() {
+            Some(program) => format!("{} ({} variants)", program, self.programs.len()),
+            None => "<empty shader variants>".to_string(),
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use crate::assets::Reloadable;
+use crate::core::runtime_error::RuntimeError;
+use crate::gl::dsa::is_dsa_enabled;
+use crate::gl::texture::{
+    active_texture, bind_texture, bind_texture_unit, create_textures, delete_textures,
+    gen_textures, generate_mipmap, generate_texture_mipmap, get_bound_texture, mipmap_levels,
+    set_texture_filter, set_texture_wrap, tex_image_2d, texture_filter, texture_storage_2d,
+    texture_sub_image_2d, texture_wrap,
+};
+use crate::gl::types::{TextureFilter, TextureFormat, TextureTarget, TextureWrap};
+use crate::glx::{Bindable, BindableState};
+use image::DynamicImage;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum TextureError {
+    #[error("Failed to load texture image `{path}`: {source}")]
+    ImageLoadError { path: PathBuf, source: image::ImageError },
+    #[error("Texture `{0}` has no image file to reload from")]
+    NoSourceFile(String),
+    #[error("Expected {expected} bytes of pixel data for a {width}x{height} {format:?} texture, got {actual}")]
+    InvalidPixelData { format: TextureFormat, width: u32, height: u32, expected: usize, actual: usize },
+    #[error(transparent)]
+    RuntimeError(#[from] RuntimeError),
+}
+
+/// Decoded 8-bit pixel data, ready to be uploaded to a texture.
+struct ImageData {
+    format: TextureFormat,
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+/// A two-dimensional OpenGL texture with a generated mipmap chain.
+///
+/// With Direct State Access (see `gl::dsa`) the texture gets immutable storage and is edited
+/// without binding it; otherwise it is bound for every upload.
+#[derive(Debug)]
+pub struct Texture {
+    id: u32,
+    path: Option<PathBuf>,
+    format: TextureFormat,
+    width: u32,
+    height: u32,
+}
+
+impl Texture {
+    /// Loads a PNG or JPEG image and uploads it into a new texture.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, TextureError> {
+        let image = load_image(path.as_ref())?;
+        let mut texture = Self::create(image.format)?;
+        texture.path = Some(path.as_ref().to_path_buf());
+        texture.upload(&image)?;
+        Ok(texture)
+    }
+
+    /// Creates a texture from tightly packed pixel rows, starting with the bottom row.
+    ///
+    /// Fails with `TextureError::InvalidPixelData` unless `pixels` holds exactly
+    /// `width * height * format.channels()` bytes.
+    pub fn from_pixels(format: TextureFormat, width: u32, height: u32, pixels: &[u8])
+        -> Result<Self, TextureError>
+    {
+        let expected = width as usize * height as usize * format.channels();
+        if pixels.len() != expected {
+            return Err(TextureError::InvalidPixelData { format, width, height, expected, actual: pixels.len() });
+        }
+        let mut texture = Self::create(format)?;
+        texture.upload(&ImageData { format, width, height, pixels: pixels.to_vec() })?;
+        Ok(texture)
+    }
+
+    fn create(format: TextureFormat) -> Result<Self, TextureError> {
+        let id = if is_dsa_enabled() {
+            create_textures(TextureTarget::Texture2D, 1)?[0]
+        } else {
+            gen_textures(1)?[0]
+        };
+        Ok(Self { id, path: None, format, width: 0, height: 0 })
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Binds the texture to the given texture unit.
+    pub fn bind_to_unit(&self, unit: u32) {
+        if is_dsa_enabled() {
+            bind_texture_unit(unit, self.id);
+        } else {
+            active_texture(unit);
+            bind_texture(TextureTarget::Texture2D, self.id);
+        }
+    }
+
+    /// Decodes the image file again and replaces the texture contents.
+    ///
+    /// The texture ID stays the same, so every user of the texture sees the new image. The
+    /// only exception is a changed image size or format with Direct State Access: immutable
+    /// storage cannot be resized, so a new texture object replaces the old one. If the image
+    /// cannot be decoded the old contents are kept.
+    pub fn reload_from_file(&mut self) -> Result<(), TextureError> {
+        let path = self.path.clone().ok_or_else(|| TextureError::NoSourceFile(self.asset_name()))?;
+        let image = load_image(&path)?;
+        self.upload(&image)
+    }
+
+    fn upload(&mut self, image: &ImageData) -> Result<(), TextureError> {
+        if is_dsa_enabled() {
+            self.upload_dsa(image)?;
+        } else {
+            bind_texture(TextureTarget::Texture2D, self.id);
+            tex_image_2d(TextureTarget::Texture2D, image.format, image.width, image.height, &image.pixels);
+            set_texture_wrap(TextureTarget::Texture2D, TextureWrap::Repeat, TextureWrap::Repeat);
+            set_texture_filter(TextureTarget::Texture2D, TextureFilter::LinearMipmapLinear, TextureFilter::Linear);
+            generate_mipmap(TextureTarget::Texture2D);
+            bind_texture(TextureTarget::Texture2D, 0);
+        }
+
+        self.format = image.format;
+        self.width = image.width;
+        self.height = image.height;
+        Ok(())
+    }
+
+    fn upload_dsa(&mut self, image: &ImageData) -> Result<(), TextureError> {
+        let mut has_storage = self.width != 0;
+        if has_storage && (image.format, image.width, image.height) != (self.format, self.width, self.height) {
+            let id = create_textures(TextureTarget::Texture2D, 1)?[0];
+            delete_textures([self.id]);
+            self.id = id;
+            has_storage = false;
+        }
+
+        if !has_storage {
+            let levels = mipmap_levels(image.width, image.height);
+            texture_storage_2d(self.id, levels, image.format, image.width, image.height);
+            texture_wrap(self.id, TextureWrap::Repeat, TextureWrap::Repeat);
+            texture_filter(self.id, TextureFilter::LinearMipmapLinear, TextureFilter::Linear);
+        }
+        texture_sub_image_2d(self.id, image.format, image.width, image.height, &image.pixels);
+        generate_texture_mipmap(self.id);
+        Ok(())
+    }
+}
+
+impl Drop for Texture {
+    fn drop(&mut self) {
+        if self.id != 0 {
+            delete_textures([self.id]);
+        }
+    }
+}
+
+impl Bindable for Texture {
+    fn bind(&self) -> anyhow::Result<()> {
+        bind_texture(TextureTarget::Texture2D, self.id);
+        Ok(())
+    }
+
+    fn unbind(&self) -> anyhow::Result<()> {
+        bind_texture(TextureTarget::Texture2D, 0);
+        Ok(())
+    }
+}
+
+impl BindableState for Texture {
+    fn is_bound(&self) -> anyhow::Result<bool> {
+        Ok(self.id > 0 && self.id == get_bound_texture(TextureTarget::Texture2D))
+    }
+}
+
+impl Reloadable for Texture {
+    fn source_files(&self) -> Vec<PathBuf> {
+        self.path.iter().cloned().collect()
+    }
+
+    fn reload(&mut self) -> anyhow::Result<()> {
+        Ok(self.reload_from_file()?)
+    }
+
+    fn asset_name(&self) -> String {
+        match &self.path {
+            Some(path) => path.display().to_string(),
+            None => format!("texture #{}", self.id),
+        }
+    }
+}
+
+/// Decodes an image file and flips it vertically, since OpenGL expects the bottom row first.
+fn load_image(path: &Path) -> Result<ImageData, TextureError> {
+    let image = image::open(path)
+        .map_err(|source| TextureError::ImageLoadError { path: path.to_path_buf(), source })?
+        .flipv();
+    let (width, height) = (image.width(), image.height());
+    let (format, pixels) = match image {
+        DynamicImage::ImageLuma8(img) => (TextureFormat::Red, img.into_raw()),
+        DynamicImage::ImageLumaA8(img) => (TextureFormat::Rg, img.into_raw()),
+        DynamicImage::ImageRgb8(img) => (TextureFormat::Rgb, img.into_raw()),
+        other => (TextureFormat::Rgba, other.to_rgba8().into_raw()),
+    };
+    Ok(ImageData { format, width, height, pixels })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_pixels_checks_the_data_length() {
+        let result = Texture::from_pixels(TextureFormat::Rgb, 2, 2, &[0; 11]);
+        assert!(matches!(result, Err(TextureError::InvalidPixelData { expected: 12, actual: 11, .. })));
+    }
+}
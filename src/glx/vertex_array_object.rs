@@ -1,19 +1,27 @@
 use std::fmt::{Display, Formatter};
-use crate::gl::state::get_integer_v;
-use crate::gl::types::GlGetParameter;
-use crate::gl::vao::{bind_vertex_array, gen_vertex_array};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use crate::gl::dsa::is_dsa_enabled;
+use crate::gl::vao::{bind_vertex_array, create_vertex_array, delete_vertex_array, gen_vertex_array, get_bound_vertex_array};
 use crate::glx::{Bindable, BindableState};
 
+static NEXT_VAO_TOKEN: AtomicU64 = AtomicU64::new(1);
+
 #[derive(Debug, Eq, PartialEq, Hash)]
 pub struct VertexArrayObject {
     id: u32,
+    token: u64,
+    // Dropped with the VAO; caches hold a `Weak` to notice deleted VAOs
+    alive: Arc<()>,
     name: Option<String>,
 }
 
 impl Default for VertexArrayObject {
     fn default() -> Self {
         Self {
-            id: gen_vertex_array(),
+            id: if is_dsa_enabled() { create_vertex_array() } else { gen_vertex_array() },
+            token: NEXT_VAO_TOKEN.fetch_add(1, Ordering::Relaxed),
+            alive: Arc::new(()),
             name: None,
         }
     }
@@ -29,12 +37,35 @@ impl Display for VertexArrayObject {
 }
 
 impl VertexArrayObject {
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// A number unique to this VAO for the lifetime of the process. Unlike the id it is never
+    /// reused by GL after the VAO is deleted.
+    pub fn token(&self) -> u64 {
+        self.token
+    }
+
+    /// Returns a handle that can no longer be upgraded once the VAO was dropped.
+    pub(crate) fn liveness(&self) -> Weak<()> {
+        Arc::downgrade(&self.alive)
+    }
+
     fn with_name<T: Into<String>>(mut self, name: T) -> Self {
         self.name = Some(name.into());
         self
     }
 }
 
+impl Drop for VertexArrayObject {
+    fn drop(&mut self) {
+        if self.id != 0 {
+            delete_vertex_array(self.id);
+        }
+    }
+}
+
 impl Bindable for VertexArrayObject {
     fn bind(&self) -> anyhow::Result<()> {
         bind_vertex_array(self.id);
@@ -49,6 +80,6 @@ impl Bindable for VertexArrayObject {
 
 impl BindableState for VertexArrayObject {
     fn is_bound(&self) -> anyhow::Result<bool> {
-        Ok(self.id > 0 && self.id == get_integer_v(GlGetParameter::VertexArrayBinding) as u32)   
+        Ok(self.id > 0 && self.id == get_bound_vertex_array())
     }
 }
@@ -12,6 +12,11 @@ pub struct VertexAttribute {
     pub normalized: bool,
     pub stride: i32,
     pub offset: Option<u32>,
+    /// Optional shader location. Attributes without a location use the next free location.
+    pub location: Option<u32>,
+    /// Number of matrix columns. Matrix attributes (e.g. `mat4` with 4 components and
+    /// 4 columns) occupy one location per column.
+    pub columns: u8,
 }
 
 impl VertexAttribute {
@@ -23,6 +28,8 @@ impl VertexAttribute {
             normalized: false,
             stride: 0,
             offset: None,
+            location: None,
+            columns: 1,
         }
     }
 
@@ -56,9 +63,30 @@ impl VertexAttribute {
         self
     }
 
+    pub fn with_location(mut self, location: Option<u32>) -> Self {
+        self.location = location;
+        self
+    }
+
+    pub fn with_columns(mut self, columns: u8) -> Self {
+        self.columns = columns.max(1);
+        self
+    }
+
+    /// Creates a matrix attribute with `columns` columns of `rows` components each,
+    /// e.g. `VertexAttribute::matrix(4, 4, VertexDataType::Float)` for a `mat4`.
+    pub fn matrix(rows: u8, columns: u8, data_type: VertexDataType) -> Self {
+        Self::new(rows, data_type).with_columns(columns)
+    }
+
+    /// Returns the size in bytes of a single column.
+    pub fn column_size(&self) -> usize {
+        self.data_type.size() * self.components as usize
+    }
+
     /// Calculates the byte size of the attribute based on its specifications or its type.
     pub fn calculate_size(&self) -> usize {
-        self.data_type.size() * self.components as usize
+        self.column_size() * self.columns as usize
     }
 }
 
@@ -84,11 +112,17 @@ impl From<VertexAttributeType> for VertexAttribute {
     }
 }
 
+// The name is only descriptive and therefore not part of the identity; two attributes are equal
+// if they result in the same attribute pointer configuration.
 impl Hash for VertexAttribute {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.components.hash(state);
         self.data_type.hash(state);
         self.normalized.hash(state);
+        self.stride.hash(state);
+        self.offset.hash(state);
+        self.location.hash(state);
+        self.columns.hash(state);
     }
 }
 
@@ -97,6 +131,10 @@ impl PartialEq<Self> for VertexAttribute {
         self.components == other.components
             && self.data_type == other.data_type
             && self.normalized == other.normalized
+            && self.stride == other.stride
+            && self.offset == other.offset
+            && self.location == other.location
+            && self.columns == other.columns
     }
 }
 
@@ -50,8 +50,8 @@ impl VertexAttributeType {
     }
 }
 
-impl Into<GLenum> for VertexAttributeType {
-    fn into(self) -> GLenum {
-        self.data_type()
+impl From<VertexAttributeType> for GLenum {
+    fn from(value: VertexAttributeType) -> Self {
+        value.data_type()
     }
 }
@@ -47,9 +47,9 @@ impl TryFrom<GLenum> for VertexDataType {
     }
 }
 
-impl Into<GLenum> for VertexDataType {
-    fn into(self) -> GLenum {
-        match self {
+impl From<VertexDataType> for GLenum {
+    fn from(value: VertexDataType) -> Self {
+        match value {
             VertexDataType::Byte => gl::BYTE,
             VertexDataType::UnsignedByte => gl::UNSIGNED_BYTE,
             VertexDataType::Short => gl::SHORT,
@@ -1,8 +1,55 @@
-use crate::core::SizeOf;
+use crate::core::runtime_error::RuntimeError;
+use crate::core::runtime_info::RuntimeInfo;
+use crate::create_runtime_info;
+use crate::gl::buffer::{bind_buffer, get_bound_buffer};
+use crate::gl::dsa::is_dsa_enabled;
+use crate::gl::types::BufferType;
+use crate::gl::vao::{
+    bind_vertex_array, enable_vertex_array_attrib, get_bound_vertex_array, enable_vertex_attrib_array,
+    vertex_array_attrib_binding, vertex_array_attrib_format, vertex_array_attrib_format_i,
+    vertex_array_attrib_format_l, vertex_array_binding_divisor, vertex_array_vertex_buffer,
+    vertex_attrib_divisor, vertex_attrib_pointer, vertex_attrib_pointer_i, vertex_attrib_pointer_l,
+};
+use crate::glx::vertex_array_object::VertexArrayObject;
 use crate::glx::vertex_attribute::VertexAttribute;
+use crate::glx::vertex_data_type::VertexDataType;
 use std::cell::RefCell;
+use std::ffi::c_void;
 use std::rc::Rc;
 
+/// Derives `VertexLayout` for a `#[repr(C)]` struct from its fields.
+///
+/// # Example
+/// ```
+/// use diego::glx::vertex_layout::VertexLayout;
+///
+/// #[repr(C)]
+/// #[derive(VertexLayout)]
+/// struct SpriteVertex {
+///     #[vertex(name = "aPos", location = 0)]
+///     position: [f32; 2],
+///     #[vertex(normalized)]
+///     color: [u8; 4],
+/// }
+///
+/// let attributes = SpriteVertex::attributes();
+/// assert_eq!(attributes[0].name.as_deref(), Some("aPos"));
+/// assert_eq!(attributes[1].offset, Some(8));
+/// assert!(attributes[1].normalized);
+/// ```
+///
+/// Field types without a vertex attribute representation are rejected:
+/// ```compile_fail
+/// use diego::glx::vertex_layout::VertexLayout;
+///
+/// #[repr(C)]
+/// #[derive(VertexLayout)]
+/// struct InvalidVertex {
+///     label: String,
+/// }
+/// ```
+pub use diego_derive::VertexLayout;
+
 pub trait VertexLayout {
     fn attributes() -> Vec<VertexAttribute>;
     fn layout_size() -> usize {
@@ -10,9 +57,31 @@ pub trait VertexLayout {
     }
 }
 
-#[derive(Default, Debug)]
+/// How often the attributes of a vertex buffer binding advance.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum StepRate {
+    /// The attributes advance once per vertex.
+    #[default]
+    PerVertex,
+    /// The attributes advance once every `n` instances.
+    PerInstance(u32),
+}
+
+impl StepRate {
+    /// Returns the value passed to `glVertexAttribDivisor`.
+    pub fn divisor(&self) -> u32 {
+        match self {
+            StepRate::PerVertex => 0,
+            StepRate::PerInstance(instances) => (*instances).max(1),
+        }
+    }
+}
+
+/// The attributes read from a single vertex buffer.
+#[derive(Default, Debug, Clone)]
 pub struct DynamicVertexLayout {
     attributes: Vec<VertexAttribute>,
+    step_rate: StepRate,
 }
 
 impl DynamicVertexLayout {
@@ -73,21 +142,43 @@ impl DynamicVertexLayout {
     /// This function accepts both arrays and vectors for flexibility in input.
     pub fn from_attributes<T: AsRef<[VertexAttribute]>>(attributes: T) -> Self {
         Self {
-            attributes: attributes.as_ref().to_vec()
+            attributes: attributes.as_ref().to_vec(),
+            step_rate: StepRate::PerVertex,
         }
     }
 
+    /// Creates a layout from the attributes of a `VertexLayout` type.
+    pub fn of<V: VertexLayout>() -> Self {
+        Self::from_attributes(V::attributes())
+    }
+
+    /// Sets how often the attributes of this layout advance, e.g.
+    /// `StepRate::PerInstance(1)` for per-instance data.
+    pub fn with_step_rate(mut self, step_rate: StepRate) -> Self {
+        self.step_rate = step_rate;
+        self
+    }
+
+    pub fn step_rate(&self) -> StepRate {
+        self.step_rate
+    }
+
+    /// Whether every attribute has a stride and an offset, as after `finalize_attributes`.
+    pub fn is_finalized(&self) -> bool {
+        self.attributes.iter().all(|attr| attr.stride != 0 && attr.offset.is_some())
+    }
+
     /// Finalizes the attributes by calculating missing stride and offset values.
     ///
     /// This function performs the following steps:
     ///
     /// 1. **Calculate Stride**: If any attribute has a stride value of 0, it computes the stride
-    /// based on the number of components and the data type size for each attribute. It then sets
-    /// the stride for attributes where it is missing (set to 0).
+    ///    based on the number of components and the data type size for each attribute. It then
+    ///    sets the stride for attributes where it is missing (set to 0).
     ///
     /// 2. **Calculate Offset**: For each attribute, if the offset is not set, it calculates and
-    /// assigns the offset based on the cumulative size of the components and data type size.
-    /// The offset starts from 0 and increments as the attributes are processed.
+    ///    assigns the offset based on the cumulative size of the components and data type size.
+    ///    The offset starts from 0 and increments as the attributes are processed.
     ///
     /// # Notes:
     /// - The `stride` of an attribute represents the number of bytes between the start of one
@@ -99,7 +190,7 @@ impl DynamicVertexLayout {
         if self.attributes.iter().any(|attr| attr.stride == 0) {
             let stride: i32 = self.attributes
                 .iter()
-                .map(|a| a.components as i32 * a.data_type.size() as i32)
+                .map(|a| a.calculate_size() as i32)
                 .sum();
             for attr in &mut self.attributes {
                 if attr.stride == 0 {
@@ -114,7 +205,7 @@ impl DynamicVertexLayout {
             if attr.offset.is_none() {
                 attr.offset = Some(current_offset);
             }
-            current_offset += attr.components as u32 * attr.data_type.size() as u32;
+            current_offset += attr.calculate_size() as u32;
         }
     }
 
@@ -139,6 +230,181 @@ impl DynamicVertexLayout {
     pub fn get_attributes(&self) -> &[VertexAttribute] {
         &self.attributes
     }
+
+    /// Configures and enables the attribute arrays of the currently bound VAO for the
+    /// currently bound array buffer.
+    ///
+    /// Attributes without an explicit location are assigned to the next free location,
+    /// starting at `0`. The attributes must have been finalized with `finalize_attributes`
+    /// before.
+    ///
+    /// The attribute pointer function is chosen by the data type: integer types that are not
+    /// normalized use `vertex_attrib_pointer_i`, doubles use `vertex_attrib_pointer_l` and all
+    /// other types `vertex_attrib_pointer`.
+    pub fn apply(&self) {
+        self.apply_from(0);
+    }
+
+    /// Same as `apply`, but automatic locations start at `first_location`. Used to place
+    /// several layouts (one per buffer) behind each other.
+    pub fn apply_from(&self, first_location: u32) {
+        let divisor = self.step_rate.divisor();
+        for (location, attr) in self.locations_from(first_location).into_iter().zip(&self.attributes) {
+            for column in 0..attr.columns as u32 {
+                apply_attribute(location + column, attr, column_offset(attr, column));
+                vertex_attrib_divisor(location + column, divisor);
+                enable_vertex_attrib_array(location + column);
+            }
+        }
+    }
+
+    /// Configures and enables the attribute arrays of `vao` to read from the array buffer
+    /// `buffer_id`, with automatic locations starting at `first_location`.
+    ///
+    /// With Direct State Access the buffer is attached to the vertex buffer binding point
+    /// `binding_index` and the VAO is configured without binding it. Otherwise the VAO and the
+    /// buffer are bound and configured with `apply_from`, and the previously bound VAO and
+    /// array buffer are bound again; the binding index is unused in that case, since every
+    /// attribute refers to its buffer directly.
+    ///
+    /// The attributes must have been finalized with `finalize_attributes` before.
+    pub fn attach(&self, vao: &VertexArrayObject, binding_index: u32, buffer_id: u32, first_location: u32) {
+        if !is_dsa_enabled() {
+            let previous_vao = get_bound_vertex_array();
+            let previous_buffer = get_bound_buffer(BufferType::ArrayBuffer);
+            bind_vertex_array(vao.id());
+            bind_buffer(BufferType::ArrayBuffer, buffer_id);
+            self.apply_from(first_location);
+            bind_vertex_array(previous_vao);
+            bind_buffer(BufferType::ArrayBuffer, previous_buffer);
+            return;
+        }
+
+        let vao = vao.id();
+        vertex_array_vertex_buffer(vao, binding_index, buffer_id, 0, self.stride());
+        vertex_array_binding_divisor(vao, binding_index, self.step_rate.divisor());
+        for (location, attr) in self.locations_from(first_location).into_iter().zip(&self.attributes) {
+            for column in 0..attr.columns as u32 {
+                format_attribute(vao, location + column, attr, column_offset(attr, column) as u32);
+                vertex_array_attrib_binding(vao, location + column, binding_index);
+                enable_vertex_array_attrib(vao, location + column);
+            }
+        }
+    }
+
+    /// Returns the byte distance between consecutive vertices: the stride of the finalized
+    /// attributes, or the packed size of the layout if no stride is set.
+    pub fn stride(&self) -> i32 {
+        match self.attributes.first() {
+            Some(attr) if attr.stride != 0 => attr.stride,
+            _ => self.layout_size() as i32,
+        }
+    }
+
+    /// Returns the first shader location of every attribute, in attribute order.
+    pub fn locations(&self) -> Vec<u32> {
+        self.locations_from(0)
+    }
+
+    /// Returns the first shader location of every attribute when automatic locations start at
+    /// `first_location`.
+    pub fn locations_from(&self, first_location: u32) -> Vec<u32> {
+        let mut next_location = first_location;
+        self.attributes
+            .iter()
+            .map(|attr| {
+                let location = attr.location.unwrap_or(next_location);
+                next_location = location + attr.columns as u32;
+                location
+            })
+            .collect()
+    }
+
+    /// Returns every location occupied by the layout, including all columns of matrices.
+    pub fn occupied_locations_from(&self, first_location: u32) -> Vec<u32> {
+        self.locations_from(first_location)
+            .into_iter()
+            .zip(&self.attributes)
+            .flat_map(|(location, attr)| location..location + attr.columns as u32)
+            .collect()
+    }
+
+    /// Returns the first location after all locations occupied by the layout.
+    pub fn next_free_location_from(&self, first_location: u32) -> u32 {
+        self.occupied_locations_from(first_location)
+            .into_iter()
+            .max()
+            .map_or(first_location, |location| location + 1)
+    }
+}
+
+fn apply_attribute(location: u32, attr: &VertexAttribute, offset: usize) {
+    let offset = Some(offset as *const c_void);
+    let components = attr.components as i32;
+    match AttributePointerKind::of(attr) {
+        AttributePointerKind::Float => vertex_attrib_pointer(
+            location,
+            components,
+            attr.data_type,
+            attr.normalized,
+            attr.stride,
+            offset,
+        ),
+        AttributePointerKind::Integer => {
+            vertex_attrib_pointer_i(location, components, attr.data_type, attr.stride, offset)
+        }
+        AttributePointerKind::Double => {
+            vertex_attrib_pointer_l(location, components, attr.data_type, attr.stride, offset)
+        }
+    }
+}
+
+fn format_attribute(vao: u32, location: u32, attr: &VertexAttribute, relative_offset: u32) {
+    let components = attr.components as i32;
+    match AttributePointerKind::of(attr) {
+        AttributePointerKind::Float => vertex_array_attrib_format(
+            vao,
+            location,
+            components,
+            attr.data_type,
+            attr.normalized,
+            relative_offset,
+        ),
+        AttributePointerKind::Integer => {
+            vertex_array_attrib_format_i(vao, location, components, attr.data_type, relative_offset)
+        }
+        AttributePointerKind::Double => {
+            vertex_array_attrib_format_l(vao, location, components, attr.data_type, relative_offset)
+        }
+    }
+}
+
+/// Returns the byte offset of a column of an attribute; `0` is the attribute itself.
+fn column_offset(attr: &VertexAttribute, column: u32) -> usize {
+    attr.offset.unwrap_or(0) as usize + column as usize * attr.column_size()
+}
+
+/// The `glVertexAttrib*Pointer` variant an attribute has to be specified with.
+#[derive(Debug, PartialEq, Eq)]
+enum AttributePointerKind {
+    Float,
+    Integer,
+    Double,
+}
+
+impl AttributePointerKind {
+    fn of(attr: &VertexAttribute) -> Self {
+        match attr.data_type {
+            VertexDataType::Double => AttributePointerKind::Double,
+            VertexDataType::Byte
+            | VertexDataType::UnsignedByte
+            | VertexDataType::Short
+            | VertexDataType::UnsignedShort
+            | VertexDataType::Int
+            | VertexDataType::UnsignedInt if !attr.normalized => AttributePointerKind::Integer,
+            _ => AttributePointerKind::Float,
+        }
+    }
 }
 
 pub type SharedDynamicVertexLayout = Rc<RefCell<DynamicVertexLayout>>;
@@ -148,3 +414,166 @@ impl From<DynamicVertexLayout> for SharedDynamicVertexLayout {
         Rc::new(RefCell::new(layout))
     }
 }
+
+/// A vertex layout spanning several buffer bindings, e.g. per-vertex positions in one buffer
+/// and per-instance transforms in another.
+///
+/// Every binding is a `DynamicVertexLayout` with its own `StepRate`. Automatic locations
+/// continue across bindings, so the attributes of the second binding follow those of the first.
+///
+/// # Example
+/// ```no_run
+/// use diego::glx::vertex_array_object::VertexArrayObject;
+/// use diego::glx::vertex_attribute::VertexAttribute;
+/// use diego::glx::vertex_data_type::VertexDataType;
+/// use diego::glx::vertex_layout::{DynamicVertexLayout, MultiStreamLayout, StepRate};
+/// use diego::vertex::textured_vertex::TexturedVertex;
+///
+/// let mut layout = MultiStreamLayout::default()
+///     .with_binding(DynamicVertexLayout::of::<TexturedVertex>())
+///     .with_binding(
+///         DynamicVertexLayout::from_attributes([VertexAttribute::matrix(4, 4, VertexDataType::Float)])
+///             .with_step_rate(StepRate::PerInstance(1)),
+///     );
+/// layout.finalize_attributes();
+///
+/// // Locations 0-2 read from the vertex buffer, 3-6 from the instance buffer.
+/// let vao = VertexArrayObject::default();
+/// let (vertex_buffer_id, instance_buffer_id) = (1, 2);
+/// layout.attach(&vao, &[vertex_buffer_id, instance_buffer_id]).unwrap();
+/// ```
+#[derive(Default, Debug)]
+pub struct MultiStreamLayout {
+    bindings: Vec<DynamicVertexLayout>,
+}
+
+impl MultiStreamLayout {
+    pub fn with_binding(mut self, layout: DynamicVertexLayout) -> Self {
+        self.bindings.push(layout);
+        self
+    }
+
+    pub fn bindings(&self) -> &[DynamicVertexLayout] {
+        &self.bindings
+    }
+
+    /// Finalizes the attributes of every binding; see `DynamicVertexLayout::finalize_attributes`.
+    pub fn finalize_attributes(&mut self) {
+        self.bindings.iter_mut().for_each(DynamicVertexLayout::finalize_attributes);
+    }
+
+    /// Returns the first automatic location of every binding.
+    pub fn first_locations(&self) -> Vec<u32> {
+        let mut next_location = 0;
+        self.bindings
+            .iter()
+            .map(|binding| {
+                let first_location = next_location;
+                next_location = binding.next_free_location_from(first_location);
+                first_location
+            })
+            .collect()
+    }
+
+    /// Configures the attribute arrays of the currently bound VAO.
+    ///
+    /// # Arguments
+    /// * `buffer_ids` - The array buffer of every binding, in binding order.
+    ///
+    /// The `ARRAY_BUFFER` binding is left at the buffer of the last binding.
+    pub fn apply(&self, buffer_ids: &[u32]) -> Result<(), RuntimeError> {
+        self.check_buffer_count(buffer_ids)?;
+        for ((binding, first_location), &buffer_id) in
+            self.bindings.iter().zip(self.first_locations()).zip(buffer_ids)
+        {
+            bind_buffer(BufferType::ArrayBuffer, buffer_id);
+            binding.apply_from(first_location);
+        }
+        Ok(())
+    }
+
+    /// Configures the attribute arrays of `vao`, using the position of every binding as its
+    /// vertex buffer binding point; see `DynamicVertexLayout::attach`.
+    ///
+    /// # Arguments
+    /// * `buffer_ids` - The array buffer of every binding, in binding order.
+    pub fn attach(&self, vao: &VertexArrayObject, buffer_ids: &[u32]) -> Result<(), RuntimeError> {
+        self.check_buffer_count(buffer_ids)?;
+        for (binding_index, ((binding, first_location), &buffer_id)) in
+            self.bindings.iter().zip(self.first_locations()).zip(buffer_ids).enumerate()
+        {
+            binding.attach(vao, binding_index as u32, buffer_id, first_location);
+        }
+        Ok(())
+    }
+
+    fn check_buffer_count(&self, buffer_ids: &[u32]) -> Result<(), RuntimeError> {
+        if buffer_ids.len() != self.bindings.len() {
+            return Err(RuntimeError::InvalidBufferSize(create_runtime_info!(format!(
+                "Layout has {} buffer bindings, but {} buffers were given",
+                self.bindings.len(),
+                buffer_ids.len()
+            ))));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finalize_attributes() {
+        let mut layout = DynamicVertexLayout::from_attributes([
+            VertexAttribute::new(3, VertexDataType::Float),
+            VertexAttribute::new(4, VertexDataType::UnsignedByte).with_location(Some(5)),
+        ]);
+        assert!(!layout.is_finalized());
+        layout.finalize_attributes();
+        assert!(layout.is_finalized());
+
+        let attributes = layout.get_attributes();
+        assert_eq!(attributes[0].offset, Some(0));
+        assert_eq!(attributes[1].offset, Some(12));
+        assert!(attributes.iter().all(|attr| attr.stride == 16));
+        assert_eq!(layout.locations(), [0, 5]);
+    }
+
+    #[test]
+    fn test_matrix_attribute_locations() {
+        let mut instance = DynamicVertexLayout::from_attributes([
+            VertexAttribute::matrix(4, 4, VertexDataType::Float),
+            VertexAttribute::new(4, VertexDataType::Float),
+        ])
+        .with_step_rate(StepRate::PerInstance(1));
+        instance.finalize_attributes();
+
+        let attributes = instance.get_attributes();
+        assert_eq!(attributes[1].offset, Some(64));
+        assert_eq!(attributes[0].stride, 80);
+        assert_eq!(instance.locations_from(3), [3, 7]);
+        assert_eq!(instance.next_free_location_from(3), 8);
+        assert_eq!(instance.step_rate().divisor(), 1);
+
+        let layout = MultiStreamLayout::default()
+            .with_binding(DynamicVertexLayout::from_attributes([
+                VertexAttribute::new(3, VertexDataType::Float),
+                VertexAttribute::new(2, VertexDataType::Float),
+            ]))
+            .with_binding(instance);
+        assert_eq!(layout.first_locations(), [0, 2]);
+    }
+
+    #[test]
+    fn test_attribute_pointer_kind() {
+        let kind = |attr: VertexAttribute| AttributePointerKind::of(&attr);
+        assert_eq!(kind(VertexAttribute::new(3, VertexDataType::Float)), AttributePointerKind::Float);
+        assert_eq!(kind(VertexAttribute::new(1, VertexDataType::Int)), AttributePointerKind::Integer);
+        assert_eq!(
+            kind(VertexAttribute::new(4, VertexDataType::UnsignedByte).with_normalized(true)),
+            AttributePointerKind::Float
+        );
+        assert_eq!(kind(VertexAttribute::new(2, VertexDataType::Double)), AttributePointerKind::Double);
+    }
+}
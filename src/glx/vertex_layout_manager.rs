@@ -1,43 +1,102 @@
+use crate::gl::buffer::get_bound_buffer;
+use crate::gl::types::BufferType;
+use crate::gl::vao::disable_vertex_attrib_array;
 use crate::glx::vertex_array_object::VertexArrayObject;
 use crate::glx::vertex_attribute::VertexAttribute;
-use crate::glx::vertex_layout::{DynamicVertexLayout, SharedDynamicVertexLayout};
-use std::collections::HashMap;
+use crate::glx::vertex_layout::{DynamicVertexLayout, SharedDynamicVertexLayout, StepRate};
 use crate::glx::Bindable;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Weak;
 
+/// Applies vertex layouts to vertex array objects and remembers which layout each VAO has.
+///
+/// Layouts are shared between VAOs with identical attributes. Activating the layout a VAO
+/// already has with the same `ARRAY_BUFFER` bound only binds the VAO; no attribute state is
+/// specified again. VAOs are tracked by their token, so a recycled GL id never hits a stale
+/// entry; entries of dropped VAOs are pruned whenever a layout is applied.
 #[derive(Default)]
-pub struct VertexLayoutManager<'a> {
-    // We use attributes as key for the layout
-    layouts: HashMap<Vec<VertexAttribute>, SharedDynamicVertexLayout>,
-    // VAO to attribute mapping
-    vao_layouts: HashMap<&'a VertexArrayObject, Vec<VertexAttribute>>,
+pub struct VertexLayoutManager {
+    // Layouts by step rate and finalized attributes
+    layouts: HashMap<StepRate, HashMap<Vec<VertexAttribute>, SharedDynamicVertexLayout>>,
+    // VAO token to the layout it has
+    vao_layouts: HashMap<u64, VaoLayout>,
+}
+
+struct VaoLayout {
+    layout: SharedDynamicVertexLayout,
+    // The ARRAY_BUFFER the layout was applied with
+    buffer: u32,
+    vao: Weak<()>,
 }
 
-impl<'a> VertexLayoutManager<'a> {
-    pub fn activate_layout<I>(&mut self, vao: &'a mut VertexArrayObject, attributes: I)
-    where
-        I: IntoIterator<Item=VertexAttribute>,
+impl VertexLayoutManager {
+    /// Binds the VAO and configures its attribute arrays, including their divisors, for the
+    /// given layout.
+    ///
+    /// The attribute pointers refer to the buffer bound to `ARRAY_BUFFER` at the time the
+    /// layout is applied, so bind the vertex buffer before activating a new layout. Attribute
+    /// arrays of the previous layout that are not used by the new one are disabled.
+    ///
+    /// # Returns
+    /// The layout now assigned to the VAO.
+    pub fn activate_layout(&mut self, vao: &VertexArrayObject, layout: &DynamicVertexLayout)
+        -> anyhow::Result<SharedDynamicVertexLayout>
     {
-        let layout_attributes: Vec<VertexAttribute> = attributes.into_iter().collect();
+        let layout = self.shared_layout(layout);
 
-        // Check whether the layout already exists (based on the attributes)
-        if !self.layouts.contains_key(&layout_attributes) {
-            // Create and add a new layout
-            let dynamic_layout = DynamicVertexLayout::from_attributes(layout_attributes.clone());
-            self.layouts.insert(layout_attributes.clone(), dynamic_layout.into_shared());
-        }
+        vao.bind()?;
 
-        // Get layout via the attributes
-        let mut layout = self.layouts.get_mut(&layout_attributes).unwrap();
+        let buffer = get_bound_buffer(BufferType::ArrayBuffer);
+        let previous = self.vao_layouts.get(&vao.token());
+        if previous.is_some_and(|previous| Rc::ptr_eq(&previous.layout, &layout) && previous.buffer == buffer) {
+            return Ok(layout);
+        }
 
-        // Bind the VAO
-        vao.bind();
+        let new_locations = layout.borrow().occupied_locations_from(0);
+        if let Some(previous) = previous {
+            for location in previous.layout.borrow().occupied_locations_from(0) {
+                if !new_locations.contains(&location) {
+                    disable_vertex_attrib_array(location);
+                }
+            }
+        }
+        layout.borrow().apply();
+        self.vao_layouts.retain(|_, entry| entry.vao.strong_count() > 0);
+        self.vao_layouts.insert(vao.token(), VaoLayout { layout: layout.clone(), buffer, vao: vao.liveness() });
+        Ok(layout)
+    }
 
-        // Check whether the VAO already has a layout that matches the attributes
-        if !self.vao_layouts.contains_key(vao) || self.vao_layouts.get(vao) != Some(&layout_attributes) {
-            // // Assign layout to the VAO
-            // layout.borrow_mut().configure(); //TODO Configuration für das VAO (... UPLOAD?)
-            // // Save layout attribute assignment for VAO
-            // self.vao_layouts.insert(vao, layout_attributes);
+    /// Returns the shared layout with the same finalized attributes and step rate, creating it
+    /// on first use. Finalized layouts are looked up without copying them.
+    fn shared_layout(&mut self, layout: &DynamicVertexLayout) -> SharedDynamicVertexLayout {
+        let layouts = self.layouts.entry(layout.step_rate()).or_default();
+        if layout.is_finalized() {
+            if let Some(shared) = layouts.get(layout.get_attributes()) {
+                return shared.clone();
+            }
         }
+        let mut layout = layout.clone();
+        layout.finalize_attributes();
+        layouts
+            .entry(layout.get_attributes().to_vec())
+            .or_insert_with(|| layout.into_shared())
+            .clone()
+    }
+
+    /// Returns the layout last applied to the VAO.
+    pub fn layout_of(&self, vao: &VertexArrayObject) -> Option<SharedDynamicVertexLayout> {
+        self.vao_layouts.get(&vao.token()).map(|entry| entry.layout.clone())
+    }
+
+    /// Forgets the layout of a VAO. The next activation for it specifies all attributes again.
+    pub fn forget_vao(&mut self, vao: &VertexArrayObject) {
+        self.vao_layouts.remove(&vao.token());
+    }
+
+    /// Forgets all cached layouts and VAO assignments.
+    pub fn clear(&mut self) {
+        self.layouts.clear();
+        self.vao_layouts.clear();
     }
 }
@@ -1,5 +1,7 @@
 #![allow(dead_code)]
 
+// Lets the code generated by `diego-derive` refer to `::diego` inside this crate as well.
+extern crate self as diego;
 extern crate gl as ogl;
 extern crate winit;
 extern crate glutin;
@@ -15,3 +17,4 @@ pub mod vertex;
 pub mod logging;
 pub mod macros;
 pub mod geometry;
+pub mod assets;
@@ -1,27 +1,143 @@
 use std::any::Any;
-use crate::logging::{log_formatter, LogLevel, LogTarget};
-use crate::logging::log_formatter::format_message;
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::sync::OnceLock;
+use crate::logging::{LogLevel, LogTarget};
+use crate::logging::log_layout::{LogLayout, ANSI_RESET};
 use crate::logging::log_manager::{LogError, LogMessage};
 
+/// When a `ConsoleTarget` writes ANSI colors.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Colors if the stream is a terminal and the `NO_COLOR` environment variable is not set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Writes log messages to stdout, and messages of `stderr_level` and above to stderr.
+///
+/// Template layouts are colored by level: the whole line, or with `highlight_segments` only
+/// the `${level}` token and, dimmed, the `${module}` and `${logger}` tokens. JSON and logfmt
+/// lines are never colored.
+///
+/// # Example
+/// ```no_run
+/// use diego::core::diego_runtime::add_logger;
+/// use diego::logging::console_logger::ConsoleTarget;
+///
+/// add_logger("console", ConsoleTarget::default().with_highlight_segments(true)).unwrap();
+/// ```
 #[derive(Debug)]
 pub struct ConsoleTarget {
     pub level: LogLevel,
-    pub format: String,
+    pub layout: LogLayout,
+    pub color: ColorMode,
+    /// The least severe level written to stderr; `None` writes everything to stdout.
+    pub stderr_level: Option<LogLevel>,
+    pub highlight_segments: bool,
 }
 
 impl Default for ConsoleTarget {
     fn default() -> Self {
         Self {
             level: LogLevel::Info,
-            format: log_formatter::DEFAULT_FORMAT.to_string(),
+            layout: LogLayout::default(),
+            color: ColorMode::Auto,
+            stderr_level: Some(LogLevel::Warn),
+            highlight_segments: false,
+        }
+    }
+}
+
+impl ConsoleTarget {
+    pub fn with_level(mut self, level: LogLevel) -> Self {
+        self.level = level;
+        self
+    }
+
+    pub fn with_layout<T: Into<LogLayout>>(mut self, layout: T) -> Self {
+        self.layout = layout.into();
+        self
+    }
+
+    pub fn with_color(mut self, color: ColorMode) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_stderr_level(mut self, stderr_level: Option<LogLevel>) -> Self {
+        self.stderr_level = stderr_level;
+        self
+    }
+
+    pub fn with_highlight_segments(mut self, highlight_segments: bool) -> Self {
+        self.highlight_segments = highlight_segments;
+        self
+    }
+
+    fn is_stderr(&self, level: LogLevel) -> bool {
+        self.stderr_level.is_some_and(|stderr_level| level >= stderr_level)
+    }
+
+    fn use_color(&self, stderr: bool) -> bool {
+        match self.color {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => {
+                static NO_COLOR: OnceLock<bool> = OnceLock::new();
+                static STDOUT_IS_TERMINAL: OnceLock<bool> = OnceLock::new();
+                static STDERR_IS_TERMINAL: OnceLock<bool> = OnceLock::new();
+
+                // See https://no-color.org: set and not empty.
+                let no_color = *NO_COLOR.get_or_init(|| env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()));
+                let is_terminal = if stderr {
+                    *STDERR_IS_TERMINAL.get_or_init(|| io::stderr().is_terminal())
+                } else {
+                    *STDOUT_IS_TERMINAL.get_or_init(|| io::stdout().is_terminal())
+                };
+                !no_color && is_terminal
+            }
+        }
+    }
+
+    fn format_line(&self, log_message: &LogMessage, color: bool) -> String {
+        match &self.layout {
+            LogLayout::Template(layout) if color => {
+                let level_style = level_style(log_message.level);
+                if self.highlight_segments {
+                    layout.render_highlighted(log_message, level_style, MODULE_STYLE)
+                } else {
+                    format!("{}{}{}", level_style, layout.render(log_message), ANSI_RESET)
+                }
+            }
+            layout => layout.render(log_message),
         }
     }
 }
 
+const MODULE_STYLE: &str = "\x1b[2m";
+
+fn level_style(level: LogLevel) -> &'static str {
+    match level {
+        LogLevel::Trace => "\x1b[90m",
+        LogLevel::Debug => "\x1b[36m",
+        LogLevel::Info => "\x1b[32m",
+        LogLevel::Warn => "\x1b[33m",
+        LogLevel::Error => "\x1b[31m",
+        LogLevel::Fatal => "\x1b[1;31m",
+    }
+}
+
 impl LogTarget for ConsoleTarget {
     fn update(&mut self, log_target: Box<dyn LogTarget>) -> bool {
         if let Some(target) = log_target.as_any().downcast_ref::<Self>() {
             self.level = target.level;
+            self.layout = target.layout.clone();
+            self.color = target.color;
+            self.stderr_level = target.stderr_level;
+            self.highlight_segments = target.highlight_segments;
             return true;
         }
         false
@@ -40,9 +156,45 @@ impl LogTarget for ConsoleTarget {
     }
 
     fn log(&self, log_message: &LogMessage) -> Result<(), LogError> {
-        if self.is_logging_enabled(log_message.level) {
-            println!("{}", format_message(&self.format, log_message));
-        }
+        let stderr = self.is_stderr(log_message.level);
+        let line = self.format_line(log_message, self.use_color(stderr));
+        // A closed stdout, e.g. a pipe to `head`, must not bring down the application.
+        let _ = if stderr {
+            writeln!(io::stderr().lock(), "{}", line)
+        } else {
+            writeln!(io::stdout().lock(), "{}", line)
+        };
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logging::log_layout::TemplateLayout;
+
+    #[test]
+    fn test_colors_and_highlighting() {
+        let message = LogMessage::new(LogLevel::Warn, "low memory")
+            .with_location(Some("game".to_string()), None, None);
+        let target = ConsoleTarget::default()
+            .with_layout(TemplateLayout::parse("[${level}] ${module}: ${message}").unwrap());
+
+        assert_eq!(target.format_line(&message, false), "[WARN] game: low memory");
+        assert_eq!(target.format_line(&message, true), "\x1b[33m[WARN] game: low memory\x1b[0m");
+
+        let target = target.with_highlight_segments(true);
+        assert_eq!(target.format_line(&message, true), "[\x1b[33mWARN\x1b[0m] \x1b[2mgame\x1b[0m: low memory");
+        assert!(!ConsoleTarget::default().with_layout(LogLayout::Json).format_line(&message, true).contains('\x1b'));
+    }
+
+    #[test]
+    fn test_stream_selection() {
+        let target = ConsoleTarget::default().with_color(ColorMode::Never);
+        assert!(!target.is_stderr(LogLevel::Info));
+        assert!(target.is_stderr(LogLevel::Warn));
+        assert!(target.is_stderr(LogLevel::Fatal));
+        assert!(!target.use_color(true));
+        assert!(!target.with_stderr_level(None).is_stderr(LogLevel::Fatal));
+    }
+}
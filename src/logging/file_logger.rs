@@ -1,16 +1,194 @@
+use crate::logging::log_layout::LogLayout;
+use crate::logging::log_manager::{LogError, LogMessage};
 use crate::logging::{LogLevel, LogTarget};
+use chrono::{Local, NaiveDate};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use regex::Regex;
 use std::any::Any;
-use crate::logging::log_manager::{LogError, LogMessage};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const SHORTDATE: &str = "${shortdate}";
+const DEFAULT_MAX_FILES: usize = 7;
+const DEFAULT_BUFFER_SIZE: usize = 8 * 1024;
+/// How long writing is suspended after an I/O error, e.g. a full disk.
+const RETRY_INTERVAL: Duration = Duration::from_secs(5);
+/// How often the target checks whether the log file was deleted underneath it.
+const EXISTENCE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
 
+/// Writes log messages to a file.
+///
+/// Messages are appended through a buffer that is flushed after every batch of the log queue
+/// and right away for errors. Missing directories are created.
+///
+/// # Rotation
+/// * By date: if `target_file` contains `${shortdate}`, e.g. `logs/game-${shortdate}.log`, a
+///   new file is started when the date changes.
+/// * By size: with a `max_file_size`, the file is renamed to `<file>.1` once it would exceed
+///   the size; older archives are shifted to `<file>.2` and so on.
+///
+/// Only the `max_files` most recent rotated files are kept next to the active one. Rotated
+/// files are gzipped (`.gz`) if `compress` is set.
+///
+/// # Errors
+/// If writing fails, e.g. because the disk is full, the message is dropped and writing is
+/// suspended for a few seconds. The number of dropped messages is written to the file once it
+/// works again. If the file is deleted while it is open, a new one is created.
+///
+/// # Example
+/// ```no_run
+/// use diego::core::diego_runtime::add_logger;
+/// use diego::logging::file_logger::FileTarget;
+///
+/// let target = FileTarget::new("logs/diego-${shortdate}.log")
+///     .with_max_file_size(10 * 1024 * 1024)
+///     .with_max_files(5)
+///     .with_compress(true);
+/// add_logger("file", target).unwrap();
+/// ```
 pub struct FileTarget {
     pub level: LogLevel,
+    /// The path of the log file; may contain `${shortdate}`.
     pub target_file: String,
-    pub format: String,
+    pub layout: LogLayout,
+    /// The size in bytes at which the file is rotated; `None` disables size based rotation.
+    pub max_file_size: Option<u64>,
+    /// The number of rotated files that are kept.
+    pub max_files: usize,
+    /// Whether rotated files are gzipped.
+    pub compress: bool,
+    /// The capacity of the write buffer in bytes.
+    pub buffer_size: usize,
+    writer: Mutex<FileWriter>,
+}
+
+impl FileTarget {
+    pub fn new<T: Into<String>>(target_file: T) -> Self {
+        Self {
+            level: LogLevel::Info,
+            target_file: target_file.into(),
+            layout: LogLayout::default(),
+            max_file_size: None,
+            max_files: DEFAULT_MAX_FILES,
+            compress: false,
+            buffer_size: DEFAULT_BUFFER_SIZE,
+            writer: Mutex::new(FileWriter::default()),
+        }
+    }
+
+    pub fn with_level(mut self, level: LogLevel) -> Self {
+        self.level = level;
+        self
+    }
+
+    pub fn with_layout<T: Into<LogLayout>>(mut self, layout: T) -> Self {
+        self.layout = layout.into();
+        self
+    }
+
+    pub fn with_max_file_size(mut self, max_file_size: u64) -> Self {
+        self.max_file_size = Some(max_file_size);
+        self
+    }
+
+    pub fn with_max_files(mut self, max_files: usize) -> Self {
+        self.max_files = max_files;
+        self
+    }
+
+    pub fn with_compress(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    pub fn with_buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = buffer_size;
+        self
+    }
+
+    /// Returns the path of the file messages are written to on `date`.
+    pub fn file_path(&self, date: NaiveDate) -> PathBuf {
+        PathBuf::from(self.target_file.replace(SHORTDATE, &date.format("%Y-%m-%d").to_string()))
+    }
+
+    fn write_line(&self, line: &str, level: LogLevel, today: NaiveDate) -> Result<(), LogError> {
+        let mut writer = self.writer.lock().unwrap();
+        writer.write_line(self, line, level, today)
+    }
+
+    /// Renames the file to `<file>.1`, shifting older archives and deleting the oldest one.
+    fn rotate_by_size(&self, path: &Path) -> io::Result<()> {
+        for index in (1..=self.max_files).rev() {
+            for archive in [archive_path(path, index, true), archive_path(path, index, false)] {
+                if !archive.exists() {
+                    continue;
+                }
+                if index == self.max_files {
+                    fs::remove_file(&archive)?;
+                } else {
+                    let gzipped = archive.extension().is_some_and(|extension| extension == "gz");
+                    fs::rename(&archive, archive_path(path, index + 1, gzipped))?;
+                }
+            }
+        }
+
+        if self.max_files == 0 {
+            return fs::remove_file(path);
+        }
+        let archive = archive_path(path, 1, false);
+        fs::rename(path, &archive)?;
+        if self.compress {
+            gzip(&archive)?;
+        }
+        Ok(())
+    }
+
+    /// Deletes all but the `max_files` most recent rotated files.
+    fn remove_old_files(&self, active: &Path) -> io::Result<()> {
+        let directory = match active.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        let pattern = rotated_file_pattern(&self.target_file);
+        let mut rotated = Vec::new();
+        for entry in fs::read_dir(directory)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if entry.path() != active && pattern.is_match(&name) {
+                rotated.push((entry.metadata()?.modified()?, entry.path()));
+            }
+        }
+
+        // Newest first
+        rotated.sort_by(|a, b| b.cmp(a));
+        for (_, path) in rotated.into_iter().skip(self.max_files) {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
 }
 
 impl LogTarget for FileTarget {
     fn update(&mut self, log_target: Box<dyn LogTarget>) -> bool {
-        todo!()
+        if let Some(target) = log_target.as_any().downcast_ref::<Self>() {
+            self.level = target.level;
+            self.layout = target.layout.clone();
+            self.max_file_size = target.max_file_size;
+            self.max_files = target.max_files;
+            self.compress = target.compress;
+            if self.target_file != target.target_file || self.buffer_size != target.buffer_size {
+                self.target_file = target.target_file.clone();
+                self.buffer_size = target.buffer_size;
+                // The next message opens the new file.
+                let _ = self.writer.get_mut().unwrap().close();
+            }
+            return true;
+        }
+        false
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -26,6 +204,267 @@ impl LogTarget for FileTarget {
     }
 
     fn log(&self, log_message: &LogMessage) -> Result<(), LogError> {
-        todo!()
+        let line = self.layout.render(log_message);
+        self.write_line(&line, log_message.level, Local::now().date_naive())
+    }
+
+    fn flush(&self) -> Result<(), LogError> {
+        self.writer.lock().unwrap().flush()
+    }
+}
+
+impl Drop for FileTarget {
+    fn drop(&mut self) {
+        let _ = self.writer.get_mut().unwrap().close();
+    }
+}
+
+#[derive(Default)]
+struct FileWriter {
+    file: Option<BufWriter<File>>,
+    path: PathBuf,
+    /// The size of the file including the buffered bytes.
+    size: u64,
+    buffered_lines: u64,
+    last_existence_check: Option<Instant>,
+    failed_at: Option<Instant>,
+    dropped_lines: u64,
+}
+
+impl FileWriter {
+    fn write_line(&mut self, target: &FileTarget, line: &str, level: LogLevel, today: NaiveDate)
+        -> Result<(), LogError>
+    {
+        if self.failed_at.is_some_and(|failed_at| failed_at.elapsed() < RETRY_INTERVAL) {
+            self.dropped_lines += 1;
+            return Ok(());
+        }
+        self.failed_at = None;
+
+        let path = target.file_path(today);
+        let dropped_lines = self.dropped_lines;
+        let result = self.prepare(target, &path, line.len() as u64 + 1).and_then(|file| {
+            if dropped_lines > 0 {
+                writeln!(file, "[{} log messages were dropped due to write errors]", dropped_lines)?;
+            }
+            writeln!(file, "{}", line)?;
+            if level >= LogLevel::Error {
+                file.flush()?;
+            }
+            Ok(())
+        });
+        match result {
+            Ok(()) => {
+                self.dropped_lines = 0;
+                self.size += line.len() as u64 + 1;
+                self.buffered_lines += 1;
+                Ok(())
+            }
+            Err(e) => {
+                self.dropped_lines += 1;
+                Err(self.fail(e))
+            }
+        }
+    }
+
+    /// Rotates the file if necessary and returns it, opened for appending.
+    fn prepare(&mut self, target: &FileTarget, path: &Path, line_size: u64) -> io::Result<&mut BufWriter<File>> {
+        if self.file.is_some() && self.path != path {
+            // The date changed.
+            let previous = self.path.clone();
+            self.close()?;
+            if target.compress && previous.exists() {
+                gzip(&previous)?;
+            }
+            target.remove_old_files(path)?;
+        } else if self.file.is_some() && self.was_deleted() {
+            self.file = None;
+        }
+
+        let exceeds_size = target.max_file_size.is_some_and(|max_size| self.size > 0 && self.size + line_size > max_size);
+        if exceeds_size {
+            self.close()?;
+            target.rotate_by_size(path)?;
+            target.remove_old_files(path)?;
+            self.size = 0;
+        }
+
+        if self.file.is_none() {
+            if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+                fs::create_dir_all(parent)?;
+            }
+            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            self.size = file.metadata()?.len();
+            self.path = path.to_path_buf();
+            self.file = Some(BufWriter::with_capacity(target.buffer_size, file));
+            self.last_existence_check = Some(Instant::now());
+
+            // A size limit below the size of the existing file rotates it right away.
+            if target.max_file_size.is_some_and(|max_size| self.size > 0 && self.size + line_size > max_size) {
+                return self.prepare(target, path, line_size);
+            }
+        }
+        Ok(self.file.as_mut().unwrap())
+    }
+
+    /// Returns whether the open file was deleted or renamed. Checked at most once a second.
+    fn was_deleted(&mut self) -> bool {
+        let now = Instant::now();
+        if self.last_existence_check.is_some_and(|checked| now - checked < EXISTENCE_CHECK_INTERVAL) {
+            return false;
+        }
+        self.last_existence_check = Some(now);
+        !self.path.exists()
+    }
+
+    fn flush(&mut self) -> Result<(), LogError> {
+        let Some(file) = self.file.as_mut() else {
+            return Ok(());
+        };
+        match file.flush() {
+            Ok(()) => {
+                self.buffered_lines = 0;
+                Ok(())
+            }
+            Err(e) => {
+                self.dropped_lines += self.buffered_lines;
+                Err(self.fail(e))
+            }
+        }
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        let result = match self.file.take() {
+            Some(mut file) => file.flush(),
+            None => Ok(()),
+        };
+        self.buffered_lines = 0;
+        result
+    }
+
+    /// Drops the file and suspends writing for `RETRY_INTERVAL`.
+    fn fail(&mut self, source: io::Error) -> LogError {
+        if let Some(file) = self.file.take() {
+            // Discard the buffer; flushing it again would fail the same way.
+            let _ = file.into_parts();
+        }
+        self.buffered_lines = 0;
+        self.failed_at = Some(Instant::now());
+        LogError::FileWriteFailed { path: self.path.clone(), source }
+    }
+}
+
+fn archive_path(path: &Path, index: usize, gzipped: bool) -> PathBuf {
+    let mut archive = path.as_os_str().to_owned();
+    archive.push(format!(".{}", index));
+    if gzipped {
+        archive.push(".gz");
+    }
+    PathBuf::from(archive)
+}
+
+/// Compresses the file to `<file>.gz` and deletes it.
+fn gzip(path: &Path) -> io::Result<()> {
+    let mut gzipped = path.as_os_str().to_owned();
+    gzipped.push(".gz");
+    let mut encoder = GzEncoder::new(File::create(PathBuf::from(gzipped))?, Compression::default());
+    io::copy(&mut File::open(path)?, &mut encoder)?;
+    encoder.finish()?;
+    fs::remove_file(path)
+}
+
+/// Matches the file names of rotated files: those of other dates and the numbered archives.
+fn rotated_file_pattern(target_file: &str) -> Regex {
+    let file_name = Path::new(target_file)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let pattern = regex::escape(&file_name).replace(&regex::escape(SHORTDATE), r"\d{4}-\d{2}-\d{2}");
+    Regex::new(&format!(r"^{}(\.\d+)?(\.gz)?$", pattern)).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("diego_file_target_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 5, day).unwrap()
+    }
+
+    #[test]
+    fn test_creates_directories_and_appends() {
+        let dir = test_dir("append");
+        let path = dir.join("nested").join("app.log");
+        let target = FileTarget::new(path.to_string_lossy());
+        target.write_line("first", LogLevel::Info, date(1)).unwrap();
+        target.write_line("second", LogLevel::Info, date(1)).unwrap();
+        target.flush().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first\nsecond\n");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_rotates_by_size_and_keeps_max_files() {
+        let dir = test_dir("size");
+        let path = dir.join("app.log");
+        let target = FileTarget::new(path.to_string_lossy()).with_max_file_size(10).with_max_files(2);
+        for line in ["line 1", "line 2", "line 3", "line 4"] {
+            target.write_line(line, LogLevel::Info, date(1)).unwrap();
+        }
+        target.flush().unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "line 4\n");
+        assert_eq!(fs::read_to_string(archive_path(&path, 1, false)).unwrap(), "line 3\n");
+        assert_eq!(fs::read_to_string(archive_path(&path, 2, false)).unwrap(), "line 2\n");
+        assert!(!archive_path(&path, 3, false).exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_rotates_by_date_with_compression() {
+        let dir = test_dir("date");
+        let pattern = dir.join("app-${shortdate}.log");
+        let target = FileTarget::new(pattern.to_string_lossy()).with_compress(true).with_max_files(1);
+        target.write_line("day 1", LogLevel::Info, date(1)).unwrap();
+        target.write_line("day 2", LogLevel::Info, date(2)).unwrap();
+        target.write_line("day 3", LogLevel::Info, date(3)).unwrap();
+        target.flush().unwrap();
+
+        assert_eq!(fs::read_to_string(dir.join("app-2024-05-03.log")).unwrap(), "day 3\n");
+        assert!(dir.join("app-2024-05-02.log.gz").exists());
+        assert!(!dir.join("app-2024-05-02.log").exists());
+        assert!(!dir.join("app-2024-05-01.log.gz").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_recreates_deleted_file() {
+        let dir = test_dir("deleted");
+        let path = dir.join("app.log");
+        let target = FileTarget::new(path.to_string_lossy());
+        target.write_line("before", LogLevel::Info, date(1)).unwrap();
+        target.flush().unwrap();
+        fs::remove_file(&path).unwrap();
+
+        target.writer.lock().unwrap().last_existence_check = None;
+        target.write_line("after", LogLevel::Info, date(1)).unwrap();
+        target.flush().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "after\n");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_rotated_file_pattern() {
+        let pattern = rotated_file_pattern("logs/app-${shortdate}.log");
+        assert!(pattern.is_match("app-2024-05-01.log"));
+        assert!(pattern.is_match("app-2024-05-01.log.3.gz"));
+        assert!(!pattern.is_match("app-2024-05-01.log.bak"));
+        assert!(!pattern.is_match("other-2024-05-01.log"));
     }
 }
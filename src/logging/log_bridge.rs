@@ -0,0 +1,200 @@
+use crate::core::diego_runtime::push_log_message;
+use crate::logging::log_manager::{LogError, LogMessage};
+use crate::logging::{LogLevel, LogTarget};
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+use std::any::Any;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static DIEGO_LOGGER: DiegoLogger = DiegoLogger;
+static BRIDGE_INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// A `log::Log` implementation that forwards the records of the `log` crate, e.g. from
+/// glutin and winit, into the diego log targets. The target, module, file and line of the
+/// records are kept.
+///
+/// Install it with `init_log_bridge`.
+pub struct DiegoLogger;
+
+impl Log for DiegoLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            push_log_message(to_log_message(record));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Installs the `DiegoLogger` as the logger of the `log` crate.
+///
+/// # Arguments
+/// * `level` - The least severe level forwarded; more verbose records are discarded by the
+///   `log` macros without being formatted.
+///
+/// # Errors
+/// If another logger, e.g. env_logger, was installed before. Use `LogCrateTarget` to send
+/// diego's messages to that logger instead.
+///
+/// # Example
+/// ```no_run
+/// use diego::core::diego_runtime::add_logger;
+/// use diego::logging::console_logger::ConsoleTarget;
+/// use diego::logging::log_bridge::init_log_bridge;
+/// use diego::logging::LogLevel;
+///
+/// add_logger("console", ConsoleTarget::default()).unwrap();
+/// init_log_bridge(LogLevel::Info).unwrap();
+/// log::info!("Also written by the console target");
+/// ```
+pub fn init_log_bridge(level: LogLevel) -> Result<(), SetLoggerError> {
+    log::set_logger(&DIEGO_LOGGER)?;
+    log::set_max_level(level.into());
+    BRIDGE_INSTALLED.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// A log target that sends diego's messages to the logger of the `log` crate, so they end
+/// up wherever the application's other messages go, e.g. env_logger or tracing.
+///
+/// The logger, module, file and line of the messages are passed on; messages without a
+/// module use the target `diego`. Messages that came from the `log` crate are not sent back. If the
+/// `DiegoLogger` itself is installed, the target does nothing.
+///
+/// # Example
+/// ```no_run
+/// use diego::core::diego_runtime::add_logger;
+/// use diego::logging::log_bridge::LogCrateTarget;
+///
+/// // after the application installed its own logger, e.g. env_logger::init()
+/// add_logger("log", LogCrateTarget::default()).unwrap();
+/// ```
+#[derive(Debug, Default)]
+pub struct LogCrateTarget {
+    pub level: LogLevel,
+}
+
+impl LogTarget for LogCrateTarget {
+    fn update(&mut self, log_target: Box<dyn LogTarget>) -> bool {
+        if let Some(target) = log_target.as_any().downcast_ref::<Self>() {
+            self.level = target.level;
+            return true;
+        }
+        false
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn target_name(&self) -> &'static str {
+        "LogCrateTarget"
+    }
+
+    fn is_logging_enabled(&self, level: LogLevel) -> bool {
+        level >= self.level
+    }
+
+    fn log(&self, log_message: &LogMessage) -> Result<(), LogError> {
+        // Sending the message to the DiegoLogger would queue it again.
+        if log_message.from_log_crate || BRIDGE_INSTALLED.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        let module = log_message.module.as_deref();
+        let target = log_message.logger.as_deref().or(module);
+        log::logger().log(
+            &Record::builder()
+                .args(format_args!("{}", log_message.message))
+                .level(log_message.level.into())
+                .target(target.unwrap_or("diego"))
+                .module_path(module)
+                .file(log_message.file.as_deref())
+                .line(log_message.line)
+                .build(),
+        );
+        Ok(())
+    }
+
+    fn flush(&self) -> Result<(), LogError> {
+        log::logger().flush();
+        Ok(())
+    }
+}
+
+fn to_log_message(record: &Record) -> LogMessage {
+    let mut log_message = LogMessage::new(record.level().into(), record.args().to_string()).with_location(
+        Some(record.module_path().unwrap_or(record.target()).to_string()),
+        record.file().map(str::to_string),
+        record.line(),
+    );
+    log_message.logger = Some(record.target().to_string());
+    log_message.from_log_crate = true;
+    log_message
+}
+
+impl From<Level> for LogLevel {
+    fn from(value: Level) -> Self {
+        match value {
+            Level::Error => LogLevel::Error,
+            Level::Warn => LogLevel::Warn,
+            Level::Info => LogLevel::Info,
+            Level::Debug => LogLevel::Debug,
+            Level::Trace => LogLevel::Trace,
+        }
+    }
+}
+
+/// `log` has no fatal level; fatal messages are sent as errors.
+impl From<LogLevel> for Level {
+    fn from(value: LogLevel) -> Self {
+        match value {
+            LogLevel::Trace => Level::Trace,
+            LogLevel::Debug => Level::Debug,
+            LogLevel::Info => Level::Info,
+            LogLevel::Warn => Level::Warn,
+            LogLevel::Error | LogLevel::Fatal => Level::Error,
+        }
+    }
+}
+
+impl From<LogLevel> for LevelFilter {
+    fn from(value: LogLevel) -> Self {
+        Level::from(value).to_level_filter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_keeps_location() {
+        let log_message = to_log_message(
+            &Record::builder()
+                .args(format_args!("surface lost"))
+                .level(Level::Warn)
+                .target("winit::platform")
+                .file(Some("src/platform.rs"))
+                .line(Some(42))
+                .build(),
+        );
+        assert!(log_message.level == LogLevel::Warn);
+        assert_eq!(log_message.message, "surface lost");
+        assert_eq!(log_message.module.as_deref(), Some("winit::platform"));
+        assert_eq!(log_message.logger.as_deref(), Some("winit::platform"));
+        assert_eq!(log_message.file.as_deref(), Some("src/platform.rs"));
+        assert_eq!(log_message.line, Some(42));
+        assert!(log_message.from_log_crate);
+    }
+
+    #[test]
+    fn test_level_conversion() {
+        assert_eq!(Level::from(LogLevel::Fatal), Level::Error);
+        assert!(LogLevel::from(Level::Trace) == LogLevel::Trace);
+        assert_eq!(LevelFilter::from(LogLevel::Info), LevelFilter::Info);
+    }
+}
@@ -0,0 +1,207 @@
+use crate::logging::log_manager::{LogError, LogMessage};
+use crate::logging::LogLevel;
+use crate::log_warn;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// The environment variable read by `diego_runtime::load_log_filter_from_env`.
+pub const LOG_FILTER_ENV_VAR: &str = "DIEGO_LOG";
+
+const LEVEL_NAMES: [&str; 7] = ["trace", "debug", "info", "warn", "error", "fatal", "off"];
+
+#[derive(Debug, Clone, PartialEq)]
+struct FilterRule {
+    name: String,
+    /// `None` turns logging off.
+    level: Option<LogLevel>,
+}
+
+/// Log levels by module prefix and logger name, e.g. `diego::gl=trace,diego::logging=warn,info`.
+///
+/// Each comma separated rule is `name=level`, a bare `level` for all other messages, or a bare
+/// `name` for everything of that name; a bare name close to a level, like `wran`, is logged as a
+/// likely typo. A rule for `diego::gl` matches the module `diego::gl` and its submodules, and
+/// the logger (the `log` target) of the same name. If several rules match, the longest name
+/// wins. Levels are case-insensitive; `off` discards the messages.
+/// Messages no rule matches are discarded, like with env_logger.
+///
+/// # Example
+/// ```
+/// use diego::logging::log_filter::LogFilter;
+/// use diego::logging::LogLevel;
+///
+/// let filter: LogFilter = "diego::gl=trace,winit=off,info".parse().unwrap();
+/// assert_eq!(filter.level_for(Some("diego::gl::texture"), None), Some(LogLevel::Trace));
+/// assert_eq!(filter.level_for(Some("game"), None), Some(LogLevel::Info));
+/// assert_eq!(filter.level_for(None, Some("winit::window")), None);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LogFilter {
+    default_level: Option<LogLevel>,
+    rules: Vec<FilterRule>,
+}
+
+impl LogFilter {
+    /// Returns a filter that passes all messages of at least `level`.
+    pub fn new(level: LogLevel) -> Self {
+        Self { default_level: Some(level), rules: Vec::new() }
+    }
+
+    pub fn parse(filter: &str) -> Result<Self, LogError> {
+        let mut result = Self::default();
+        for rule in filter.split(',').map(str::trim).filter(|rule| !rule.is_empty()) {
+            match rule.split_once('=') {
+                Some((name, level)) => {
+                    let name = name.trim();
+                    if name.is_empty() {
+                        return Err(invalid_filter(filter, &format!("`{}` has no name", rule)));
+                    }
+                    result = result.with_rule(name, parse_level(filter, level.trim())?);
+                }
+                None => match parse_level(filter, rule) {
+                    Ok(level) => result.default_level = level,
+                    Err(_) => {
+                        if let Some(level) = similar_level_name(rule) {
+                            log_warn!("Log filter `{}`: treating `{}` as a module name, did you mean `{}`?",
+                                filter, rule, level);
+                        }
+                        result = result.with_rule(rule, Some(LogLevel::Trace));
+                    }
+                },
+            }
+        }
+        Ok(result)
+    }
+
+    /// Adds a rule; `None` turns logging for the name off. A rule with the same name is
+    /// replaced.
+    pub fn with_rule<T: Into<String>>(mut self, name: T, level: Option<LogLevel>) -> Self {
+        let name = name.into();
+        self.rules.retain(|rule| rule.name != name);
+        self.rules.push(FilterRule { name, level });
+        self
+    }
+
+    /// Returns the least severe level passed for the module and logger, or `None` if
+    /// messages from there are discarded.
+    pub fn level_for(&self, module: Option<&str>, logger: Option<&str>) -> Option<LogLevel> {
+        self.rules.iter()
+            .filter(|rule| [module, logger].into_iter().flatten().any(|path| matches_path(&rule.name, path)))
+            .max_by_key(|rule| rule.name.len())
+            .map_or(self.default_level, |rule| rule.level)
+    }
+
+    pub fn is_enabled(&self, log_message: &LogMessage) -> bool {
+        self.level_for(log_message.module.as_deref(), log_message.logger.as_deref())
+            .is_some_and(|level| log_message.level >= level)
+    }
+}
+
+impl FromStr for LogFilter {
+    type Err = LogError;
+
+    fn from_str(filter: &str) -> Result<Self, Self::Err> {
+        Self::parse(filter)
+    }
+}
+
+/// Whether `path` is `name` or below it, e.g. `diego::gl::texture` for `diego::gl`.
+pub(crate) fn matches_path(name: &str, path: &str) -> bool {
+    path.strip_prefix(name).is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+}
+
+fn parse_level(filter: &str, level: &str) -> Result<Option<LogLevel>, LogError> {
+    if level.eq_ignore_ascii_case("off") {
+        return Ok(None);
+    }
+    level.parse().map(Some).map_err(|_| invalid_filter(filter, &format!("unknown level `{}`", level)))
+}
+
+/// Returns the level name `word` is probably a typo of, if any.
+fn similar_level_name(word: &str) -> Option<&'static str> {
+    let word = word.to_ascii_lowercase();
+    LEVEL_NAMES.into_iter().find(|name| edit_distance(&word, name) <= 2)
+}
+
+/// The Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &b_char) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(a_char != b_char);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+fn invalid_filter(filter: &str, reason: &str) -> LogError {
+    LogError::InvalidFilter(filter.to_string(), reason.to_string())
+}
+
+/// The filters the log worker applies: per logger name, falling back to the global filter.
+/// Loggers without either are filtered by `LogTarget::is_logging_enabled`.
+#[derive(Debug, Default)]
+pub(crate) struct LogFilters {
+    pub global: Option<LogFilter>,
+    /// Kept by name, so a filter also applies to a logger that is added later.
+    pub loggers: HashMap<String, LogFilter>,
+}
+
+impl LogFilters {
+    pub fn get(&self, logger_name: &str) -> Option<&LogFilter> {
+        self.loggers.get(logger_name).or(self.global.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_longest_matching_rule_wins() {
+        let filter = LogFilter::parse("diego=info, diego::gl=trace, diego::gl::shader=off, warn").unwrap();
+        assert_eq!(filter.level_for(Some("diego::assets"), None), Some(LogLevel::Info));
+        assert_eq!(filter.level_for(Some("diego::gl::texture"), None), Some(LogLevel::Trace));
+        assert_eq!(filter.level_for(Some("diego::gl::shader"), None), None);
+        assert_eq!(filter.level_for(Some("diego_extra"), None), Some(LogLevel::Warn));
+        assert_eq!(filter.level_for(None, None), Some(LogLevel::Warn));
+
+        let bare_name = LogFilter::parse("game").unwrap();
+        assert_eq!(bare_name.level_for(Some("game::ai"), None), Some(LogLevel::Trace));
+        assert_eq!(bare_name.level_for(Some("diego"), None), None);
+    }
+
+    #[test]
+    fn test_is_enabled_uses_module_and_logger() {
+        let filter = LogFilter::parse("wgpu=error,debug").unwrap();
+        let message = LogMessage::new(LogLevel::Warn, "from the log crate");
+        let mut from_log_crate = LogMessage::new(LogLevel::Warn, "from the log crate");
+        from_log_crate.logger = Some("wgpu".to_string());
+        assert!(filter.is_enabled(&message));
+        assert!(!filter.is_enabled(&from_log_crate));
+        assert!(!filter.is_enabled(&LogMessage::new(LogLevel::Trace, "too verbose")));
+    }
+
+    #[test]
+    fn test_invalid_filters() {
+        assert!(LogFilter::parse("diego=loud").is_err());
+        assert!(LogFilter::parse("=info").is_err());
+        assert_eq!(LogFilter::parse("").unwrap(), LogFilter::default());
+    }
+
+    #[test]
+    fn test_level_typos_are_detected() {
+        assert_eq!(similar_level_name("wran"), Some("warn"));
+        assert_eq!(similar_level_name("DEBGU"), Some("debug"));
+        assert_eq!(similar_level_name("mygame"), None);
+        assert_eq!(similar_level_name("diego"), None);
+        // A typo is still a name rule, like with env_logger.
+        let filter = LogFilter::parse("wran").unwrap();
+        assert_eq!(filter.level_for(Some("wran"), None), Some(LogLevel::Trace));
+    }
+}
@@ -0,0 +1,573 @@
+use crate::logging::log_manager::{LogError, LogMessage};
+use chrono::format::{Item, StrftimeItems};
+use chrono::SecondsFormat;
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+use std::fmt::Write;
+use std::str::FromStr;
+
+pub const DEFAULT_FORMAT: &str = "${longdate} [${level}] ${message}";
+
+pub(crate) const ANSI_RESET: &str = "\x1b[0m";
+
+const LONGDATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f";
+const SHORTDATE_FORMAT: &str = "%Y-%m-%d";
+const TIME_FORMAT: &str = "%H:%M:%S%.3f";
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// The timestamp in a chrono format; `${longdate}`, `${shortdate}` and `${time}` are
+    /// dates with fixed formats.
+    Date(String),
+    Ticks,
+    Level,
+    Message,
+    Logger,
+    Module,
+    File,
+    Line,
+    Thread,
+    ThreadId,
+    Uptime,
+    Fields,
+    Field(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Case {
+    Upper,
+    Lower,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Modifiers {
+    /// Pads to this many characters; positive values align right, negative values left.
+    padding: i32,
+    truncate: Option<usize>,
+    case: Option<Case>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Literal(String),
+    Token(Token, Modifiers),
+}
+
+/// A `${...}` template that is parsed once and then renders log messages.
+///
+/// See `docs/logging_formats.md` for the tokens and modifiers, e.g.
+/// `${date:format=%H:%M} ${level:uppercase:padding=-5} ${module:truncate=20} ${message}`.
+///
+/// # Example
+/// ```
+/// use diego::logging::log_layout::TemplateLayout;
+///
+/// let layout = TemplateLayout::parse("[${level:lowercase}] ${message}").unwrap();
+/// assert!(TemplateLayout::parse("${unknown}").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateLayout {
+    template: String,
+    parts: Vec<Part>,
+}
+
+impl TemplateLayout {
+    /// Parses a template.
+    ///
+    /// # Errors
+    /// `LogError::InvalidLayout` for unknown tokens or options, unclosed `${` and invalid
+    /// date formats.
+    pub fn parse(template: &str) -> Result<Self, LogError> {
+        let mut parts = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find("${") {
+            if start > 0 {
+                parts.push(Part::Literal(rest[..start].to_string()));
+            }
+            let Some(length) = rest[start..].find('}') else {
+                return Err(invalid_layout(template, "`${` is not closed"));
+            };
+            let (token, modifiers) = parse_token(&rest[start + 2..start + length])
+                .map_err(|reason| invalid_layout(template, &reason))?;
+            parts.push(Part::Token(token, modifiers));
+            rest = &rest[start + length + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Literal(rest.to_string()));
+        }
+
+        Ok(Self { template: template.to_string(), parts })
+    }
+
+    /// Returns the template the layout was parsed from.
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    pub fn render(&self, message: &LogMessage) -> String {
+        self.render_styled(message, None)
+    }
+
+    /// Renders the message with the `${level}` token wrapped in `level_style` and the
+    /// `${module}` and `${logger}` tokens in `module_style`, both ANSI escape sequences.
+    pub(crate) fn render_highlighted(&self, message: &LogMessage, level_style: &str, module_style: &str) -> String {
+        self.render_styled(message, Some((level_style, module_style)))
+    }
+
+    fn render_styled(&self, message: &LogMessage, styles: Option<(&str, &str)>) -> String {
+        let mut output = String::with_capacity(self.template.len() + message.message.len());
+        for part in &self.parts {
+            match part {
+                Part::Literal(literal) => output.push_str(literal),
+                Part::Token(token, modifiers) => {
+                    let style = match (token, styles) {
+                        (Token::Level, Some((level_style, _))) => Some(level_style),
+                        (Token::Module | Token::Logger, Some((_, module_style))) => Some(module_style),
+                        _ => None,
+                    };
+                    if let Some(style) = style {
+                        output.push_str(style);
+                    }
+
+                    let value = render_token(token, message);
+                    if *modifiers == Modifiers::default() {
+                        output.push_str(&value);
+                    } else {
+                        apply_modifiers(&mut output, &value, modifiers);
+                    }
+
+                    if style.is_some() {
+                        output.push_str(ANSI_RESET);
+                    }
+                }
+            }
+        }
+        output
+    }
+}
+
+impl Default for TemplateLayout {
+    fn default() -> Self {
+        Self::parse(DEFAULT_FORMAT).unwrap()
+    }
+}
+
+impl FromStr for TemplateLayout {
+    type Err = LogError;
+
+    fn from_str(template: &str) -> Result<Self, Self::Err> {
+        Self::parse(template)
+    }
+}
+
+/// How a log target renders a message into a line.
+///
+/// The machine-readable layouts use these field names:
+/// `timestamp` (RFC 3339 with milliseconds), `level`, `message`, `logger`, `module`, `file`,
+/// `line` and `thread`. Absent values are left out. The key/value fields of the message are
+/// nested under `fields` in JSON and appended as they are in logfmt.
+///
+/// # Example
+/// ```no_run
+/// use diego::core::diego_runtime::add_logger;
+/// use diego::logging::file_logger::FileTarget;
+/// use diego::logging::log_layout::LogLayout;
+///
+/// add_logger("shipper", FileTarget::new("logs/diego.jsonl").with_layout(LogLayout::Json)).unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogLayout {
+    /// A `${...}` template, see `TemplateLayout`.
+    Template(TemplateLayout),
+    /// One JSON object per line.
+    Json,
+    /// `key=value` pairs, quoted where necessary, see <https://brandur.org/logfmt>.
+    Logfmt,
+}
+
+impl LogLayout {
+    pub fn render(&self, message: &LogMessage) -> String {
+        match self {
+            LogLayout::Template(layout) => layout.render(message),
+            LogLayout::Json => Value::Object(json_object(message)).to_string(),
+            LogLayout::Logfmt => logfmt_line(message),
+        }
+    }
+}
+
+impl Default for LogLayout {
+    fn default() -> Self {
+        LogLayout::Template(TemplateLayout::default())
+    }
+}
+
+impl From<TemplateLayout> for LogLayout {
+    fn from(layout: TemplateLayout) -> Self {
+        LogLayout::Template(layout)
+    }
+}
+
+/// Returns the fields of a message in the order they appear in the machine-readable layouts,
+/// without the key/value fields.
+fn standard_fields(message: &LogMessage) -> Vec<(&'static str, Value)> {
+    let mut fields = vec![
+        ("timestamp", Value::from(message.timestamp.to_rfc3339_opts(SecondsFormat::Millis, false))),
+        ("level", Value::from(message.level.to_string())),
+        ("message", Value::from(message.message.as_str())),
+    ];
+    if let Some(logger) = &message.logger {
+        fields.push(("logger", Value::from(logger.as_str())));
+    }
+    if let Some(module) = &message.module {
+        fields.push(("module", Value::from(module.as_str())));
+    }
+    if let Some(file) = &message.file {
+        fields.push(("file", Value::from(file.as_str())));
+    }
+    if let Some(line) = message.line {
+        fields.push(("line", Value::from(line)));
+    }
+    fields.push(("thread", Value::from(message.thread())));
+    fields
+}
+
+/// Returns the message as a JSON object, with the key/value fields under `fields`.
+pub(crate) fn json_object(message: &LogMessage) -> Map<String, Value> {
+    let mut object: Map<String, Value> = standard_fields(message).into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect();
+    if !message.fields.is_empty() {
+        let fields = message.fields.iter()
+            .map(|(key, value)| (key.clone(), Value::from(value.as_str())))
+            .collect();
+        object.insert("fields".to_string(), Value::Object(fields));
+    }
+    object
+}
+
+fn logfmt_line(message: &LogMessage) -> String {
+    let mut line = String::new();
+    let standard = standard_fields(message).into_iter().map(|(key, value)| match value {
+        Value::String(value) => (Cow::Borrowed(key), Cow::Owned(value)),
+        value => (Cow::Borrowed(key), Cow::Owned(value.to_string())),
+    });
+    let fields = message.fields.iter().map(|(key, value)| (logfmt_key(key), Cow::Borrowed(value.as_str())));
+    for (key, value) in standard.chain(fields) {
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&key);
+        line.push('=');
+        push_logfmt_value(&mut line, &value);
+    }
+    line
+}
+
+/// Replaces the characters that would end a logfmt key.
+fn logfmt_key(key: &str) -> Cow<'_, str> {
+    if key.is_empty() {
+        Cow::Borrowed("_")
+    } else if key.chars().any(needs_quoting) {
+        Cow::Owned(key.chars().map(|c| if needs_quoting(c) { '_' } else { c }).collect())
+    } else {
+        Cow::Borrowed(key)
+    }
+}
+
+fn needs_quoting(c: char) -> bool {
+    c.is_whitespace() || c.is_control() || c == '=' || c == '"' || c == '\\'
+}
+
+/// Quotes values that are empty or contain whitespace, `=`, quotes, backslashes or control
+/// characters.
+fn push_logfmt_value(line: &mut String, value: &str) {
+    if !value.is_empty() && !value.chars().any(needs_quoting) {
+        line.push_str(value);
+        return;
+    }
+
+    line.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => line.push_str("\\\""),
+            '\\' => line.push_str("\\\\"),
+            '\n' => line.push_str("\\n"),
+            '\r' => line.push_str("\\r"),
+            '\t' => line.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(line, "\\u{:04x}", c as u32);
+            }
+            c => line.push(c),
+        }
+    }
+    line.push('"');
+}
+
+fn invalid_layout(template: &str, reason: &str) -> LogError {
+    LogError::InvalidLayout(template.to_string(), reason.to_string())
+}
+
+/// Parses the content between `${` and `}`: the name, then options separated by `:`.
+/// `format=` takes the rest of the token, so date formats may contain colons.
+fn parse_token(content: &str) -> Result<(Token, Modifiers), String> {
+    let (name, mut options) = match content.split_once(':') {
+        Some((name, options)) => (name, Some(options)),
+        None => (content, None),
+    };
+
+    let mut token = match name {
+        "date" | "longdate" => Token::Date(LONGDATE_FORMAT.to_string()),
+        "shortdate" => Token::Date(SHORTDATE_FORMAT.to_string()),
+        "time" => Token::Date(TIME_FORMAT.to_string()),
+        "ticks" => Token::Ticks,
+        "level" => Token::Level,
+        "message" => Token::Message,
+        "logger" => Token::Logger,
+        "module" => Token::Module,
+        "file" => Token::File,
+        "line" => Token::Line,
+        "thread" => Token::Thread,
+        "threadid" => Token::ThreadId,
+        "uptime" => Token::Uptime,
+        "fields" => Token::Fields,
+        "field" => {
+            let (key, rest) = match options {
+                Some(options) => split_option(options),
+                None => ("", None),
+            };
+            if key.is_empty() || key.contains('=') {
+                return Err("`${field}` requires a field name, e.g. `${field:fps}`".to_string());
+            }
+            options = rest;
+            Token::Field(key.to_string())
+        }
+        _ => return Err(format!("unknown token `${{{}}}`", name)),
+    };
+
+    let mut modifiers = Modifiers::default();
+    while let Some(remaining) = options {
+        if let Some(format) = remaining.strip_prefix("format=") {
+            if name != "date" {
+                return Err(format!("`format` is only supported by `${{date}}`, not `${{{}}}`", name));
+            }
+            if format.is_empty() || StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+                return Err(format!("invalid date format `{}`", format));
+            }
+            token = Token::Date(format.to_string());
+            break;
+        }
+
+        let (option, rest) = split_option(remaining);
+        match option {
+            "uppercase" | "uppercase=true" => modifiers.case = Some(Case::Upper),
+            "lowercase" | "lowercase=true" => modifiers.case = Some(Case::Lower),
+            _ => match option.split_once('=') {
+                Some(("padding", value)) => {
+                    modifiers.padding = value.parse().map_err(|_| format!("invalid padding `{}`", value))?;
+                }
+                Some(("truncate", value)) => {
+                    let length = value.parse().map_err(|_| format!("invalid truncation `{}`", value))?;
+                    modifiers.truncate = Some(length);
+                }
+                _ => return Err(format!("unknown option `{}`", option)),
+            },
+        }
+        options = rest;
+    }
+
+    Ok((token, modifiers))
+}
+
+fn split_option(options: &str) -> (&str, Option<&str>) {
+    match options.split_once(':') {
+        Some((option, rest)) => (option, Some(rest)),
+        None => (options, None),
+    }
+}
+
+fn render_token<'a>(token: &Token, message: &'a LogMessage) -> Cow<'a, str> {
+    match token {
+        Token::Date(format) => Cow::Owned(message.timestamp.format(format).to_string()),
+        Token::Ticks => Cow::Owned(message.timestamp.timestamp_nanos_opt().unwrap_or_default().to_string()),
+        Token::Level => Cow::Owned(message.level.to_string()),
+        Token::Message => Cow::Borrowed(&message.message),
+        Token::Logger => Cow::Borrowed(message.logger.as_deref().or(message.module.as_deref()).unwrap_or_default()),
+        Token::Module => Cow::Borrowed(message.module.as_deref().unwrap_or_default()),
+        Token::File => Cow::Borrowed(message.file.as_deref().unwrap_or_default()),
+        Token::Line => Cow::Owned(message.line.map(|line| line.to_string()).unwrap_or_default()),
+        Token::Thread => Cow::Owned(message.thread()),
+        Token::ThreadId => Cow::Owned(format!("{:?}", message.thread_id)),
+        Token::Uptime => Cow::Owned(format!("{:.3}", message.uptime().as_secs_f64())),
+        Token::Fields => Cow::Owned(format_fields(message)),
+        Token::Field(key) => Cow::Borrowed(message.field(key).unwrap_or_default()),
+    }
+}
+
+fn apply_modifiers(output: &mut String, value: &str, modifiers: &Modifiers) {
+    let value = match modifiers.case {
+        Some(Case::Upper) => Cow::Owned(value.to_uppercase()),
+        Some(Case::Lower) => Cow::Owned(value.to_lowercase()),
+        None => Cow::Borrowed(value),
+    };
+    let value = match modifiers.truncate {
+        Some(length) => match value.char_indices().nth(length) {
+            Some((end, _)) => &value[..end],
+            None => &value,
+        },
+        None => &value,
+    };
+    let width = modifiers.padding.unsigned_abs() as usize;
+    if modifiers.padding < 0 {
+        let _ = write!(output, "{:<width$}", value);
+    } else {
+        let _ = write!(output, "{:>width$}", value);
+    }
+}
+
+/// Renders the key/value fields as `key=value`, separated by spaces.
+pub(crate) fn format_fields(message: &LogMessage) -> String {
+    message.fields.iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+    use crate::logging::LogLevel;
+    use super::*;
+
+    fn format_message(format: &str, message: &LogMessage) -> String {
+        TemplateLayout::parse(format).unwrap().render(message)
+    }
+
+    #[test]
+    fn test_format_message_with_longdate() {
+        let log_message = LogMessage::new(LogLevel::Info, "Test message");
+        let format = "${longdate} [${level}] ${message}";
+        let result = format_message(format, &log_message);
+
+        // Check whether the format is replaced correctly
+        assert!(result.contains("INFO"));
+        assert!(result.contains("Test message"));
+
+        // Check whether the timestamp is in the correct format
+        let regex = Regex::new(r"\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}\.\d{3}").unwrap();
+        assert!(regex.is_match(&result), "Timestamp format is incorrect");
+    }
+
+    #[test]
+    fn test_format_message_with_shortdate() {
+        let log_message = LogMessage::new(LogLevel::Error, "An error occurred");
+        let format = "${shortdate} [${level}] ${message}";
+        let result = format_message(format, &log_message);
+
+        // Check whether the date, level and message are formatted correctly
+        assert!(result.contains("ERROR"));
+        assert!(result.contains("An error occurred"));
+
+        // Check whether the timestamp is in the correct format
+        let regex = Regex::new(r"\d{4}-\d{2}-\d{2}").unwrap();
+        assert!(regex.is_match(&result), "Short date format is incorrect");
+    }
+
+    #[test]
+    fn test_format_message_with_time() {
+        let log_message = LogMessage::new(LogLevel::Debug, "Debugging");
+        let format = "${time} [${level}] ${message}";
+        let result = format_message(format, &log_message);
+
+        // Check whether the time, level and message are formatted correctly
+        assert!(result.contains("DEBUG"));
+        assert!(result.contains("Debugging"));
+
+        // Check whether the timestamp is in the correct format
+        let regex = Regex::new(r"\d{2}:\d{2}:\d{2}\.\d{3}").unwrap();
+        assert!(regex.is_match(&result), "Time format is incorrect");
+    }
+
+    #[test]
+    fn test_format_message_with_ticks() {
+        let log_message = LogMessage::new(LogLevel::Warn, "Warning issued");
+
+
+        let format = "${ticks} [${level}] ${message}";
+        let result = format_message(format, &log_message);
+
+        // Check whether the level and the message are formatted correctly
+        assert!(result.contains("WARN"));
+        assert!(result.contains("Warning issued"));
+
+        // Check whether the ticks are formatted correctly
+        let regex = Regex::new(r"\d+").unwrap();
+        assert!(regex.is_match(&result), "Ticks format is incorrect");
+    }
+
+    #[test]
+    fn test_format_message_with_location_and_fields() {
+        let log_message = LogMessage::new(LogLevel::Info, "Frame finished")
+            .with_location(Some("game::render".to_string()), Some("src/render.rs".to_string()), Some(12))
+            .with_field("fps", 60)
+            .with_field("frame_time", 16.6);
+        let format = "${module} ${file}:${line} [${level}] ${message} ${fields}";
+        let result = format_message(format, &log_message);
+
+        assert_eq!(result, "game::render src/render.rs:12 [INFO] Frame finished fps=60 frame_time=16.6");
+        assert_eq!(format_message("${field:fps}/${field:missing}", &log_message), "60/");
+        assert_eq!(format_message("${logger}", &log_message), "game::render");
+    }
+
+    #[test]
+    fn test_date_format_and_modifiers() {
+        let log_message = LogMessage::new(LogLevel::Warn, "Warning issued")
+            .with_location(Some("diego::assets::hot_reloader".to_string()), None, None);
+
+        let regex = Regex::new(r"^\d{2}:\d{2}$").unwrap();
+        assert!(regex.is_match(&format_message("${date:format=%H:%M}", &log_message)));
+
+        let result = format_message("[${level:lowercase:padding=-6}][${module:truncate=5:padding=7}]", &log_message);
+        assert_eq!(result, "[warn  ][  diego]");
+        assert_eq!(format_message("${message:uppercase=true}", &log_message), "WARNING ISSUED");
+    }
+
+    #[test]
+    fn test_json_layout() {
+        let log_message = LogMessage::new(LogLevel::Error, "Shader \"basic\" failed:\n\tline 3")
+            .with_location(Some("game::render".to_string()), Some("src/render.rs".to_string()), Some(7))
+            .with_field("program", 12);
+        let line = LogLayout::Json.render(&log_message);
+        assert!(!line.contains('\n'));
+
+        let json: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(json["level"], "ERROR");
+        assert_eq!(json["message"], "Shader \"basic\" failed:\n\tline 3");
+        assert_eq!(json["module"], "game::render");
+        assert_eq!(json["line"], 7);
+        assert_eq!(json["fields"]["program"], "12");
+        assert!(json.get("logger").is_none());
+        assert!(json["timestamp"].as_str().unwrap().contains('T'));
+    }
+
+    #[test]
+    fn test_logfmt_layout() {
+        let log_message = LogMessage::new(LogLevel::Info, "say \"hi\"\n")
+            .with_location(Some("game".to_string()), None, Some(3))
+            .with_field("fps", 60)
+            .with_field("odd key", "a=b")
+            .with_field("empty", "");
+        let line = LogLayout::Logfmt.render(&log_message);
+
+        assert!(line.starts_with("timestamp="), "{}", line);
+        assert!(line.contains(r#" level=INFO message="say \"hi\"\n" module=game line=3 thread="#), "{}", line);
+        assert!(line.ends_with(r#" fps=60 odd_key="a=b" empty="""#), "{}", line);
+    }
+
+    #[test]
+    fn test_invalid_templates() {
+        for template in ["${unknown}", "${level", "${field}", "${level:padding=x}", "${level:bold}",
+                         "${time:format=%H}", "${date:format=%Q}"] {
+            assert!(TemplateLayout::parse(template).is_err(), "{} should be rejected", template);
+        }
+    }
+}
@@ -1,10 +1,17 @@
-use crate::logging::log_queue::LogQueue;
+use crate::logging::log_filter::{LogFilter, LogFilters};
+use crate::logging::log_queue::{LogQueue, LogQueueHandle, OverflowPolicy, DEFAULT_QUEUE_CAPACITY};
 use crate::logging::{LogLevel, LogTarget};
+use chrono::{DateTime, Local};
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
-use std::time::Instant;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
+/// The time the first message was created, the reference for `LogMessage::uptime`.
+static LOGGING_START: OnceLock<Instant> = OnceLock::new();
+
 #[derive(Error, Debug)]
 pub enum LogError {
     #[error("Logger name must not be empty. Please provide a valid, non-empty string to uniquely identify the logger."
@@ -16,32 +23,117 @@ pub enum LogError {
     LoggerNotFound(String),
     #[error("Given log target was of wrong type; required type is {0}, but it was {1}")]
     WrongTargetType(String, String),
+    #[error("Failed to write log file `{path}`: {source}")]
+    FileWriteFailed { path: PathBuf, source: std::io::Error },
+    #[error("Failed to send log messages to `{address}`: {source}")]
+    NetworkFailed { address: String, source: std::io::Error },
+    #[error("Failed to start the log worker thread: {0}")]
+    WorkerFailed(std::io::Error),
+    #[error("Unknown log level `{0}`")]
+    InvalidLevel(String),
+    #[error("Invalid log filter `{0}`: {1}")]
+    InvalidFilter(String, String),
+    #[error("Invalid log layout `{0}`: {1}")]
+    InvalidLayout(String, String),
 }
 
+/// A message with everything known about where and when it was logged.
+///
+/// The `log_*!` macros fill in the location and the key/value fields; the thread and the
+/// timestamp are taken when the message is created, not when a target writes it.
+#[derive(Debug, Clone)]
 pub struct LogMessage {
     pub level: LogLevel,
     pub message: String,
     pub log_time: Instant,
+    /// The wall-clock time the message was logged at.
+    pub timestamp: DateTime<Local>,
+    /// The module the message was logged from.
+    pub module: Option<String>,
+    /// The `log` target of messages from the `log` crate; `${logger}` falls back to `module`.
+    pub logger: Option<String>,
+    /// The source file the message was logged from.
+    pub file: Option<String>,
+    /// The line in `file` the message was logged from.
+    pub line: Option<u32>,
+    /// The name of the thread the message was logged from, if it has one.
+    pub thread_name: Option<String>,
+    pub thread_id: ThreadId,
+    /// Key/value pairs in the order they were given, e.g. `fps = 60` in `log_info!(fps = 60, "frame")`.
+    pub fields: Vec<(String, String)>,
+    /// Whether the message was forwarded from the `log` crate, see `logging::log_bridge`.
+    pub(crate) from_log_crate: bool,
 }
 
 impl LogMessage {
-    pub(crate) fn new<T: AsRef<str>>(level: LogLevel, message: T) -> Self {
-        Self { level, message: message.as_ref().to_owned(), log_time: Instant::now() }
+    pub fn new<T: AsRef<str>>(level: LogLevel, message: T) -> Self {
+        let thread = thread::current();
+        LOGGING_START.get_or_init(Instant::now);
+        Self {
+            level,
+            message: message.as_ref().to_owned(),
+            log_time: Instant::now(),
+            timestamp: Local::now(),
+            module: None,
+            logger: None,
+            file: None,
+            line: None,
+            thread_name: thread.name().map(str::to_string),
+            thread_id: thread.id(),
+            fields: Vec::new(),
+            from_log_crate: false,
+        }
+    }
+
+    pub fn with_location(mut self, module: Option<String>, file: Option<String>, line: Option<u32>) -> Self {
+        self.module = module;
+        self.file = file;
+        self.line = line;
+        self
+    }
+
+    pub fn with_field<K: Into<String>, V: ToString>(mut self, key: K, value: V) -> Self {
+        self.fields.push((key.into(), value.to_string()));
+        self
+    }
+
+    /// Returns the value of the first field with the given key.
+    pub fn field(&self, key: &str) -> Option<&str> {
+        self.fields.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    /// Returns the time since the first log message of the process was created.
+    pub fn uptime(&self) -> Duration {
+        LOGGING_START.get().map(|start| self.log_time.duration_since(*start)).unwrap_or_default()
+    }
+
+    /// Returns the thread name, or the thread id for unnamed threads.
+    pub fn thread(&self) -> String {
+        match &self.thread_name {
+            Some(name) => name.clone(),
+            None => format!("{:?}", self.thread_id),
+        }
     }
 }
 
 pub(crate) struct LogManager {
     loggers: Arc<RwLock<HashMap<String, Box<dyn LogTarget>>>>,
+    filters: Arc<RwLock<LogFilters>>,
     logger_count: u32,
-    message_queue: Option<LogQueue>
+    message_queue: Option<LogQueue>,
+    queue_capacity: usize,
+    overflow_policy: OverflowPolicy,
 }
 
 impl LogManager {
     pub(crate) fn new() -> Self {
         Self {
             loggers: Arc::new(RwLock::new(HashMap::new())),
+            filters: Arc::new(RwLock::new(LogFilters::default())),
             logger_count: 0,
-            message_queue: None
+            message_queue: None,
+            queue_capacity: DEFAULT_QUEUE_CAPACITY,
+            overflow_policy: OverflowPolicy::default(),
         }
     }
 
@@ -133,7 +225,8 @@ impl LogManager {
 
     pub fn start_log_processing(&mut self) {
         if self.message_queue.is_none() {
-            let create_and_run_result = LogQueue::create_and_run(self.loggers.clone());
+            let create_and_run_result = LogQueue::create_and_run(
+                self.loggers.clone(), self.filters.clone(), self.queue_capacity, self.overflow_policy);
             match create_and_run_result {
                 Ok(log_queue) => {
                     self.message_queue = Some(log_queue);
@@ -145,10 +238,52 @@ impl LogManager {
         }
     }
 
+    /// Writes the queued messages and stops the worker thread.
     pub fn stop_log_processing(&mut self) {
-        if let Some(msq_queue) = self.message_queue.as_mut() {
-            msq_queue.cancel();
-            self.message_queue = None;
+        if let Some(mut msg_queue) = self.message_queue.take() {
+            msg_queue.shutdown();
+        }
+    }
+
+    /// Takes the queue out of the manager, so it can be shut down without holding the
+    /// manager's lock.
+    pub(crate) fn take_queue(&mut self) -> Option<LogQueue> {
+        self.message_queue.take()
+    }
+
+    /// Returns a handle to the running queue; push and flush through it after releasing the
+    /// manager's lock.
+    pub(crate) fn queue_handle(&self) -> Option<LogQueueHandle> {
+        self.message_queue.as_ref().map(LogQueue::handle)
+    }
+
+    /// Sets the filter for all loggers without their own filter; `None` lets the loggers
+    /// filter by their levels again.
+    pub fn set_filter(&mut self, filter: Option<LogFilter>) {
+        self.filters.write().unwrap().global = filter;
+    }
+
+    /// Sets the filter of the logger with the name, which need not be added yet; `None`
+    /// falls back to the global filter.
+    pub fn set_logger_filter<N: AsRef<str>>(&mut self, name: N, filter: Option<LogFilter>) -> Result<(), LogError> {
+        let logger_name = name.as_ref().to_owned();
+        if logger_name.is_empty() {
+            return Err(LogError::InvalidLoggerName);
+        }
+
+        let mut filters = self.filters.write().unwrap();
+        match filter {
+            Some(filter) => filters.loggers.insert(logger_name, filter),
+            None => filters.loggers.remove(&logger_name),
+        };
+        Ok(())
+    }
+
+    pub fn set_queue_capacity(&mut self, capacity: usize, overflow_policy: OverflowPolicy) {
+        self.queue_capacity = capacity;
+        self.overflow_policy = overflow_policy;
+        if let Some(msg_queue) = self.message_queue.as_ref() {
+            msg_queue.set_overflow_policy(capacity, overflow_policy);
         }
     }
 
@@ -163,12 +298,4 @@ impl LogManager {
             self.stop_log_processing();
         }
     }
-
-    pub fn add_log_message(&mut self, level: LogLevel, message: String) {
-        if let Some(log_queue) = self.message_queue.as_mut() {
-            println!("Added log message: {} ({})", message, level);
-            let log_message = LogMessage::new(level, message);
-            log_queue.push_message(log_message);
-        }
-    }
 }
@@ -1,65 +1,408 @@
+use crate::logging::log_filter::LogFilters;
 use crate::logging::log_manager::{LogError, LogMessage};
-use crate::logging::LogTarget;
+use crate::logging::{LogLevel, LogTarget};
 use std::collections::{HashMap, VecDeque};
-use std::sync::{Arc, RwLock};
-use std::thread;
-use std::time::Duration;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, RwLock};
+use std::thread::{self, JoinHandle, ThreadId};
 
+pub const DEFAULT_QUEUE_CAPACITY: usize = 10_000;
+
+/// What happens to a message that is logged while the log queue is full.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// The logging thread waits until the worker has made room. Messages logged by the log
+    /// targets themselves are dropped instead, since the worker cannot wait for itself.
+    #[default]
+    Block,
+    /// The oldest queued message is dropped to make room.
+    DropOldest,
+    /// The new message is dropped.
+    DropNewest,
+}
+
+struct QueueState {
+    messages: VecDeque<LogMessage>,
+    capacity: usize,
+    overflow_policy: OverflowPolicy,
+    /// Messages dropped because of the overflow policy since the last batch.
+    dropped: u64,
+    /// The number of flushes requested and completed; a flush is done once `flushed`
+    /// reaches the value `flush_requested` had when it was requested.
+    flush_requested: u64,
+    flushed: u64,
+    shutdown: bool,
+    worker_running: bool,
+}
+
+struct SharedQueue {
+    state: Mutex<QueueState>,
+    /// Signalled when messages, a flush request or the shutdown arrive.
+    work_available: Condvar,
+    /// Signalled when the worker took messages out of the queue.
+    space_available: Condvar,
+    /// Signalled when a batch was written and flushed, and when the worker stops.
+    batch_done: Condvar,
+    worker_thread: Mutex<Option<ThreadId>>,
+}
+
+impl SharedQueue {
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        // A panicking log target must not disable logging for the rest of the process.
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn is_worker_thread(&self) -> bool {
+        *self.worker_thread.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) == Some(thread::current().id())
+    }
+
+    fn push_message(&self, log_message: LogMessage) {
+        let mut state = self.lock();
+        if state.shutdown {
+            return;
+        }
+
+        if state.messages.len() >= state.capacity {
+            match state.overflow_policy {
+                OverflowPolicy::Block if !self.is_worker_thread() => {
+                    while state.messages.len() >= state.capacity && state.worker_running && !state.shutdown {
+                        state = self.space_available.wait(state).unwrap_or_else(|poisoned| poisoned.into_inner());
+                    }
+                    if state.shutdown {
+                        return;
+                    }
+                }
+                OverflowPolicy::DropOldest => {
+                    state.messages.pop_front();
+                    state.dropped += 1;
+                }
+                OverflowPolicy::Block | OverflowPolicy::DropNewest => {
+                    state.dropped += 1;
+                    return;
+                }
+            }
+        }
+
+        state.messages.push_back(log_message);
+        self.work_available.notify_one();
+    }
+
+    /// Waits until all messages queued so far were written and the targets flushed.
+    fn flush(&self) {
+        if self.is_worker_thread() {
+            return;
+        }
+
+        let mut state = self.lock();
+        state.flush_requested += 1;
+        let ticket = state.flush_requested;
+        self.work_available.notify_one();
+        while state.flushed < ticket && state.worker_running {
+            state = self.batch_done.wait(state).unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+    }
+}
+
+/// Marks the worker as stopped when it returns or unwinds, so nobody waits for it forever.
+struct WorkerGuard(Arc<SharedQueue>);
+
+impl Drop for WorkerGuard {
+    fn drop(&mut self) {
+        self.0.lock().worker_running = false;
+        self.0.space_available.notify_all();
+        self.0.batch_done.notify_all();
+    }
+}
+
+/// A bounded queue of log messages, written to the log targets by a worker thread.
+///
+/// The worker sleeps until messages arrive and writes them without holding the queue lock,
+/// so logging threads only wait for each other, not for slow targets.
 pub(crate) struct LogQueue {
-    message_queue: Arc<RwLock<VecDeque<LogMessage>>>,
-    loggers: Arc<RwLock<HashMap<String, Box<dyn LogTarget>>>>,
-    cancel: Arc<RwLock<bool>>,
+    shared: Arc<SharedQueue>,
+    worker: Option<JoinHandle<()>>,
 }
 
 impl LogQueue {
-    fn new(loggers: Arc<RwLock<HashMap<String, Box<dyn LogTarget>>>>) -> Self {
+    fn new(capacity: usize, overflow_policy: OverflowPolicy) -> Self {
+        let state = QueueState {
+            messages: VecDeque::new(),
+            capacity: capacity.max(1),
+            overflow_policy,
+            dropped: 0,
+            flush_requested: 0,
+            flushed: 0,
+            shutdown: false,
+            worker_running: true,
+        };
         Self {
-            message_queue: Arc::new(RwLock::new(VecDeque::new())),
-            loggers,
-            cancel: Arc::new(RwLock::new(false)),
+            shared: Arc::new(SharedQueue {
+                state: Mutex::new(state),
+                work_available: Condvar::new(),
+                space_available: Condvar::new(),
+                batch_done: Condvar::new(),
+                worker_thread: Mutex::new(None),
+            }),
+            worker: None,
         }
     }
 
-    pub fn create_and_run(loggers: Arc<RwLock<HashMap<String, Box<dyn LogTarget>>>>)
-        -> Result<Self, LogError> {
-        let mut msg_queue = Self::new(loggers);
-        msg_queue.process_queue();
+    pub fn create_and_run(loggers: Arc<RwLock<HashMap<String, Box<dyn LogTarget>>>>,
+                          filters: Arc<RwLock<LogFilters>>, capacity: usize,
+                          overflow_policy: OverflowPolicy) -> Result<Self, LogError> {
+        let mut msg_queue = Self::new(capacity, overflow_policy);
+        let shared = Arc::clone(&msg_queue.shared);
+        let worker = thread::Builder::new()
+            .name("diego-log".to_string())
+            .spawn(move || process_queue(shared, loggers, filters))
+            .map_err(LogError::WorkerFailed)?;
+        *msg_queue.shared.worker_thread.lock().unwrap() = Some(worker.thread().id());
+        msg_queue.worker = Some(worker);
         Ok(msg_queue)
     }
 
-    fn process_queue(&mut self) {
-        let loggers = Arc::clone(&self.loggers);
-        let cancel = Arc::clone(&self.cancel);
-        let msg_queue = Arc::clone(&self.message_queue);
-
-        thread::spawn(move || {
-            loop {
-                let mut queue = msg_queue.write().unwrap();
-                while let Some(log_message) = queue.pop_front() {
-                    let loggers = loggers.read().unwrap();
-                    for logger in loggers.values() {
-                        if let Err(e) = logger.log(&log_message) {
-                            eprintln!("Error logging message: {}", e);
-                            // TODO: Add extended error logging (file etc.)
-                        }
-                    }
-                }                 
-                
-                if *cancel.read().unwrap() {
-                    break;
-                }
+    pub fn set_overflow_policy(&self, capacity: usize, overflow_policy: OverflowPolicy) {
+        let mut state = self.shared.lock();
+        state.capacity = capacity.max(1);
+        state.overflow_policy = overflow_policy;
+        self.shared.space_available.notify_all();
+    }
+
+    pub fn push_message(&self, log_message: LogMessage) {
+        self.shared.push_message(log_message);
+    }
 
-                thread::sleep(Duration::from_millis(100));
+    /// Returns a handle to push to and wait for the queue without holding the `LogManager`
+    /// lock, which the worker may need when a target logs itself.
+    pub fn handle(&self) -> LogQueueHandle {
+        LogQueueHandle(Arc::clone(&self.shared))
+    }
+
+    /// Writes all queued messages, then stops and joins the worker. Messages pushed
+    /// afterwards are discarded.
+    pub fn shutdown(&mut self) {
+        self.shared.lock().shutdown = true;
+        self.shared.work_available.notify_one();
+        self.shared.space_available.notify_all();
+        if let Some(worker) = self.worker.take() {
+            if worker.thread().id() != thread::current().id() && worker.join().is_err() {
+                eprintln!("Log worker thread panicked");
             }
-        });
+        }
     }
+}
 
-    pub fn cancel(&mut self) {
-        *self.cancel.write().unwrap() = true;
+impl Drop for LogQueue {
+    fn drop(&mut self) {
+        self.shutdown();
     }
+}
+
+/// Pushes messages and waits for the queue, see `LogQueue::handle`.
+pub(crate) struct LogQueueHandle(Arc<SharedQueue>);
 
+impl LogQueueHandle {
     pub fn push_message(&self, log_message: LogMessage) {
-        let mut queue = self.message_queue.write().unwrap();
-        queue.push_back(log_message);
+        self.0.push_message(log_message);
+    }
+
+    /// Waits until the messages queued so far are written.
+    pub fn flush(&self) {
+        self.0.flush();
+    }
+}
+
+fn process_queue(shared: Arc<SharedQueue>, loggers: Arc<RwLock<HashMap<String, Box<dyn LogTarget>>>>,
+                 filters: Arc<RwLock<LogFilters>>) {
+    let _guard = WorkerGuard(Arc::clone(&shared));
+    loop {
+        let (batch, dropped, flush_ticket, shutdown) = {
+            let mut state = shared.lock();
+            while state.messages.is_empty() && state.flushed == state.flush_requested && !state.shutdown {
+                state = shared.work_available.wait(state).unwrap_or_else(|poisoned| poisoned.into_inner());
+            }
+            let batch: Vec<LogMessage> = state.messages.drain(..).collect();
+            let dropped = std::mem::take(&mut state.dropped);
+            shared.space_available.notify_all();
+            (batch, dropped, state.flush_requested, state.shutdown)
+        };
+
+        {
+            let loggers = loggers.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+            let filters = filters.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+            if dropped > 0 {
+                let notice = LogMessage::new(LogLevel::Warn,
+                    format!("{} log messages were dropped because the log queue was full", dropped));
+                write_message(&loggers, &filters, &notice);
+            }
+            for log_message in &batch {
+                write_message(&loggers, &filters, log_message);
+            }
+            for logger in loggers.values() {
+                if let Err(e) = logger.flush() {
+                    eprintln!("Error flushing log target: {}", e);
+                }
+            }
+        }
+
+        let mut state = shared.lock();
+        state.flushed = flush_ticket;
+        shared.batch_done.notify_all();
+        if shutdown && state.messages.is_empty() {
+            break;
+        }
+    }
+}
+
+fn write_message(loggers: &HashMap<String, Box<dyn LogTarget>>, filters: &LogFilters, log_message: &LogMessage) {
+    for (name, logger) in loggers {
+        let enabled = match filters.get(name) {
+            Some(filter) => filter.is_enabled(log_message),
+            None => logger.is_logging_enabled(log_message.level),
+        };
+        if !enabled {
+            continue;
+        }
+        if let Err(e) = logger.log(log_message) {
+            eprintln!("Error logging message: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logging::log_filter::LogFilter;
+    use std::any::Any;
+    use std::time::Duration;
+
+    type Messages = Arc<Mutex<Vec<String>>>;
+
+    /// Records the messages and blocks while `gate` is closed.
+    #[derive(Default)]
+    struct RecordingTarget {
+        messages: Messages,
+        gate: Arc<Mutex<()>>,
+    }
+
+    impl LogTarget for RecordingTarget {
+        fn update(&mut self, _log_target: Box<dyn LogTarget>) -> bool {
+            false
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn target_name(&self) -> &'static str {
+            "RecordingTarget"
+        }
+
+        fn is_logging_enabled(&self, _level: LogLevel) -> bool {
+            true
+        }
+
+        fn log(&self, log_message: &LogMessage) -> Result<(), LogError> {
+            let _gate = self.gate.lock().unwrap();
+            self.messages.lock().unwrap().push(log_message.message.clone());
+            Ok(())
+        }
+    }
+
+    fn create_queue(capacity: usize, overflow_policy: OverflowPolicy)
+        -> (LogQueue, Messages, Arc<Mutex<()>>) {
+        create_filtered_queue(capacity, overflow_policy, LogFilters::default())
+    }
+
+    fn create_filtered_queue(capacity: usize, overflow_policy: OverflowPolicy, filters: LogFilters)
+        -> (LogQueue, Messages, Arc<Mutex<()>>) {
+        let target = RecordingTarget::default();
+        let messages = Arc::clone(&target.messages);
+        let gate = Arc::clone(&target.gate);
+        let mut loggers: HashMap<String, Box<dyn LogTarget>> = HashMap::new();
+        loggers.insert("recording".to_string(), Box::new(target));
+        let filters = Arc::new(RwLock::new(filters));
+        let queue = LogQueue::create_and_run(Arc::new(RwLock::new(loggers)), filters, capacity, overflow_policy)
+            .unwrap();
+        (queue, messages, gate)
+    }
+
+    /// Waits until the worker took the first message and is blocked on the closed gate.
+    fn wait_until_worker_is_busy(queue: &LogQueue) {
+        while !queue.shared.lock().messages.is_empty() {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn test_flush_and_shutdown_write_everything() {
+        let (mut queue, messages, _gate) = create_queue(DEFAULT_QUEUE_CAPACITY, OverflowPolicy::Block);
+        queue.push_message(LogMessage::new(LogLevel::Info, "first"));
+        queue.handle().flush();
+        assert_eq!(*messages.lock().unwrap(), vec!["first"]);
+
+        queue.push_message(LogMessage::new(LogLevel::Info, "second"));
+        queue.shutdown();
+        queue.push_message(LogMessage::new(LogLevel::Info, "discarded"));
+        assert_eq!(*messages.lock().unwrap(), vec!["first", "second"]);
+    }
+
+    #[test]
+    fn test_drop_oldest_and_drop_newest() {
+        const NOTICE: &str = "2 log messages were dropped because the log queue was full";
+        for (overflow_policy, expected) in [
+            (OverflowPolicy::DropOldest, vec!["blocker", NOTICE, "3", "4"]),
+            (OverflowPolicy::DropNewest, vec!["blocker", NOTICE, "1", "2"]),
+        ] {
+            let (mut queue, messages, gate) = create_queue(2, overflow_policy);
+            let closed = gate.lock().unwrap();
+            queue.push_message(LogMessage::new(LogLevel::Info, "blocker"));
+            wait_until_worker_is_busy(&queue);
+            for message in ["1", "2", "3", "4"] {
+                queue.push_message(LogMessage::new(LogLevel::Info, message));
+            }
+            drop(closed);
+            queue.shutdown();
+            assert_eq!(*messages.lock().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_block_waits_for_room() {
+        let (mut queue, messages, gate) = create_queue(1, OverflowPolicy::Block);
+        let closed = gate.lock().unwrap();
+        queue.push_message(LogMessage::new(LogLevel::Info, "blocker"));
+        wait_until_worker_is_busy(&queue);
+        queue.push_message(LogMessage::new(LogLevel::Info, "1"));
+
+        let handle = queue.handle();
+        let producer = thread::spawn(move || {
+            handle.push_message(LogMessage::new(LogLevel::Info, "2"));
+        });
+        thread::sleep(Duration::from_millis(50));
+        assert!(!producer.is_finished());
+
+        drop(closed);
+        producer.join().unwrap();
+        queue.shutdown();
+        assert_eq!(*messages.lock().unwrap(), vec!["blocker", "1", "2"]);
+    }
+
+    #[test]
+    fn test_logger_filter_overrides_global_filter() {
+        let global = LogFilter::parse("error").unwrap();
+        let filters = LogFilters { global: Some(global.clone()), ..LogFilters::default() };
+        let (mut queue, messages, _gate) = create_filtered_queue(DEFAULT_QUEUE_CAPACITY, OverflowPolicy::Block, filters);
+        queue.push_message(LogMessage::new(LogLevel::Warn, "below the global level"));
+        queue.shutdown();
+        assert!(messages.lock().unwrap().is_empty());
+
+        let mut filters = LogFilters { global: Some(global), ..LogFilters::default() };
+        filters.loggers.insert("recording".to_string(), LogFilter::parse("game=trace").unwrap());
+        let (mut queue, messages, _gate) = create_filtered_queue(DEFAULT_QUEUE_CAPACITY, OverflowPolicy::Block, filters);
+        let game_message = LogMessage::new(LogLevel::Trace, "game").with_location(Some("game::ai".to_string()), None, None);
+        queue.push_message(game_message);
+        queue.push_message(LogMessage::new(LogLevel::Fatal, "other"));
+        queue.shutdown();
+        assert_eq!(*messages.lock().unwrap(), vec!["game"]);
     }
 }
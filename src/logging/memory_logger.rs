@@ -0,0 +1,200 @@
+use crate::logging::log_filter::matches_path;
+use crate::logging::log_manager::{LogError, LogMessage};
+use crate::logging::{LogLevel, LogTarget};
+use std::any::Any;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+const DEFAULT_CAPACITY: usize = 1000;
+
+/// Criteria for `MemoryTarget::query`; a message has to match all of them.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryQuery {
+    pub min_level: Option<LogLevel>,
+    /// A module prefix like in `LogFilter`; also matches the logger of the message.
+    pub module: Option<String>,
+    /// Text the message has to contain.
+    pub text: Option<String>,
+}
+
+impl MemoryQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_min_level(mut self, min_level: LogLevel) -> Self {
+        self.min_level = Some(min_level);
+        self
+    }
+
+    pub fn with_module<T: Into<String>>(mut self, module: T) -> Self {
+        self.module = Some(module.into());
+        self
+    }
+
+    pub fn with_text<T: Into<String>>(mut self, text: T) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    pub fn matches(&self, log_message: &LogMessage) -> bool {
+        self.min_level.is_none_or(|level| log_message.level >= level)
+            && self.module.as_deref().is_none_or(|module| {
+                [log_message.module.as_deref(), log_message.logger.as_deref()].into_iter()
+                    .flatten()
+                    .any(|path| matches_path(module, path))
+            })
+            && self.text.as_deref().is_none_or(|text| log_message.message.contains(text))
+    }
+}
+
+/// Keeps the last `capacity` messages in memory, e.g. for assertions in tests or the
+/// scrollback of an in-game console.
+///
+/// Clones share the messages, so keep a clone to read them after adding the target. Messages
+/// are written by the log queue's worker; call `diego_runtime::flush_logs` before reading
+/// messages that were just logged.
+///
+/// # Example
+/// ```no_run
+/// use diego::core::diego_runtime::{add_logger, flush_logs};
+/// use diego::logging::memory_logger::{MemoryQuery, MemoryTarget};
+/// use diego::logging::LogLevel;
+///
+/// let memory = MemoryTarget::new(500);
+/// add_logger("memory", memory.clone()).unwrap();
+/// // load the scene
+/// flush_logs();
+/// assert!(!memory.contains(&MemoryQuery::new().with_min_level(LogLevel::Error).with_module("diego::gl")));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MemoryTarget {
+    pub level: LogLevel,
+    /// The number of messages kept; older ones are discarded.
+    pub capacity: usize,
+    messages: Arc<Mutex<VecDeque<LogMessage>>>,
+}
+
+impl Default for MemoryTarget {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl MemoryTarget {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            level: LogLevel::Trace,
+            capacity,
+            messages: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+        }
+    }
+
+    pub fn with_level(mut self, level: LogLevel) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Returns copies of all kept messages, oldest first.
+    pub fn snapshot(&self) -> Vec<LogMessage> {
+        self.messages.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Returns copies of the kept messages matching the query, oldest first.
+    pub fn query(&self, query: &MemoryQuery) -> Vec<LogMessage> {
+        self.messages.lock().unwrap().iter().filter(|message| query.matches(message)).cloned().collect()
+    }
+
+    /// Whether any kept message matches the query.
+    pub fn contains(&self, query: &MemoryQuery) -> bool {
+        self.messages.lock().unwrap().iter().any(|message| query.matches(message))
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        self.messages.lock().unwrap().clear();
+    }
+}
+
+impl LogTarget for MemoryTarget {
+    /// Takes over the level and capacity; the registered target keeps its messages.
+    fn update(&mut self, log_target: Box<dyn LogTarget>) -> bool {
+        if let Some(target) = log_target.as_any().downcast_ref::<Self>() {
+            self.level = target.level;
+            self.capacity = target.capacity;
+            let mut messages = self.messages.lock().unwrap();
+            let excess = messages.len().saturating_sub(self.capacity);
+            messages.drain(..excess);
+            return true;
+        }
+        false
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn target_name(&self) -> &'static str {
+        "MemoryTarget"
+    }
+
+    fn is_logging_enabled(&self, level: LogLevel) -> bool {
+        level >= self.level
+    }
+
+    fn log(&self, log_message: &LogMessage) -> Result<(), LogError> {
+        if self.capacity == 0 {
+            return Ok(());
+        }
+        let mut messages = self.messages.lock().unwrap();
+        if messages.len() >= self.capacity {
+            messages.pop_front();
+        }
+        messages.push_back(log_message.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(level: LogLevel, module: &str, text: &str) -> LogMessage {
+        LogMessage::new(level, text).with_location(Some(module.to_string()), None, None)
+    }
+
+    #[test]
+    fn test_keeps_the_last_messages() {
+        let memory = MemoryTarget::new(2);
+        let registered = memory.clone();
+        for text in ["first", "second", "third"] {
+            registered.log(&message(LogLevel::Info, "game", text)).unwrap();
+        }
+        let texts: Vec<String> = memory.snapshot().into_iter().map(|message| message.message).collect();
+        assert_eq!(texts, vec!["second", "third"]);
+
+        memory.clear();
+        assert!(memory.is_empty());
+    }
+
+    #[test]
+    fn test_query() {
+        let memory = MemoryTarget::default();
+        memory.log(&message(LogLevel::Error, "diego::gl::texture", "GL_INVALID_ENUM in glTexImage2D")).unwrap();
+        memory.log(&message(LogLevel::Info, "diego::gl", "Using the Direct State Access code path")).unwrap();
+        memory.log(&message(LogLevel::Error, "game::scene", "Failed to load scene `intro`")).unwrap();
+
+        let gl_errors = MemoryQuery::new().with_min_level(LogLevel::Error).with_module("diego::gl");
+        assert_eq!(memory.query(&gl_errors).len(), 1);
+        assert!(memory.contains(&MemoryQuery::new().with_text("scene `intro`")));
+        assert!(!memory.contains(&MemoryQuery::new().with_module("diego::gl::shader")));
+        assert_eq!(memory.query(&MemoryQuery::new().with_module("diego")).len(), 2);
+    }
+}
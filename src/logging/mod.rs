@@ -2,14 +2,17 @@ use std::any::Any;
 use std::fmt::{Debug, Display, Formatter, Result};
 use crate::logging::log_manager::{LogError, LogMessage};
 use std::result::Result as StdResult;
+use std::str::FromStr;
 
 pub mod log_manager;
 pub mod file_logger;
 pub mod console_logger;
 pub mod network_logger;
 pub mod log_queue;
-
-mod log_formatter;
+pub mod log_bridge;
+pub mod log_layout;
+pub mod log_filter;
+pub mod memory_logger;
 
 #[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub enum LogLevel {
@@ -35,6 +38,18 @@ impl LogLevel {
     }
 }
 
+/// Parses the level names case-insensitively, e.g. `warn` or `WARN`.
+impl FromStr for LogLevel {
+    type Err = LogError;
+
+    fn from_str(level: &str) -> StdResult<Self, Self::Err> {
+        [LogLevel::Trace, LogLevel::Debug, LogLevel::Info, LogLevel::Warn, LogLevel::Error, LogLevel::Fatal]
+            .into_iter()
+            .find(|candidate| candidate.as_str().eq_ignore_ascii_case(level))
+            .ok_or_else(|| LogError::InvalidLevel(level.to_string()))
+    }
+}
+
 impl Display for LogLevel {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         write!(f, "{}", self.as_str())
@@ -51,6 +66,13 @@ pub trait LogTarget: Any + Send + Sync {
     fn update(&mut self, log_target: Box<dyn LogTarget>) -> bool;
     fn as_any(&self) -> &dyn Any;
     fn target_name(&self) -> &'static str;
+    /// Whether the target writes messages of the level; only asked by the log queue if no
+    /// `LogFilter` applies to the target.
     fn is_logging_enabled(&self, level: LogLevel) -> bool;
+    /// Writes the message; the log queue only passes messages the target is enabled for.
     fn log(&self, log_message: &LogMessage) -> StdResult<(), LogError>;
+    /// Writes buffered messages; called after every batch of messages.
+    fn flush(&self) -> StdResult<(), LogError> {
+        Ok(())
+    }
 }
@@ -1,3 +1,603 @@
-pub(crate) struct NetworkLogger {
-    
+use crate::logging::log_layout::json_object;
+use crate::logging::log_manager::{LogError, LogMessage};
+use crate::logging::{LogLevel, LogTarget};
+use chrono::SecondsFormat;
+use std::any::Any;
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const DEFAULT_MAX_BACKLOG: usize = 10_000;
+/// RFC 5426 asks receivers to accept datagrams of at least 2048 bytes.
+const DEFAULT_MAX_DATAGRAM_SIZE: usize = 2048;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+const WRITE_TIMEOUT: Duration = Duration::from_secs(2);
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+const MIN_BACKOFF: Duration = Duration::from_millis(250);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// The `user-level messages` syslog facility.
+const DEFAULT_FACILITY: u8 = 1;
+
+/// The wire format and transport of a `NetworkLogger`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkProtocol {
+    /// RFC 5424 syslog messages, one per datagram (RFC 5426).
+    SyslogUdp,
+    /// RFC 5424 syslog messages with octet-counting framing (RFC 6587).
+    SyslogTcp,
+    /// One JSON object per line with the fields of `LogLayout::Json` plus `host`, `app` and `pid`.
+    JsonTcp,
+}
+
+/// Sends log messages to a syslog server or log collector.
+///
+/// Messages are encoded when they are logged and kept in a backlog, which a sender thread of
+/// the target works off; name resolution, connecting and sending never block the log worker.
+/// If the collector cannot be reached, the connection is retried with an exponential backoff
+/// of up to 30 seconds. The backlog holds at most `max_backlog` messages; the oldest messages
+/// are dropped beyond that, and their number is reported once the connection works again.
+/// Syslog datagrams are truncated to `max_datagram_size` bytes, and a message the socket
+/// rejects for good, e.g. one that is too long, is dropped instead of being retried.
+///
+/// Send errors are returned by the next `log` or `flush` call. Dropping the target waits up to
+/// five seconds for the backlog to be sent.
+///
+/// # Example
+/// ```no_run
+/// use diego::core::diego_runtime::add_logger;
+/// use diego::logging::network_logger::{NetworkLogger, NetworkProtocol};
+///
+/// let target = NetworkLogger::new("logs.example.com:514", NetworkProtocol::SyslogUdp)
+///     .with_app_name("my-game");
+/// add_logger("syslog", target).unwrap();
+/// ```
+pub struct NetworkLogger {
+    pub level: LogLevel,
+    /// The `host:port` of the server.
+    pub address: String,
+    pub protocol: NetworkProtocol,
+    /// The application name sent with every message.
+    pub app_name: String,
+    /// The host name sent with every message; `-` if unknown.
+    pub hostname: String,
+    /// The syslog facility (0-23).
+    pub facility: u8,
+    /// The number of unsent messages kept while the server is unreachable.
+    pub max_backlog: usize,
+    /// The largest UDP payload sent; longer syslog messages are truncated.
+    pub max_datagram_size: usize,
+    shared: Arc<Shared>,
+    sender: OnceLock<()>,
+}
+
+impl NetworkLogger {
+    pub fn new<T: Into<String>>(address: T, protocol: NetworkProtocol) -> Self {
+        Self {
+            level: LogLevel::Info,
+            address: address.into(),
+            protocol,
+            app_name: "diego".to_string(),
+            hostname: std::env::var("HOSTNAME")
+                .or_else(|_| std::env::var("COMPUTERNAME"))
+                .unwrap_or_else(|_| "-".to_string()),
+            facility: DEFAULT_FACILITY,
+            max_backlog: DEFAULT_MAX_BACKLOG,
+            max_datagram_size: DEFAULT_MAX_DATAGRAM_SIZE,
+            shared: Arc::default(),
+            sender: OnceLock::new(),
+        }
+    }
+
+    pub fn with_level(mut self, level: LogLevel) -> Self {
+        self.level = level;
+        self
+    }
+
+    pub fn with_app_name<T: Into<String>>(mut self, app_name: T) -> Self {
+        self.app_name = app_name.into();
+        self
+    }
+
+    pub fn with_hostname<T: Into<String>>(mut self, hostname: T) -> Self {
+        self.hostname = hostname.into();
+        self
+    }
+
+    pub fn with_facility(mut self, facility: u8) -> Self {
+        self.facility = facility.min(23);
+        self
+    }
+
+    pub fn with_max_backlog(mut self, max_backlog: usize) -> Self {
+        self.max_backlog = max_backlog;
+        self
+    }
+
+    pub fn with_max_datagram_size(mut self, max_datagram_size: usize) -> Self {
+        self.max_datagram_size = max_datagram_size;
+        self
+    }
+
+    /// Returns the number of messages waiting to be sent.
+    pub fn backlog_len(&self) -> usize {
+        self.shared.lock().backlog.len()
+    }
+
+    fn endpoint(&self) -> Endpoint {
+        Endpoint {
+            address: self.address.clone(),
+            protocol: self.protocol,
+            app_name: self.app_name.clone(),
+            hostname: self.hostname.clone(),
+            facility: self.facility,
+            max_datagram_size: self.max_datagram_size,
+        }
+    }
+
+    fn start_sender(&self) -> Result<(), LogError> {
+        if self.sender.get().is_some() {
+            return Ok(());
+        }
+        let shared = self.shared.clone();
+        thread::Builder::new()
+            .name("diego-network-logger".to_string())
+            .spawn(move || send_backlog(shared))
+            .map_err(LogError::WorkerFailed)?;
+        let _ = self.sender.set(());
+        Ok(())
+    }
+
+    /// Waits until the backlog was sent, the server turned out to be unreachable or five
+    /// seconds passed.
+    fn wait_for_backlog(&self) {
+        let deadline = Instant::now() + FLUSH_TIMEOUT;
+        let mut state = self.shared.lock();
+        while state.sending || (!state.backlog.is_empty() && state.retry_at.is_none() && self.sender.get().is_some()) {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            state = self.shared.changed.wait_timeout(state, deadline - now).unwrap().0;
+        }
+    }
+
+    fn take_error(&self, state: &mut State) -> Result<(), LogError> {
+        match state.last_error.take() {
+            Some(source) => Err(LogError::NetworkFailed { address: self.address.clone(), source }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for NetworkLogger {
+    /// Gives the sender thread up to five seconds to send the backlog, then stops it.
+    fn drop(&mut self) {
+        self.wait_for_backlog();
+        self.shared.lock().shutdown = true;
+        self.shared.changed.notify_all();
+    }
+}
+
+impl LogTarget for NetworkLogger {
+    /// Takes over the settings; the registered target keeps its backlog and sender thread.
+    /// Already encoded messages keep the previous format.
+    fn update(&mut self, log_target: Box<dyn LogTarget>) -> bool {
+        if let Some(target) = log_target.as_any().downcast_ref::<Self>() {
+            self.level = target.level;
+            self.address = target.address.clone();
+            self.protocol = target.protocol;
+            self.app_name = target.app_name.clone();
+            self.hostname = target.hostname.clone();
+            self.facility = target.facility;
+            self.max_backlog = target.max_backlog;
+            self.max_datagram_size = target.max_datagram_size;
+            return true;
+        }
+        false
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn target_name(&self) -> &'static str {
+        "NetworkLogger"
+    }
+
+    fn is_logging_enabled(&self, level: LogLevel) -> bool {
+        level >= self.level
+    }
+
+    fn log(&self, log_message: &LogMessage) -> Result<(), LogError> {
+        let endpoint = self.endpoint();
+        let record = endpoint.encode(log_message);
+        {
+            let mut state = self.shared.lock();
+            if state.endpoint.address != endpoint.address || state.endpoint.protocol != endpoint.protocol {
+                state.retry_at = None;
+            }
+            state.endpoint = endpoint;
+            state.push(record, self.max_backlog);
+        }
+        self.shared.changed.notify_all();
+        self.start_sender()?;
+        self.take_error(&mut self.shared.lock())
+    }
+
+    /// Returns the last send error without waiting; the log worker calls this after every
+    /// batch.
+    fn flush(&self) -> Result<(), LogError> {
+        self.take_error(&mut self.shared.lock())
+    }
+}
+
+/// Where and how messages are sent; a copy of the `NetworkLogger` settings for the sender thread.
+#[derive(Debug, Clone)]
+struct Endpoint {
+    address: String,
+    protocol: NetworkProtocol,
+    app_name: String,
+    hostname: String,
+    facility: u8,
+    max_datagram_size: usize,
+}
+
+impl Default for Endpoint {
+    fn default() -> Self {
+        Self {
+            address: String::new(),
+            protocol: NetworkProtocol::SyslogUdp,
+            app_name: String::new(),
+            hostname: String::new(),
+            facility: DEFAULT_FACILITY,
+            max_datagram_size: DEFAULT_MAX_DATAGRAM_SIZE,
+        }
+    }
+}
+
+impl Endpoint {
+    /// Encodes a message in the wire format of the protocol, including the framing.
+    fn encode(&self, log_message: &LogMessage) -> Vec<u8> {
+        let timestamp = log_message.timestamp.to_rfc3339_opts(SecondsFormat::Millis, false);
+        match self.protocol {
+            NetworkProtocol::SyslogUdp => {
+                let mut message = self.syslog_message(log_message, &timestamp);
+                truncate_at_char_boundary(&mut message, self.max_datagram_size);
+                message.into_bytes()
+            }
+            NetworkProtocol::SyslogTcp => {
+                let message = self.syslog_message(log_message, &timestamp);
+                format!("{} {}", message.len(), message).into_bytes()
+            }
+            NetworkProtocol::JsonTcp => {
+                let mut json = json_object(log_message);
+                json.insert("host".to_string(), self.hostname.clone().into());
+                json.insert("app".to_string(), self.app_name.clone().into());
+                json.insert("pid".to_string(), std::process::id().into());
+                let json = serde_json::Value::Object(json);
+                format!("{}\n", json).into_bytes()
+            }
+        }
+    }
+
+    /// Formats `<PRI>1 TIMESTAMP HOSTNAME APP-NAME PROCID MSGID STRUCTURED-DATA MSG`.
+    fn syslog_message(&self, log_message: &LogMessage, timestamp: &str) -> String {
+        let priority = self.facility as u32 * 8 + syslog_severity(log_message.level) as u32;
+        format!(
+            "<{}>1 {} {} {} {} - - {}",
+            priority,
+            timestamp,
+            syslog_name(&self.hostname, 255),
+            syslog_name(&self.app_name, 48),
+            std::process::id(),
+            log_message.message
+        )
+    }
+
+    fn resolve_address(&self) -> io::Result<SocketAddr> {
+        self.address.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("`{}` did not resolve to an address", self.address))
+        })
+    }
+
+    fn connect(&self) -> io::Result<Socket> {
+        let address = self.resolve_address()?;
+        match self.protocol {
+            NetworkProtocol::SyslogUdp => {
+                let local: SocketAddr = match address {
+                    SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+                    SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+                };
+                let socket = UdpSocket::bind(local)?;
+                socket.set_write_timeout(Some(WRITE_TIMEOUT))?;
+                socket.connect(address)?;
+                Ok(Socket::Udp(socket))
+            }
+            NetworkProtocol::SyslogTcp | NetworkProtocol::JsonTcp => {
+                let stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)?;
+                stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+                stream.set_nodelay(true)?;
+                Ok(Socket::Tcp(stream))
+            }
+        }
+    }
+
+    fn is_same_server(&self, other: &Endpoint) -> bool {
+        self.address == other.address && self.protocol == other.protocol
+    }
+}
+
+enum Socket {
+    Udp(UdpSocket),
+    Tcp(TcpStream),
+}
+
+impl Socket {
+    fn send(&mut self, record: &[u8]) -> io::Result<()> {
+        match self {
+            Socket::Udp(socket) => socket.send(record).map(|_| ()),
+            Socket::Tcp(stream) => stream.write_all(record),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Shared {
+    state: Mutex<State>,
+    /// Signalled when messages are added, sent or the target is dropped.
+    changed: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+}
+
+#[derive(Default)]
+struct State {
+    endpoint: Endpoint,
+    backlog: VecDeque<Vec<u8>>,
+    dropped: u64,
+    backoff: Duration,
+    /// When the sender connects again after a failure.
+    retry_at: Option<Instant>,
+    /// Whether the sender is connecting or sending without holding the lock.
+    sending: bool,
+    last_error: Option<io::Error>,
+    shutdown: bool,
+}
+
+impl State {
+    fn push(&mut self, record: Vec<u8>, max_backlog: usize) {
+        while self.backlog.len() >= max_backlog.max(1) {
+            self.backlog.pop_front();
+            self.dropped += 1;
+        }
+        self.backlog.push_back(record);
+    }
+
+    /// Schedules the next connection attempt.
+    fn fail(&mut self, source: io::Error) {
+        self.backoff = (self.backoff * 2).clamp(MIN_BACKOFF, MAX_BACKOFF);
+        self.retry_at = Some(Instant::now() + self.backoff);
+        self.last_error = Some(source);
+    }
+}
+
+/// The sender thread: sends the backlog one message at a time, connecting first if
+/// necessary, until the `NetworkLogger` is dropped.
+fn send_backlog(shared: Arc<Shared>) {
+    let mut socket: Option<(Socket, Endpoint)> = None;
+    let mut state = shared.lock();
+    while !state.shutdown {
+        if state.backlog.is_empty() {
+            state = shared.changed.wait(state).unwrap();
+            continue;
+        }
+        if let Some(retry_at) = state.retry_at {
+            let now = Instant::now();
+            if now < retry_at {
+                state = shared.changed.wait_timeout(state, retry_at - now).unwrap().0;
+                continue;
+            }
+            state.retry_at = None;
+        }
+
+        if socket.as_ref().is_none_or(|(_, endpoint)| !endpoint.is_same_server(&state.endpoint)) {
+            let endpoint = state.endpoint.clone();
+            state.sending = true;
+            drop(state);
+            let result = endpoint.connect();
+            state = shared.lock();
+            state.sending = false;
+            match result {
+                Ok(connected) => socket = Some((connected, endpoint)),
+                Err(e) => {
+                    socket = None;
+                    state.fail(e);
+                    shared.changed.notify_all();
+                    continue;
+                }
+            }
+            if state.dropped > 0 {
+                let notice = LogMessage::new(
+                    LogLevel::Warn,
+                    format!("{} log messages were dropped while the server was unreachable", state.dropped),
+                );
+                let notice = state.endpoint.encode(&notice);
+                state.backlog.push_front(notice);
+                state.dropped = 0;
+            }
+        }
+
+        let record = state.backlog.pop_front().unwrap();
+        state.sending = true;
+        drop(state);
+        let result = socket.as_mut().unwrap().0.send(&record);
+        state = shared.lock();
+        state.sending = false;
+        match result {
+            Ok(()) => state.backoff = Duration::ZERO,
+            // Sending it again would fail the same way.
+            Err(e) if is_permanent_error(&e) => state.last_error = Some(e),
+            Err(e) => {
+                socket = None;
+                state.backlog.push_front(record);
+                state.fail(e);
+            }
+        }
+        shared.changed.notify_all();
+    }
+}
+
+/// Whether the error is caused by the message itself rather than the connection.
+fn is_permanent_error(error: &io::Error) -> bool {
+    #[cfg(target_os = "linux")]
+    const EMSGSIZE: i32 = 90;
+    #[cfg(windows)]
+    const EMSGSIZE: i32 = 10040;
+    #[cfg(not(any(target_os = "linux", windows)))]
+    const EMSGSIZE: i32 = 40;
+
+    matches!(error.kind(), io::ErrorKind::InvalidInput | io::ErrorKind::InvalidData)
+        || error.raw_os_error() == Some(EMSGSIZE)
+}
+
+/// Shortens `text` to at most `max_len` bytes without splitting a character.
+fn truncate_at_char_boundary(text: &mut String, max_len: usize) {
+    if text.len() > max_len {
+        let mut len = max_len;
+        while !text.is_char_boundary(len) {
+            len -= 1;
+        }
+        text.truncate(len);
+    }
+}
+
+/// Maps the log level to the syslog severity.
+fn syslog_severity(level: LogLevel) -> u8 {
+    match level {
+        LogLevel::Trace | LogLevel::Debug => 7,
+        LogLevel::Info => 6,
+        LogLevel::Warn => 4,
+        LogLevel::Error => 3,
+        LogLevel::Fatal => 2,
+    }
+}
+
+/// Syslog header fields are printable ASCII without spaces, `-` if empty.
+fn syslog_name(name: &str, max_len: usize) -> String {
+    let name: String = name.chars().filter(|c| c.is_ascii_graphic()).take(max_len).collect();
+    if name.is_empty() { "-".to_string() } else { name }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_syslog_over_udp() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        server.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let logger = NetworkLogger::new(server.local_addr().unwrap().to_string(), NetworkProtocol::SyslogUdp)
+            .with_app_name("test app")
+            .with_hostname("host");
+        logger.log(&LogMessage::new(LogLevel::Warn, "low on memory")).unwrap();
+
+        let mut buffer = [0; 1024];
+        let len = server.recv(&mut buffer).unwrap();
+        let datagram = String::from_utf8_lossy(&buffer[..len]);
+        // Facility 1 (user) * 8 + severity 4 (warning)
+        assert!(datagram.starts_with("<12>1 "), "{}", datagram);
+        assert!(datagram.contains(" host testapp "), "{}", datagram);
+        assert!(datagram.ends_with(" - - low on memory"), "{}", datagram);
+    }
+
+    #[test]
+    fn test_syslog_over_tcp_is_octet_counted() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let logger = NetworkLogger::new(listener.local_addr().unwrap().to_string(), NetworkProtocol::SyslogTcp);
+        logger.log(&LogMessage::new(LogLevel::Info, "hello")).unwrap();
+
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut length = Vec::new();
+        reader.read_until(b' ', &mut length).unwrap();
+        let length: usize = String::from_utf8_lossy(&length).trim().parse().unwrap();
+        let mut message = vec![0; length];
+        io::Read::read_exact(&mut reader, &mut message).unwrap();
+        let message = String::from_utf8(message).unwrap();
+        assert!(message.starts_with("<14>1 "), "{}", message);
+        assert!(message.ends_with(" hello"), "{}", message);
+    }
+
+    #[test]
+    fn test_json_lines_over_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let logger = NetworkLogger::new(listener.local_addr().unwrap().to_string(), NetworkProtocol::JsonTcp)
+            .with_app_name("game");
+        logger.log(&LogMessage::new(LogLevel::Error, "first")).unwrap();
+        logger.log(&LogMessage::new(LogLevel::Info, "second").with_field("fps", 60)).unwrap();
+
+        let (stream, _) = listener.accept().unwrap();
+        let mut lines = BufReader::new(stream).lines();
+        let first: serde_json::Value = serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+        assert_eq!(first["level"], "ERROR");
+        assert_eq!(first["message"], "first");
+        assert_eq!(first["app"], "game");
+        let second: serde_json::Value = serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+        assert_eq!(second["message"], "second");
+        assert_eq!(second["fields"]["fps"], "60");
+    }
+
+    #[test]
+    fn test_long_datagrams_are_truncated() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        server.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let logger = NetworkLogger::new(server.local_addr().unwrap().to_string(), NetworkProtocol::SyslogUdp)
+            .with_max_datagram_size(64);
+        logger.log(&LogMessage::new(LogLevel::Info, "é".repeat(100))).unwrap();
+
+        let mut buffer = [0; 1024];
+        let len = server.recv(&mut buffer).unwrap();
+        assert!((63..=64).contains(&len), "{}", len);
+        assert!(std::str::from_utf8(&buffer[..len]).is_ok());
+    }
+
+    #[test]
+    fn test_backlog_is_capped_and_sent_after_reconnect() {
+        // Reserve a port without listening on it.
+        let address = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let logger = NetworkLogger::new(address.to_string(), NetworkProtocol::JsonTcp).with_max_backlog(2);
+        let result = logger.log(&LogMessage::new(LogLevel::Info, "lost"));
+        logger.wait_for_backlog();
+        assert!(result.and(logger.flush()).is_err());
+        // Keep the sender waiting while the backlog fills up.
+        logger.shared.lock().retry_at = Some(Instant::now() + Duration::from_secs(60));
+        logger.log(&LogMessage::new(LogLevel::Info, "kept 1")).unwrap();
+        logger.log(&LogMessage::new(LogLevel::Info, "kept 2")).unwrap();
+        assert_eq!(logger.backlog_len(), 2);
+
+        let listener = TcpListener::bind(address).unwrap();
+        logger.shared.lock().retry_at = None;
+        logger.shared.changed.notify_all();
+        logger.wait_for_backlog();
+        logger.flush().unwrap();
+        assert_eq!(logger.backlog_len(), 0);
+
+        let (stream, _) = listener.accept().unwrap();
+        let messages: Vec<String> = BufReader::new(stream)
+            .lines()
+            .take(3)
+            .map(|line| serde_json::from_str::<serde_json::Value>(&line.unwrap()).unwrap()["message"].to_string())
+            .collect();
+        assert!(messages[0].contains("1 log messages were dropped"), "{:?}", messages);
+        assert_eq!(messages[1..], ["\"kept 1\"", "\"kept 2\""]);
+    }
 }
@@ -1,4 +1,21 @@
 
+/// Builds a `LogMessage` with the location of the caller and queues it. The `key = value,`
+/// pairs in front of the format arguments are collected into the square brackets first.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_message {
+    ($level:expr, [$($fields:tt)*] $key:ident = $value:expr, $($rest:tt)+) => {
+        $crate::__log_message!($level, [$($fields)* ($key, $value)] $($rest)+)
+    };
+    ($level:expr, [$(($key:ident, $value:expr))*] $($arg:tt)+) => {
+        $crate::core::diego_runtime::push_log_message(
+            $crate::logging::log_manager::LogMessage::new($level, format!($($arg)+))
+                .with_location(Some(module_path!().to_string()), Some(file!().to_string()), Some(line!()))
+                $(.with_field(stringify!($key), $value))*
+        )
+    };
+}
+
 /// Logs a message with `LogLevel::Trace`.
 ///
 /// This macro allows you to log debug-level messages, which are typically used
@@ -8,6 +25,8 @@
 /// # Example
 ///
 /// ```no_run
+/// # use diego::log_trace;
+/// # let value = 42;
 /// log_trace!("Debugging value: {}", value);
 /// ```
 ///
@@ -17,7 +36,7 @@
 #[macro_export]
 macro_rules! log_trace {
     ($($arg:tt)*) => ({
-        $crate::core::diego_runtime::add_log_message($crate::logging::LogLevel::Trace, format!($($arg)*));
+        $crate::__log_message!($crate::logging::LogLevel::Trace, [] $($arg)*);
     })
 }
 
@@ -30,6 +49,8 @@ macro_rules! log_trace {
 /// # Example
 ///
 /// ```no_run
+/// # use diego::log_debug;
+/// # let value = 42;
 /// log_debug!("Debugging value: {}", value);
 /// ```
 ///
@@ -39,7 +60,7 @@ macro_rules! log_trace {
 #[macro_export]
 macro_rules! log_debug {
     ($($arg:tt)*) => ({
-        $crate::core::diego_runtime::add_log_message($crate::logging::LogLevel::Debug, format!($($arg)*));
+        $crate::__log_message!($crate::logging::LogLevel::Debug, [] $($arg)*);
     })
 }
 
@@ -52,20 +73,31 @@ macro_rules! log_debug {
 /// # Example
 ///
 /// ```no_run
+/// # use diego::log_info;
+/// # let timestamp = 42;
 /// log_info!("Application started at: {}", timestamp);
 /// ```
 ///
 /// This will log the message "Application started at: {timestamp}" at the info level.
 /// The message is processed by the logging system and sent to the appropriate
 /// log targets.
+///
+/// Like all `log_*!` macros, it records the module, file and line it is called from, and
+/// accepts `key = value` fields in front of the message. The values must implement `Display`.
+///
+/// ```no_run
+/// # use diego::log_info;
+/// # let frame_time = 16.6;
+/// log_info!(fps = 60, frame_time = frame_time, "Frame finished");
+/// ```
 #[macro_export]
 macro_rules! log_info {
     ($($arg:tt)*) => ({
-        $crate::core::diego_runtime::add_log_message($crate::logging::LogLevel::Info, format!($($arg)*));
+        $crate::__log_message!($crate::logging::LogLevel::Info, [] $($arg)*);
     })
 }
 
-/// Logs a warning-level message using the `add_log_message` function
+/// Logs a warning-level message using the `push_log_message` function
 /// within the Diego runtime.
 ///
 /// This macro accepts any number of arguments that can be formatted into a string,
@@ -74,10 +106,12 @@ macro_rules! log_info {
 /// # Example
 ///
 /// ```no_run
+/// # use diego::log_warn;
+/// # let some_variable = 42;
 /// log_warn!("This is a warning message: {}", some_variable);
 /// ```
 ///
-/// The message will be passed to the `add_log_message` function in the `diego_runtime` module,
+/// The message will be passed to the `push_log_message` function in the `diego_runtime` module,
 /// with a log level of `Warn`.
 ///
 /// # Parameters
@@ -86,11 +120,11 @@ macro_rules! log_info {
 #[macro_export]
 macro_rules! log_warn {
     ($($arg:tt)*) => ({
-        $crate::core::diego_runtime::add_log_message($crate::logging::LogLevel::Warn, format!($($arg)*));
+        $crate::__log_message!($crate::logging::LogLevel::Warn, [] $($arg)*);
     })
 }
 
-/// Logs an error-level message using the `add_log_message` function within the Diego runtime.
+/// Logs an error-level message using the `push_log_message` function within the Diego runtime.
 ///
 /// This macro accepts any number of arguments that can be formatted into a string,
 /// similar to the `format!` macro.
@@ -98,23 +132,25 @@ macro_rules! log_warn {
 /// # Example
 ///
 /// ```no_run
+/// # use diego::log_error;
+/// # let some_variable = 42;
 /// log_error!("This is an error message: {}", some_variable);
 /// ```
 ///
-/// The message will be passed to the `add_log_message` function in the `diego_runtime` module,
+/// The message will be passed to the `push_log_message` function in the `diego_runtime` module,
 /// with a log level of `Error`.
 ///
 /// # Parameters
 /// * `$($arg:tt)*`: A variadic argument list, similar to the standard `println!` macro, which
-/// allows formatting of the message string.
+///   allows formatting of the message string.
 #[macro_export]
 macro_rules! log_error {
     ($($arg:tt)*) => ({
-        $crate::core::diego_runtime::add_log_message($crate::logging::LogLevel::Error, format!($($arg)*));
+        $crate::__log_message!($crate::logging::LogLevel::Error, [] $($arg)*);
     })
 }
 
-/// Logs a fatal-level message using the `add_log_message` function within the Diego runtime.
+/// Logs a fatal-level message using the `push_log_message` function within the Diego runtime.
 ///
 /// This macro accepts any number of arguments that can be formatted into a string,
 /// similar to the `format!` macro. Fatal logs indicate critical issues that may cause the
@@ -123,10 +159,12 @@ macro_rules! log_error {
 /// # Example
 ///
 /// ```no_run
+/// # use diego::log_fatal;
+/// # let some_variable = 42;
 /// log_fatal!("This is a fatal error: {}", some_variable);
 /// ```
 ///
-/// The message will be passed to the `add_log_message` function in the `diego_runtime` module,
+/// The message will be passed to the `push_log_message` function in the `diego_runtime` module,
 /// with a log level of `Fatal`.
 ///
 /// # Parameters
@@ -135,6 +173,6 @@ macro_rules! log_error {
 #[macro_export]
 macro_rules! log_fatal {
     ($($arg:tt)*) => ({
-        $crate::core::diego_runtime::add_log_message($crate::logging::LogLevel::Fatal, format!($($arg)*));
+        $crate::__log_message!($crate::logging::LogLevel::Fatal, [] $($arg)*);
     })
 }
@@ -1,12 +1,12 @@
-use crate::glx::vertex_attribute::VertexAttribute;
-use crate::glx::vertex_attribute_type::VertexAttributeType;
 use crate::glx::vertex_layout::VertexLayout;
 use crate::vertex::VertexEq;
+use bytemuck::{Pod, Zeroable};
 
 #[repr(C)]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable, VertexLayout)]
 pub struct TexturedVertex {
     pub position: [f32; 3],   // XYZ coordinates
+    #[vertex(name = "tex_coord")]
     pub tex_coords: [f32; 2], // UV texture coordinates
     pub color: [f32; 4],      // color of the vertex
 }
@@ -21,16 +21,6 @@ impl Default for TexturedVertex {
     }
 }
 
-impl VertexLayout for TexturedVertex {
-    fn attributes() -> Vec<VertexAttribute> {
-        vec![
-            VertexAttributeType::Position.into(),
-            VertexAttributeType::TexCoords.into(),
-            VertexAttributeType::Color.into(),
-        ]
-    }
-}
-
 impl VertexEq<TexturedVertex> for TexturedVertex {
     fn is_similar(&self, other: &TexturedVertex, tolerance: f32) -> bool {
         self.position
@@ -59,3 +49,21 @@ impl TexturedVertex {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::glx::vertex_data_type::VertexDataType;
+
+    #[test]
+    fn test_derived_layout() {
+        let attributes = TexturedVertex::attributes();
+        let names: Vec<_> = attributes.iter().map(|a| a.name.as_deref().unwrap()).collect();
+        assert_eq!(names, ["position", "tex_coord", "color"]);
+
+        let offsets: Vec<_> = attributes.iter().map(|a| a.offset.unwrap()).collect();
+        assert_eq!(offsets, [0, 12, 20]);
+        assert!(attributes.iter().all(|a| a.stride == 36 && a.data_type == VertexDataType::Float));
+        assert_eq!(TexturedVertex::layout_size(), 36);
+    }
+}